@@ -0,0 +1,463 @@
+use super::scalar::Vars;
+use super::*;
+
+// Translation of a well typed program into a standalone C99 program. Every
+// procedure becomes a C function and the main program becomes main. The
+// variables of a procedure are declared at the start of its function,
+// since a variable declared in a branch is visible after it. Ints are
+// int32_t, a result of + or * which is no int32_t is a runtime error as in
+// exec. Bools are bool.
+//
+// C does not fix the order in which operands are evaluated, so calls are
+// moved into temporaries in the order exec evaluates them, and so is an
+// operand with + or * if a later one may overflow or has a call. What is
+// left of an expression has no effects other than an overflow, which the
+// operands of && and || must not skip, so they become & and | then.
+//
+// Runtime errors print the message of exec to stderr and exit with 1. The
+// functions of the runtime are inline, so that C compilers do not warn
+// about those a program does not use.
+// Read statements take the command-line arguments, or the lines of stdin
+// if there are none. Only ints and bools are translated.
+
+// Variables are v_x, procedures f_p and temporaries t1, t2, .. which
+// keeps them apart from each other, from C keywords and from the runtime
+pub fn compile(stmt: &Stmt, procs: &[Proc]) -> Result<String, String> {
+    let mut out = String::from(RUNTIME);
+    for p in procs {
+        out.push_str(&format!("static {};\n", Gen::header(p)?));
+    }
+    for p in procs {
+        let mut g = Gen::new(procs, &p.params);
+        g.line("imp_enter();");
+        g.stmt(&p.body)?;
+        g.line(&format!("imp_no_return(\"{}\");", p.name));
+        g.line(&format!(
+            "return {};",
+            if p.ret == Type::TyBool { "false" } else { "0" }
+        ));
+        out.push_str(&format!("\nstatic {} {{\n", Gen::header(p)?));
+        out.push_str(&g.locals()?);
+        out.push_str(&g.out);
+        out.push_str("}\n");
+    }
+    let mut g = Gen::new(procs, &[]);
+    g.stmt(stmt)?;
+    g.line("return 0;");
+    out.push_str("\nint main(int argc, char **argv) {\n");
+    out.push_str("    imp_args = argv + 1;\n");
+    out.push_str("    imp_from_args = argc > 1;\n");
+    out.push_str(&g.locals()?);
+    out.push_str(&g.out);
+    out.push_str("}\n");
+    Ok(out)
+}
+
+const RUNTIME: &str = r#"#include <stdbool.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <ctype.h>
+
+static char **imp_args;
+static bool imp_from_args;
+static int imp_depth;
+
+static inline void imp_error(const char *msg) {
+    fprintf(stderr, "%s\n", msg);
+    exit(1);
+}
+
+static inline int32_t imp_check(int64_t x, int32_t a, const char *op, int32_t b) {
+    if (x < INT32_MIN || x > INT32_MAX) {
+        fprintf(stderr, "integer overflow in %ld %s %ld\n", (long)a, op, (long)b);
+        exit(1);
+    }
+    return (int32_t)x;
+}
+
+static inline int32_t imp_add(int32_t a, int32_t b) {
+    return imp_check((int64_t)a + b, a, "+", b);
+}
+
+static inline int32_t imp_mul(int32_t a, int32_t b) {
+    return imp_check((int64_t)a * b, a, "*", b);
+}
+
+static inline const char *imp_bool(bool b) {
+    return b ? "true" : "false";
+}
+
+static inline void imp_print_int(int32_t x) {
+    printf("Ouput %d\n", (int)x);
+}
+
+static inline void imp_print_bool(bool b) {
+    printf("Output %s\n", imp_bool(b));
+}
+
+static inline void imp_enter(void) {
+    if (imp_depth == 1000) {
+        imp_error("stack overflow after 1000 nested calls");
+    }
+    imp_depth++;
+}
+
+static inline void imp_no_return(const char *name) {
+    fprintf(stderr, "procedure %s ended without return\n", name);
+    exit(1);
+}
+
+/* the next argument or line of stdin, trimmed */
+static inline char *imp_item(void) {
+    static char line[4096];
+    char *x;
+    size_t n;
+    if (imp_from_args) {
+        if (*imp_args == NULL) {
+            imp_error("no more input to read");
+        }
+        x = *imp_args++;
+    } else {
+        if (fgets(line, sizeof line, stdin) == NULL) {
+            imp_error("no more input to read");
+        }
+        line[strcspn(line, "\r\n")] = 0;
+        x = line;
+    }
+    while (isspace((unsigned char)*x)) {
+        x++;
+    }
+    n = strlen(x);
+    while (n > 0 && isspace((unsigned char)x[n - 1])) {
+        n--;
+    }
+    x[n] = 0;
+    return x;
+}
+
+static inline void imp_bad_input(const char *x, const char *ty) {
+    fprintf(stderr, "cannot read \"%s\" as %s\n", x, ty);
+    exit(1);
+}
+
+static inline int32_t imp_read_int(void) {
+    char *x = imp_item();
+    char *end;
+    long long n;
+    const char *d = x + (*x == '+' || *x == '-');
+    if (!isdigit((unsigned char)*d)) {
+        imp_bad_input(x, "int");
+    }
+    n = strtoll(x, &end, 10);
+    if (*end != 0 || n < INT32_MIN || n > INT32_MAX) {
+        imp_bad_input(x, "int");
+    }
+    return (int32_t)n;
+}
+
+static inline bool imp_read_bool(void) {
+    char *x = imp_item();
+    if (strcmp(x, "true") != 0 && strcmp(x, "false") != 0) {
+        imp_bad_input(x, "bool");
+    }
+    return x[0] == 't';
+}
+"#;
+
+struct Gen<'a> {
+    vars: Vars<'a>,
+    out: String,
+    indent: usize,
+    temps: usize,
+    // the label of the step of each enclosing loop, if it has one
+    loops: Vec<Option<String>>,
+    labels: usize,
+}
+
+impl<'a> Gen<'a> {
+    fn new(procs: &'a [Proc], params: &[(String, Type)]) -> Gen<'a> {
+        Gen {
+            vars: Vars::new(procs, params, "C"),
+            out: String::new(),
+            indent: 1,
+            temps: 0,
+            loops: Vec::new(),
+            labels: 0,
+        }
+    }
+    fn header(p: &Proc) -> Result<String, String> {
+        let mut params = Vec::new();
+        for (x, ty) in &p.params {
+            params.push(format!("{} v_{}", c_type(ty)?, x));
+        }
+        if params.is_empty() {
+            params.push("void".to_string());
+        }
+        Ok(format!(
+            "{} f_{}({})",
+            c_type(&p.ret)?,
+            p.name,
+            params.join(", ")
+        ))
+    }
+    fn line(&mut self, x: &str) {
+        self.out.push_str(&"    ".repeat(self.indent));
+        self.out.push_str(x);
+        self.out.push('\n');
+    }
+    // The declarations of the variables which are not parameters
+    fn locals(&self) -> Result<String, String> {
+        let mut decls = String::new();
+        for (x, ty) in self.vars.locals() {
+            let init = if *ty == Type::TyBool { "false" } else { "0" };
+            decls.push_str(&format!("    {} v_{} = {};\n", c_type(ty)?, x, init));
+        }
+        Ok(decls)
+    }
+    fn stmt(&mut self, stmt: &Stmt) -> Result<(), String> {
+        match stmt {
+            Stmt::Skip | Stmt::Assume { .. } => {}
+            Stmt::Seq { first, second } => {
+                self.stmt(first)?;
+                self.stmt(second)?;
+            }
+            Stmt::Decl { lhs, ty, rhs } => {
+                let ty = match ty {
+                    Some(ty) => ty.clone(),
+                    None => self.vars.ty(rhs)?,
+                };
+                let x = self.exp(rhs)?;
+                self.vars.declare(lhs, ty)?;
+                self.line(&format!("v_{} = {};", lhs, x));
+            }
+            Stmt::Assign { lhs, rhs } => {
+                let x = self.exp(rhs)?;
+                self.line(&format!("v_{} = {};", lhs, x));
+            }
+            Stmt::Read { lhs, ty } => {
+                self.vars.declare(lhs, ty.clone())?;
+                let f = if *ty == Type::TyBool { "bool" } else { "int" };
+                self.line(&format!("v_{} = imp_read_{}();", lhs, f));
+            }
+            Stmt::Print { print_exp } => {
+                let f = if self.vars.ty(print_exp)? == Type::TyBool {
+                    "bool"
+                } else {
+                    "int"
+                };
+                let x = self.exp(print_exp)?;
+                self.line(&format!("imp_print_{}({});", f, x));
+            }
+            Stmt::Assert { cond } => {
+                let c = self.exp(cond)?;
+                let mut format = String::from("assertion failed: %s");
+                let mut args = vec![quote(&cond.pretty())];
+                let vars: Vec<String> = cond
+                    .vars()
+                    .into_iter()
+                    .filter(|x| self.vars.contains(x))
+                    .collect();
+                for (k, x) in vars.iter().enumerate() {
+                    format.push_str(if k == 0 { " with " } else { ", " });
+                    if self.vars.ty(&Exp::Var { name: x.clone() })? == Type::TyBool {
+                        format.push_str(&format!("{} = %s", x));
+                        args.push(format!("imp_bool(v_{})", x));
+                    } else {
+                        format.push_str(&format!("{} = %d", x));
+                        args.push(format!("(int)v_{}", x));
+                    }
+                }
+                self.line(&format!("if (!{}) {{", c));
+                self.indent += 1;
+                self.line(&format!(
+                    "fprintf(stderr, {}, {});",
+                    quote(&(format + "\n")),
+                    args.join(", ")
+                ));
+                self.line("exit(1);");
+                self.indent -= 1;
+                self.line("}");
+            }
+            Stmt::Return { exp } => {
+                let x = self.exp(exp)?;
+                self.line("imp_depth--;");
+                self.line(&format!("return {};", x));
+            }
+            Stmt::Break => self.line("break;"),
+            Stmt::Continue => match self.loops.last().cloned().flatten() {
+                Some(label) => self.line(&format!("goto {};", label)),
+                None => self.line("continue;"),
+            },
+            Stmt::IfThenElse {
+                cond,
+                then_stmt,
+                else_stmt,
+            } => {
+                let c = self.exp(cond)?;
+                self.line(&format!("if ({}) {{", strip(&c)));
+                self.block(then_stmt)?;
+                if let Stmt::Skip = **else_stmt {
+                    self.line("}");
+                } else {
+                    self.line("} else {");
+                    self.block(else_stmt)?;
+                    self.line("}");
+                }
+            }
+            Stmt::While { cond, stmt, .. } => {
+                // a condition with temporaries is evaluated at the start of
+                // the body
+                let hoisted = self.hoists(cond);
+                if hoisted {
+                    self.line("while (1) {");
+                    self.indent += 1;
+                    let c = self.exp(cond)?;
+                    self.line(&format!("if (!{}) {{", c));
+                    self.line("    break;");
+                    self.line("}");
+                    self.indent -= 1;
+                } else {
+                    let c = self.exp(cond)?;
+                    self.line(&format!("while ({}) {{", strip(&c)));
+                }
+                self.loops.push(None);
+                self.block(stmt)?;
+                self.loops.pop();
+                self.line("}");
+            }
+            // continue in body jumps to the label before step
+            Stmt::Step { body, step } => {
+                self.labels += 1;
+                let label = format!("step{}", self.labels);
+                *self.loops.last_mut().unwrap() = Some(label.clone());
+                self.stmt(body)?;
+                self.line(&format!("{}:;", label));
+                self.stmt(step)?;
+            }
+            Stmt::ArrayAssign { .. } | Stmt::FieldAssign { .. } => {
+                return Err(self.vars.unsupported(&stmt.pretty()))
+            }
+        }
+        Ok(())
+    }
+    fn block(&mut self, stmt: &Stmt) -> Result<(), String> {
+        self.indent += 1;
+        self.stmt(stmt)?;
+        self.indent -= 1;
+        Ok(())
+    }
+    // A C expression without effects for e, the calls in e are assigned to
+    // temporaries before
+    fn exp(&mut self, e: &Exp) -> Result<String, String> {
+        if let Some((op, left, right)) = e.binary() {
+            let mut l = self.exp(left)?;
+            if overflows(left) && (overflows(right) || self.vars.has_call(right)) {
+                l = self.temp(left, &l)?;
+            }
+            let r = self.exp(right)?;
+            return Ok(match op {
+                BinOp::Plus => format!("imp_add({}, {})", strip(&l), strip(&r)),
+                BinOp::Mult => format!("imp_mul({}, {})", strip(&l), strip(&r)),
+                BinOp::And if overflows(right) => format!("({} & {})", l, r),
+                BinOp::Or if overflows(right) => format!("({} | {})", l, r),
+                _ => format!("({} {} {})", l, op.symbol(), r),
+            });
+        }
+        match e {
+            Exp::Num { val } if *val < 0 => Ok(format!("({})", val)),
+            Exp::Num { val } => Ok(val.to_string()),
+            Exp::Bool { val } => Ok(val.to_string()),
+            Exp::Var { name } => {
+                self.vars.ty(e)?;
+                Ok(format!("v_{}", name))
+            }
+            Exp::Grp { exp } => self.exp(exp),
+            Exp::Neg { exp } => Ok(format!("!{}", self.exp(exp)?)),
+            Exp::Call { name, args } => {
+                let ty = c_type(&self.vars.ty(e)?)?;
+                let mut xs = Vec::new();
+                for (k, a) in args.iter().enumerate() {
+                    let mut x = self.exp(a)?;
+                    let later = &args[k + 1..];
+                    if overflows(a) && later.iter().any(|b| overflows(b) || self.vars.has_call(b)) {
+                        x = self.temp(a, &x)?;
+                    }
+                    xs.push(strip(&x).to_string());
+                }
+                self.temps += 1;
+                let t = format!("t{}", self.temps);
+                self.line(&format!("{} {} = f_{}({});", ty, t, name, xs.join(", ")));
+                Ok(t)
+            }
+            _ => Err(self.vars.unsupported(&e.pretty())),
+        }
+    }
+    // A temporary with the value x of e
+    fn temp(&mut self, e: &Exp, x: &str) -> Result<String, String> {
+        let ty = c_type(&self.vars.ty(e)?)?;
+        self.temps += 1;
+        let t = format!("t{}", self.temps);
+        self.line(&format!("{} {} = {};", ty, t, strip(x)));
+        Ok(t)
+    }
+    // Whether the temporaries of e are assigned before the expression
+    fn hoists(&self, e: &Exp) -> bool {
+        if self.vars.has_call(e) {
+            return true;
+        }
+        match e {
+            Exp::Grp { exp } | Exp::Neg { exp } => self.hoists(exp),
+            _ => match e.binary() {
+                Some((_, l, r)) => {
+                    (overflows(l) && overflows(r)) || self.hoists(l) || self.hoists(r)
+                }
+                None => false,
+            },
+        }
+    }
+}
+
+// Whether the C expression for e may overflow, calls are temporaries
+fn overflows(e: &Exp) -> bool {
+    match e {
+        Exp::Plus { .. } | Exp::Mult { .. } => true,
+        Exp::Grp { exp } | Exp::Neg { exp } => overflows(exp),
+        _ => e
+            .binary()
+            .is_some_and(|(_, l, r)| overflows(l) || overflows(r)),
+    }
+}
+
+// e without the parentheses around it
+fn strip(e: &str) -> &str {
+    match e.strip_prefix('(').and_then(|x| x.strip_suffix(')')) {
+        Some(x) if balanced(x) => x,
+        _ => e,
+    }
+}
+
+fn balanced(x: &str) -> bool {
+    let mut open = 0;
+    for c in x.chars() {
+        match c {
+            '(' => open += 1,
+            ')' if open == 0 => return false,
+            ')' => open -= 1,
+            _ => {}
+        }
+    }
+    open == 0
+}
+
+fn c_type(ty: &Type) -> Result<&'static str, String> {
+    match ty {
+        Type::TyInt => Ok("int32_t"),
+        Type::TyBool => Ok("bool"),
+        _ => Err(format!(
+            "values of type {} are not supported by the C backend",
+            ty
+        )),
+    }
+}
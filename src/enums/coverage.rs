@@ -0,0 +1,209 @@
+use super::parser::Pos;
+use super::*;
+
+// Statement and branch coverage of an execution, collected with a Hook.
+// The hook is called before every statement and additionally before every
+// further evaluation of a While condition, so the number of executions of a
+// While is its number of calls minus the iterations of its body plus the
+// iterations left by break.
+
+pub struct Coverage {
+    // calls of the hook for each statement index
+    pub hits: Vec<usize>,
+}
+
+impl Hook for Coverage {
+    fn before(&mut self, idx: usize, _: usize, _: &Stmt, _: &ValState) -> Result<(), RuntimeError> {
+        self.hits[idx] += 1;
+        Ok(())
+    }
+}
+
+// The main program and the procedure bodies with the index of their first
+// statement
+fn bodies<'a>(stmt: &'a Stmt, procs: &'a [Proc]) -> Vec<(usize, &'a Stmt)> {
+    let mut out = vec![(0, stmt)];
+    out.extend(procs.iter().map(|p| (p.start, &*p.body)));
+    out
+}
+
+// Every statement of the program with its index
+fn stmts<'a>(stmt: &'a Stmt, procs: &'a [Proc]) -> Vec<(usize, &'a Stmt)> {
+    let mut out = Vec::new();
+    for (start, body) in bodies(stmt, procs) {
+        let mut i = start;
+        body.visit(&mut i, &mut |i, s| out.push((i, s)));
+    }
+    out
+}
+
+impl Coverage {
+    pub fn new(stmt: &Stmt, procs: &[Proc]) -> Coverage {
+        let size: usize = bodies(stmt, procs).iter().map(|(_, s)| s.size()).sum();
+        Coverage {
+            hits: vec![0; size],
+        }
+    }
+    // Number of executions of each statement
+    pub fn counts(&self, stmt: &Stmt, procs: &[Proc]) -> Vec<usize> {
+        let stmts = stmts(stmt, procs);
+        let mut counts = self.hits.clone();
+        // the body of a While at i starts at i + 1
+        for (i, s) in stmts.into_iter().rev() {
+            if let Stmt::While { stmt, .. } = s {
+                counts[i] -= counts[i + 1];
+                for (j, b) in stmt.jumps(i + 1) {
+                    if let Stmt::Break = b {
+                        counts[i] += counts[j];
+                    }
+                }
+            }
+        }
+        counts
+    }
+}
+
+// Branches of stmt as (statement index, taken counts); IfThenElse has the
+// branches then and else, While the body and the exit
+fn branches(stmt: &Stmt, procs: &[Proc], counts: &[usize]) -> Vec<(usize, Vec<usize>)> {
+    let mut out = Vec::new();
+    for (i, s) in stmts(stmt, procs) {
+        match s {
+            Stmt::IfThenElse { then_stmt, .. } => {
+                out.push((i, vec![counts[i + 1], counts[i + 1 + then_stmt.size()]]));
+            }
+            Stmt::While { .. } => out.push((i, vec![counts[i + 1], counts[i]])),
+            _ => {}
+        }
+    }
+    out
+}
+
+struct Report<'a> {
+    counts: &'a [usize],
+    // count column and source text of each line
+    lines: Vec<(String, String)>,
+}
+
+impl Report<'_> {
+    fn count(&self, idx: usize) -> String {
+        match self.counts[idx] {
+            0 => "#####".to_string(),
+            n => n.to_string(),
+        }
+    }
+    fn line(&mut self, col: String, depth: usize, text: String) {
+        self.lines
+            .push((col, format!("{}{}", "  ".repeat(depth), text)));
+    }
+    fn stmt(&mut self, s: &Stmt, idx: usize, depth: usize) {
+        match s {
+            Stmt::Seq { first, second } => {
+                self.stmt(first, idx, depth);
+                self.lines.last_mut().unwrap().1.push(';');
+                self.stmt(second, idx + first.size(), depth);
+            }
+            Stmt::IfThenElse {
+                cond,
+                then_stmt,
+                else_stmt,
+            } => {
+                let else_idx = idx + 1 + then_stmt.size();
+                let text = format!(
+                    "if {} {{  // then: {}, else: {}",
+                    cond.pretty(),
+                    self.counts[idx + 1],
+                    self.counts[else_idx]
+                );
+                self.line(self.count(idx), depth, text);
+                self.stmt(then_stmt, idx + 1, depth + 1);
+                self.line(String::new(), depth, "} else {".to_string());
+                self.stmt(else_stmt, else_idx, depth + 1);
+                self.line(String::new(), depth, "}".to_string());
+            }
+            Stmt::While { cond, inv, stmt } => {
+                let mut text = if s.is_for() {
+                    for_header(None, s)
+                } else {
+                    format!("while {}", cond.pretty())
+                };
+                if let (Some(inv), false) = (inv, s.is_for()) {
+                    text.push_str(&format!(" invariant {}", inv.pretty()));
+                }
+                text.push_str(&format!(" {{  // body: {}", self.counts[idx + 1]));
+                self.line(self.count(idx), depth, text);
+                self.stmt(stmt, idx + 1, depth + 1);
+                self.line(String::new(), depth, "}".to_string());
+            }
+            // the step is part of the for header
+            Stmt::Step { body, .. } => self.stmt(body, idx, depth),
+            s => self.line(self.count(idx), depth, s.pretty()),
+        }
+    }
+}
+
+// The pretty printed program with the number of executions of each
+// statement, ##### marks statements which never ran
+pub fn report(stmt: &Stmt, procs: &[Proc], counts: &[usize]) -> String {
+    let mut r = Report {
+        counts,
+        lines: Vec::new(),
+    };
+    for p in procs {
+        r.line(String::new(), 0, format!("{} {{", p.header()));
+        r.stmt(&p.body, p.start, 1);
+        r.line(String::new(), 0, "}".to_string());
+    }
+    r.stmt(stmt, 0, 0);
+    let lines: Vec<String> = r
+        .lines
+        .iter()
+        .map(|(col, text)| format!("{:>7} | {}", col, text))
+        .collect();
+    lines.join("\n") + "\n"
+}
+
+// Coverage in the lcov tracefile format; lines with several statements
+// get the highest count among them
+pub fn lcov(stmt: &Stmt, procs: &[Proc], pos: &[Pos], source: &str, counts: &[usize]) -> String {
+    let mut out = vec!["TN:".to_string(), format!("SF:{}", source)];
+    let bs = branches(stmt, procs, counts);
+    let mut taken = 0;
+    let mut found = 0;
+    for (i, b) in &bs {
+        for (k, n) in b.iter().enumerate() {
+            // "-" if the statement itself never ran
+            let n = if counts[*i] == 0 {
+                "-".to_string()
+            } else {
+                n.to_string()
+            };
+            out.push(format!("BRDA:{},{},{},{}", pos[*i].line, i, k, n));
+            found += 1;
+            if n != "-" && n != "0" {
+                taken += 1;
+            }
+        }
+    }
+    out.push(format!("BRF:{}", found));
+    out.push(format!("BRH:{}", taken));
+    let mut lines = std::collections::BTreeMap::new();
+    for (i, n) in counts.iter().enumerate() {
+        let c = lines.entry(pos[i].line).or_insert(0);
+        *c = (*c).max(*n);
+    }
+    for (line, n) in &lines {
+        out.push(format!("DA:{},{}", line, n));
+    }
+    out.push(format!("LF:{}", lines.len()));
+    out.push(format!("LH:{}", lines.values().filter(|n| **n > 0).count()));
+    out.push("end_of_record".to_string());
+    out.join("\n") + "\n"
+}
+
+// Runs stmt in an empty state and records its coverage
+pub fn run(stmt: &Stmt, procs: &[Proc], mut s: ValState) -> (Coverage, Result<(), RuntimeError>) {
+    let mut c = Coverage::new(stmt, procs);
+    let r = exec_hooked(stmt, &mut s, &mut c);
+    (c, r)
+}
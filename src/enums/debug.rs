@@ -0,0 +1,199 @@
+use super::parser::Pos;
+use super::*;
+use std::collections::BTreeSet;
+use std::io::{BufRead, Write};
+
+// A command line debugger built on the Hook of the interpreter. Execution
+// stops before the first statement, at breakpoints and when a watched
+// variable has changed.
+
+const HELP: &str = "commands:
+  step, s             run to the next statement
+  next, n             run to the next statement outside of the current one
+  continue, c         run to the next breakpoint or watchpoint
+  break, b LINE|#IDX  stop before the statement on LINE or with index IDX
+  delete, d LINE|#IDX remove a breakpoint
+  watch, w VAR        stop when VAR changes
+  print, p [VAR]      show VAR or all variables
+  quit, q             stop the program";
+
+#[derive(PartialEq)]
+enum Mode {
+    Step,
+    // index and depth of the current statement: stop at the next other
+    // statement with at most this depth, so a loop is run to its end
+    Next(usize, usize),
+    Continue,
+    // the input has ended, never stop again
+    Run,
+}
+
+pub struct Debugger<'a, R: BufRead, W: Write> {
+    pos: &'a [Pos],
+    input: R,
+    out: W,
+    mode: Mode,
+    breakpoints: BTreeSet<usize>,
+    // watched variables with the value they had when last checked
+    watches: Vec<(String, String)>,
+}
+
+fn show_var(s: &ValState, x: &str) -> String {
+    match s.get(x) {
+        Some(v) => v.to_string(),
+        None => "undeclared".to_string(),
+    }
+}
+
+impl<'a, R: BufRead, W: Write> Debugger<'a, R, W> {
+    pub fn new(pos: &'a [Pos], input: R, out: W) -> Self {
+        Debugger {
+            pos,
+            input,
+            out,
+            mode: Mode::Step,
+            breakpoints: BTreeSet::new(),
+            watches: Vec::new(),
+        }
+    }
+    fn show_idx(&self, idx: usize) -> String {
+        let p = self.pos[idx];
+        format!("{}:{} [#{}]", p.line, p.col, idx)
+    }
+    // Statement index from "#IDX" or the first statement on line "LINE"
+    fn location(&self, arg: &str) -> Result<usize, String> {
+        if let Some(idx) = arg.strip_prefix('#') {
+            return match idx.parse() {
+                Ok(i) if i < self.pos.len() => Ok(i),
+                _ => Err(format!("no statement #{}", idx)),
+            };
+        }
+        let line: usize = arg
+            .parse()
+            .map_err(|_| format!("expected LINE or #IDX, found '{}'", arg))?;
+        self.pos
+            .iter()
+            .position(|p| p.line == line)
+            .ok_or(format!("no statement on line {}", line))
+    }
+    // Reports the watched variables which changed; true if there are any
+    fn check_watches(&mut self, s: &ValState) -> bool {
+        let mut changed = false;
+        for (x, last) in self.watches.iter_mut() {
+            let v = show_var(s, x);
+            if v != *last {
+                writeln!(self.out, "watch {}: {} -> {}", x, last, v).unwrap();
+                *last = v;
+                changed = true;
+            }
+        }
+        changed
+    }
+    // Reads commands until one of them resumes execution
+    fn prompt(&mut self, idx: usize, depth: usize, s: &ValState) -> Result<(), RuntimeError> {
+        loop {
+            write!(self.out, "(imp) ").unwrap();
+            self.out.flush().unwrap();
+            let mut line = String::new();
+            if self.input.read_line(&mut line).unwrap_or(0) == 0 {
+                writeln!(self.out).unwrap();
+                self.mode = Mode::Run;
+                return Ok(());
+            }
+            let words: Vec<&str> = line.split_whitespace().collect();
+            match words.as_slice() {
+                [] => {}
+                ["step" | "s"] => {
+                    self.mode = Mode::Step;
+                    return Ok(());
+                }
+                ["next" | "n"] => {
+                    self.mode = Mode::Next(idx, depth);
+                    return Ok(());
+                }
+                ["continue" | "c"] => {
+                    self.mode = Mode::Continue;
+                    return Ok(());
+                }
+                ["quit" | "q"] => return Err(RuntimeError::Stopped),
+                ["break" | "b", arg] => match self.location(arg) {
+                    Ok(i) => {
+                        self.breakpoints.insert(i);
+                        writeln!(self.out, "breakpoint at {}", self.show_idx(i)).unwrap();
+                    }
+                    Err(e) => writeln!(self.out, "{}", e).unwrap(),
+                },
+                ["delete" | "d", arg] => match self.location(arg) {
+                    Ok(i) if self.breakpoints.remove(&i) => {
+                        writeln!(self.out, "deleted breakpoint at {}", self.show_idx(i)).unwrap()
+                    }
+                    Ok(i) => writeln!(self.out, "no breakpoint at {}", self.show_idx(i)).unwrap(),
+                    Err(e) => writeln!(self.out, "{}", e).unwrap(),
+                },
+                ["watch" | "w", x] => {
+                    let v = show_var(s, x);
+                    writeln!(self.out, "watching {} = {}", x, v).unwrap();
+                    self.watches.push((x.to_string(), v));
+                }
+                ["print" | "p", x] => writeln!(self.out, "{} = {}", x, show_var(s, x)).unwrap(),
+                ["print" | "p"] => {
+                    let mut xs: Vec<&String> = s.keys().collect();
+                    xs.sort();
+                    for x in xs {
+                        writeln!(self.out, "{} = {}", x, s[x]).unwrap();
+                    }
+                }
+                ["help" | "h"] => writeln!(self.out, "{}", HELP).unwrap(),
+                _ => writeln!(self.out, "unknown command, try help").unwrap(),
+            }
+        }
+    }
+    // Called once the program has ended
+    pub fn finish(&mut self, s: &ValState) {
+        self.check_watches(s);
+        writeln!(self.out, "program finished").unwrap();
+    }
+}
+
+impl<'a, R: BufRead, W: Write> Hook for Debugger<'a, R, W> {
+    fn before(
+        &mut self,
+        idx: usize,
+        depth: usize,
+        stmt: &Stmt,
+        s: &ValState,
+    ) -> Result<(), RuntimeError> {
+        let watch = self.check_watches(s);
+        if self.mode == Mode::Run {
+            return Ok(());
+        }
+        let brk = self.breakpoints.contains(&idx);
+        let stop = match self.mode {
+            Mode::Step => true,
+            Mode::Next(from, d) => depth < d || (depth == d && idx != from),
+            _ => false,
+        };
+        if !(stop || brk || watch) {
+            return Ok(());
+        }
+        if brk {
+            write!(self.out, "breakpoint ").unwrap();
+        }
+        writeln!(self.out, "{}: {}", self.show_idx(idx), stmt.pretty()).unwrap();
+        self.prompt(idx, depth, s)
+    }
+}
+
+// Debugs stmt, reading commands from input
+pub fn debug(
+    stmt: &Stmt,
+    pos: &[Pos],
+    mut s: ValState,
+    input: impl BufRead,
+    out: impl Write,
+) -> Result<(), RuntimeError> {
+    let mut d = Debugger::new(pos, input, out);
+    exec_hooked(stmt, &mut s, &mut d)?;
+    d.finish(&s);
+    Ok(())
+}
@@ -0,0 +1,672 @@
+use super::*;
+
+// Derivation trees of the big-step evaluation judgement <S, σ> ⇓ σ' and the
+// typing judgement Γ ⊢ S ⇒ Γ', with the rule applied at each node. The
+// trees are printed as LaTeX using the bussproofs package. States and type
+// environments are numbered (σ_0, Γ_0, ...) and listed below the tree.
+
+pub struct Derivation {
+    pub rule: &'static str,
+    pub conclusion: String,
+    pub premises: Vec<Derivation>,
+}
+
+fn node(rule: &'static str, conclusion: String, premises: Vec<Derivation>) -> Derivation {
+    Derivation {
+        rule,
+        conclusion,
+        premises,
+    }
+}
+
+fn tex_var(x: &str) -> String {
+    format!("\\mathit{{{}}}", x.replace('_', "\\_"))
+}
+
+// A string literal in typewriter font
+fn tex_str(x: &str) -> String {
+    let mut t = String::new();
+    for c in quote(x).chars() {
+        match c {
+            '\\' => t.push_str("\\textbackslash{}"),
+            '~' => t.push_str("\\textasciitilde{}"),
+            '^' => t.push_str("\\textasciicircum{}"),
+            '{' | '}' | '_' | '#' | '$' | '%' | '&' => {
+                t.push('\\');
+                t.push(c);
+            }
+            c => t.push(c),
+        }
+    }
+    format!("\\texttt{{{}}}", t)
+}
+
+fn tex_exp(e: &Exp) -> String {
+    let bin = |l: &Exp, op: &str, r: &Exp| format!("({} {} {})", tex_exp(l), op, tex_exp(r));
+    match e {
+        Exp::Var { name } => tex_var(name),
+        Exp::Num { val } => val.to_string(),
+        Exp::Bool { val } => format!("\\mathsf{{{}}}", val),
+        Exp::Str { val } => tex_str(val),
+        Exp::Plus { left, right } => bin(left, "+", right),
+        Exp::Mult { left, right } => bin(left, "\\cdot", right),
+        Exp::And { left, right } => bin(left, "\\land", right),
+        Exp::Or { left, right } => bin(left, "\\lor", right),
+        Exp::Equ { left, right } => bin(left, "==", right),
+        Exp::Less { left, right } => bin(left, "<", right),
+        Exp::Neg { exp } => format!("\\neg {}", tex_exp(exp)),
+        Exp::Grp { exp } => format!("({})", tex_exp(exp)),
+        Exp::Call { name, args } => {
+            let args: Vec<String> = args.iter().map(tex_exp).collect();
+            format!("{}({})", tex_var(name), args.join(", "))
+        }
+        Exp::Lambda { param, ty, body } => format!(
+            "(\\lambda {} {{:}} {}.\\ {})",
+            tex_var(param),
+            tex_type(ty),
+            tex_exp(body)
+        ),
+        Exp::Array { elems } => {
+            let elems: Vec<String> = elems.iter().map(tex_exp).collect();
+            format!("[{}]", elems.join(", "))
+        }
+        Exp::Index { array, index } => format!("{}[{}]", tex_exp(array), tex_exp(index)),
+        Exp::Update {
+            array,
+            index,
+            value,
+        } => format!(
+            "{}[{} \\mapsto {}]",
+            tex_exp(array),
+            tex_exp(index),
+            tex_exp(value)
+        ),
+        Exp::Len { exp } => format!("|{}|", tex_exp(exp)),
+        Exp::Record { name, fields } => {
+            let fields: Vec<String> = fields
+                .iter()
+                .map(|(x, e)| format!("{} = {}", tex_var(x), tex_exp(e)))
+                .collect();
+            format!("{}\\{{{}\\}}", tex_var(name), fields.join(", "))
+        }
+        Exp::Field { exp, field } => format!("{}.{}", tex_exp(exp), tex_var(field)),
+        Exp::With { exp, field, value } => format!(
+            "{}[.{} \\mapsto {}]",
+            tex_exp(exp),
+            tex_var(field),
+            tex_exp(value)
+        ),
+    }
+}
+
+fn tex_stmt(s: &Stmt) -> String {
+    match s {
+        Stmt::Skip => "\\mathbf{skip}".to_string(),
+        Stmt::Seq { first, second } => format!("{};\\ {}", tex_stmt(first), tex_stmt(second)),
+        Stmt::Decl { lhs, ty: None, rhs } => format!("{} := {}", tex_var(lhs), tex_exp(rhs)),
+        Stmt::Decl {
+            lhs,
+            ty: Some(ty),
+            rhs,
+        } => format!("{} : {} := {}", tex_var(lhs), tex_type(ty), tex_exp(rhs)),
+        Stmt::Assign { lhs, rhs } => format!("{} = {}", tex_var(lhs), tex_exp(rhs)),
+        Stmt::IfThenElse {
+            cond,
+            then_stmt,
+            else_stmt,
+        } => format!(
+            "\\mathbf{{if}}\\ {}\\ \\{{{}\\}}\\ \\mathbf{{else}}\\ \\{{{}\\}}",
+            tex_exp(cond),
+            tex_stmt(then_stmt),
+            tex_stmt(else_stmt)
+        ),
+        Stmt::While { cond, stmt, .. } if matches!(**stmt, Stmt::Step { .. }) => {
+            let Stmt::Step { body, step } = &**stmt else {
+                unreachable!()
+            };
+            format!(
+                "\\mathbf{{for}}\\ ;\\ {};\\ {}\\ \\{{{}\\}}",
+                tex_exp(cond),
+                tex_stmt(step),
+                tex_stmt(body)
+            )
+        }
+        Stmt::Step { body, step } => format!("{};\\ {}", tex_stmt(body), tex_stmt(step)),
+        Stmt::While { cond, stmt, .. } => format!(
+            "\\mathbf{{while}}\\ {}\\ \\{{{}\\}}",
+            tex_exp(cond),
+            tex_stmt(stmt)
+        ),
+        Stmt::Print { print_exp } => format!("\\mathbf{{print}}\\ {}", tex_exp(print_exp)),
+        Stmt::Assert { cond } => format!("\\mathbf{{assert}}\\ {}", tex_exp(cond)),
+        Stmt::Assume { cond } => format!("\\mathbf{{assume}}\\ {}", tex_exp(cond)),
+        Stmt::Return { exp } => format!("\\mathbf{{return}}\\ {}", tex_exp(exp)),
+        Stmt::ArrayAssign { lhs, index, rhs } => {
+            format!("{}[{}] = {}", tex_var(lhs), tex_exp(index), tex_exp(rhs))
+        }
+        Stmt::FieldAssign { lhs, field, rhs } => {
+            format!("{}.{} = {}", tex_var(lhs), tex_var(field), tex_exp(rhs))
+        }
+        Stmt::Break => "\\mathbf{break}".to_string(),
+        Stmt::Continue => "\\mathbf{continue}".to_string(),
+        Stmt::Read { lhs, ty } => format!("\\mathbf{{read}}\\ {} : {}", tex_var(lhs), tex_type(ty)),
+    }
+}
+
+fn tex_val(v: &Val) -> String {
+    match v.flag {
+        Kind::ValueInt => v.val_i.unwrap().to_string(),
+        Kind::ValueBool => format!("\\mathsf{{{}}}", v.val_b.unwrap()),
+        Kind::ValueStr => tex_str(v.val_s.as_ref().unwrap()),
+        // the captured variables are left out
+        Kind::Closure => {
+            let c = v.val_c.as_ref().unwrap();
+            format!(
+                "\\langle \\lambda {} {{:}} {}.\\ {} \\rangle",
+                tex_var(&c.param),
+                tex_type(&c.ty),
+                tex_exp(&c.body)
+            )
+        }
+        Kind::Array => {
+            let xs: Vec<String> = v.val_a.as_ref().unwrap().iter().map(tex_val).collect();
+            format!("[{}]", xs.join(", "))
+        }
+        Kind::Record => {
+            let r = v.val_r.as_ref().unwrap();
+            let xs: Vec<String> = r
+                .fields
+                .iter()
+                .map(|(x, v)| format!("{} = {}", tex_var(x), tex_val(v)))
+                .collect();
+            format!("{}\\{{{}\\}}", tex_var(&r.name), xs.join(", "))
+        }
+        Kind::Undefined => "\\bot".to_string(),
+    }
+}
+
+fn tex_type(t: &Type) -> String {
+    match t {
+        Type::TyIllTyped => "\\bot".to_string(),
+        Type::TyFun(a, b) if matches!(**a, Type::TyFun(..)) => {
+            format!("({}) \\to {}", tex_type(a), tex_type(b))
+        }
+        Type::TyFun(a, b) => format!("{} \\to {}", tex_type(a), tex_type(b)),
+        Type::TyArray(ty) => format!("\\mathsf{{array}}\\langle {} \\rangle", tex_type(ty)),
+        t => format!("\\mathsf{{{}}}", t),
+    }
+}
+
+// Names the distinct states or environments seen so far
+struct Names {
+    symbol: &'static str,
+    defs: Vec<String>,
+}
+
+impl Names {
+    fn name(&mut self, def: String) -> String {
+        let i = match self.defs.iter().position(|d| *d == def) {
+            Some(i) => i,
+            None => {
+                self.defs.push(def);
+                self.defs.len() - 1
+            }
+        };
+        format!("{}_{{{}}}", self.symbol, i)
+    }
+}
+
+fn tex_map(entries: Vec<String>) -> String {
+    let mut entries = entries;
+    entries.sort();
+    format!("[{}]", entries.join(", "))
+}
+
+struct Eval {
+    states: Names,
+    // break or continue which leaves the statements up to its loop
+    jump: Option<&'static str>,
+}
+
+impl Eval {
+    fn state(&mut self, s: &ValState) -> String {
+        let def = tex_map(
+            s.iter()
+                .map(|(x, v)| format!("{} \\mapsto {}", tex_var(x), tex_val(v)))
+                .collect(),
+        );
+        self.states.name(def)
+    }
+    // The derivation for e and its value; every subexpression is evaluated
+    // once and its value is passed up to the conclusion
+    fn exp(&mut self, e: &Exp, s: &mut ValState) -> Result<(Derivation, Val), RuntimeError> {
+        let mut premises = Vec::new();
+        let mut vals = Vec::new();
+        for a in e.operands() {
+            let (d, v) = self.exp(a, s)?;
+            premises.push(d);
+            vals.push(v);
+        }
+        let rule = match e {
+            Exp::Var { .. } => "E-Var",
+            Exp::Num { .. } => "E-Num",
+            Exp::Bool { .. } => "E-Bool",
+            Exp::Str { .. } => "E-Str",
+            Exp::Plus { .. } if vals[0].flag == Kind::ValueStr => "E-Concat",
+            Exp::Plus { .. } => "E-Plus",
+            Exp::Mult { .. } => "E-Mult",
+            Exp::And { .. } => "E-And",
+            Exp::Or { .. } => "E-Or",
+            Exp::Equ { .. } => "E-Equ",
+            Exp::Less { .. } => "E-Less",
+            Exp::Neg { .. } => "E-Neg",
+            Exp::Grp { .. } => "E-Grp",
+            // the evaluation of the body is not part of the tree
+            Exp::Call { name, .. } if s.contains_key(name) => "E-App",
+            Exp::Call { .. } => "E-Call",
+            Exp::Lambda { .. } => "E-Lam",
+            Exp::Array { .. } => "E-Array",
+            Exp::Index { .. } => "E-Index",
+            Exp::Update { .. } => "E-Update",
+            Exp::Len { .. } => "E-Len",
+            Exp::Record { .. } => "E-Record",
+            Exp::Field { .. } => "E-Field",
+            Exp::With { .. } => "E-With",
+        };
+        let state = self.state(s);
+        let v = e.apply_in(vals, s, 0, &mut NoHook)?;
+        let conclusion = format!(
+            "\\langle {}, {} \\rangle \\Downarrow {}",
+            tex_exp(e),
+            state,
+            tex_val(&v)
+        );
+        Ok((node(rule, conclusion, premises), v))
+    }
+    // The derivations for the expressions of st, see Stmt::exps, and their
+    // values
+    fn exps(
+        &mut self,
+        st: &Stmt,
+        s: &mut ValState,
+    ) -> Result<(Vec<Derivation>, Vec<Val>), RuntimeError> {
+        let mut premises = Vec::new();
+        let mut vals = Vec::new();
+        for e in st.exps() {
+            let (d, v) = self.exp(e, s)?;
+            premises.push(d);
+            vals.push(v);
+        }
+        Ok((premises, vals))
+    }
+    fn stmt(&mut self, st: &Stmt, mut s: ValState) -> Result<(Derivation, ValState), RuntimeError> {
+        let before = self.state(&s);
+        let (rule, premises) = match st {
+            Stmt::Skip => ("E-Skip", vec![]),
+            Stmt::Seq { first, second } => {
+                let (d1, s1) = self.stmt(first, s)?;
+                if self.jump.is_some() {
+                    s = s1;
+                    ("E-Seq-Jump", vec![d1])
+                } else {
+                    let (d2, s2) = self.stmt(second, s1)?;
+                    s = s2;
+                    ("E-Seq", vec![d1, d2])
+                }
+            }
+            // continue ends the body and still runs the step, break skips it
+            Stmt::Step { body, step } => {
+                let (d1, s1) = self.stmt(body, s)?;
+                if self.jump == Some("continue") {
+                    self.jump = None;
+                }
+                if self.jump.is_some() {
+                    s = s1;
+                    ("E-Step-Break", vec![d1])
+                } else {
+                    let (d2, s2) = self.stmt(step, s1)?;
+                    s = s2;
+                    ("E-Step", vec![d1, d2])
+                }
+            }
+            Stmt::Break => {
+                self.jump = Some("break");
+                ("E-Break", vec![])
+            }
+            Stmt::Continue => {
+                self.jump = Some("continue");
+                ("E-Continue", vec![])
+            }
+            Stmt::Read { .. } => {
+                st.eval(&mut s)?;
+                ("E-Read", vec![])
+            }
+            Stmt::Decl { .. }
+            | Stmt::Assign { .. }
+            | Stmt::ArrayAssign { .. }
+            | Stmt::FieldAssign { .. }
+            | Stmt::Print { .. }
+            | Stmt::Assert { .. }
+            | Stmt::Assume { .. }
+            | Stmt::Return { .. } => {
+                let (premises, vals) = self.exps(st, &mut s)?;
+                // Print leaves the state alone, its output is not wanted here
+                if !matches!(st, Stmt::Print { .. }) {
+                    st.perform(vals, &mut s)?;
+                }
+                let rule = match st {
+                    Stmt::Decl { .. } => "E-Decl",
+                    Stmt::Assign { .. } => "E-Assign",
+                    Stmt::ArrayAssign { .. } => "E-ArrayAssign",
+                    Stmt::FieldAssign { .. } => "E-FieldAssign",
+                    Stmt::Print { .. } => "E-Print",
+                    Stmt::Assert { .. } => "E-Assert",
+                    Stmt::Return { .. } => "E-Return",
+                    _ => "E-Assume",
+                };
+                (rule, premises)
+            }
+            Stmt::IfThenElse {
+                cond,
+                then_stmt,
+                else_stmt,
+            } => {
+                // conditions of type-checked programs are bool
+                let (d, v) = self.exp(cond, &mut s)?;
+                if v.val_b == Some(true) {
+                    let (d1, s1) = self.stmt(then_stmt, s)?;
+                    s = s1;
+                    ("E-If-True", vec![d, d1])
+                } else {
+                    let (d2, s2) = self.stmt(else_stmt, s)?;
+                    s = s2;
+                    ("E-If-False", vec![d, d2])
+                }
+            }
+            Stmt::While { cond, stmt, .. } => {
+                let (d, v) = self.exp(cond, &mut s)?;
+                if v.val_b != Some(true) {
+                    ("E-While-False", vec![d])
+                } else {
+                    let (d1, s1) = self.stmt(stmt, s)?;
+                    if self.jump.take() == Some("break") {
+                        s = s1;
+                        ("E-While-Break", vec![d, d1])
+                    } else {
+                        let (d2, s2) = self.stmt(st, s1)?;
+                        s = s2;
+                        ("E-While-True", vec![d, d1, d2])
+                    }
+                }
+            }
+        };
+        let after = match self.jump {
+            Some(j) => format!("\\mathbf{{{}}}, {}", j, self.state(&s)),
+            None => self.state(&s),
+        };
+        let conclusion = format!(
+            "\\langle {}, {} \\rangle \\Downarrow {}",
+            tex_stmt(st),
+            before,
+            after
+        );
+        Ok((node(rule, conclusion, premises), s))
+    }
+}
+
+struct Typing {
+    envs: Names,
+}
+
+impl Typing {
+    fn env(&mut self, t: &TyState) -> String {
+        let def = tex_map(
+            t.iter()
+                .map(|(x, ty)| format!("{} : {}", tex_var(x), tex_type(ty)))
+                .collect(),
+        );
+        self.envs.name(def)
+    }
+    fn exp(&mut self, e: &Exp, t: &mut TyState) -> Derivation {
+        let (rule, premises) = match e {
+            Exp::Var { .. } => ("T-Var", vec![]),
+            Exp::Num { .. } => ("T-Num", vec![]),
+            Exp::Bool { .. } => ("T-Bool", vec![]),
+            Exp::Str { .. } => ("T-Str", vec![]),
+            Exp::Plus { left, right } if e.infer(t) == Type::TyStr => {
+                ("T-Concat", vec![self.exp(left, t), self.exp(right, t)])
+            }
+            Exp::Plus { left, right } => ("T-Plus", vec![self.exp(left, t), self.exp(right, t)]),
+            Exp::Mult { left, right } => ("T-Mult", vec![self.exp(left, t), self.exp(right, t)]),
+            Exp::And { left, right } => ("T-And", vec![self.exp(left, t), self.exp(right, t)]),
+            Exp::Or { left, right } => ("T-Or", vec![self.exp(left, t), self.exp(right, t)]),
+            Exp::Equ { left, right } => ("T-Equ", vec![self.exp(left, t), self.exp(right, t)]),
+            Exp::Less { left, right } => ("T-Less", vec![self.exp(left, t), self.exp(right, t)]),
+            Exp::Neg { exp } => ("T-Neg", vec![self.exp(exp, t)]),
+            Exp::Grp { exp } => ("T-Grp", vec![self.exp(exp, t)]),
+            Exp::Call { name, args } if t.contains_key(name) => {
+                ("T-App", args.iter().map(|a| self.exp(a, t)).collect())
+            }
+            Exp::Call { args, .. } => ("T-Call", args.iter().map(|a| self.exp(a, t)).collect()),
+            Exp::Lambda { param, ty, body } => {
+                let mut inner = t.clone();
+                inner.insert(param.clone(), ty.clone());
+                ("T-Lam", vec![self.exp(body, &mut inner)])
+            }
+            Exp::Array { elems } => ("T-Array", elems.iter().map(|e| self.exp(e, t)).collect()),
+            Exp::Index { array, index } => {
+                ("T-Index", vec![self.exp(array, t), self.exp(index, t)])
+            }
+            Exp::Update {
+                array,
+                index,
+                value,
+            } => (
+                "T-Update",
+                vec![self.exp(array, t), self.exp(index, t), self.exp(value, t)],
+            ),
+            Exp::Len { exp } => ("T-Len", vec![self.exp(exp, t)]),
+            Exp::Record { fields, .. } => (
+                "T-Record",
+                fields.iter().map(|(_, e)| self.exp(e, t)).collect(),
+            ),
+            Exp::Field { exp, .. } => ("T-Field", vec![self.exp(exp, t)]),
+            Exp::With { exp, value, .. } => ("T-With", vec![self.exp(exp, t), self.exp(value, t)]),
+        };
+        let ty = e.infer(t);
+        let conclusion = format!("{} \\vdash {} : {}", self.env(t), tex_exp(e), tex_type(&ty));
+        node(rule, conclusion, premises)
+    }
+    // The derivation for st and the environment after it, None if st is
+    // ill-typed
+    fn stmt(&mut self, st: &Stmt, mut t: TyState) -> (Derivation, Option<TyState>) {
+        let before = self.env(&t);
+        let mut ok = true;
+        let (rule, premises) = match st {
+            Stmt::Skip => ("T-Skip", vec![]),
+            Stmt::Seq { first, second } => {
+                let (d1, t1) = self.stmt(first, t.clone());
+                match t1 {
+                    Some(t1) => {
+                        let (d2, t2) = self.stmt(second, t1);
+                        ok = t2.is_some();
+                        t = t2.unwrap_or_default();
+                        ("T-Seq", vec![d1, d2])
+                    }
+                    None => {
+                        ok = false;
+                        ("T-Seq", vec![d1])
+                    }
+                }
+            }
+            Stmt::IfThenElse {
+                cond,
+                then_stmt,
+                else_stmt,
+            } => {
+                let d = self.exp(cond, &mut t);
+                let (d1, t1) = self.stmt(then_stmt, t.clone());
+                let (d2, t2) = self.stmt(else_stmt, t1.clone().unwrap_or_default());
+                ok = cond.infer(&mut t) != Type::TyIllTyped && t1.is_some() && t2.is_some();
+                t = t2.unwrap_or_default();
+                ("T-If", vec![d, d1, d2])
+            }
+            Stmt::While { cond, stmt, .. } => {
+                let d = self.exp(cond, &mut t);
+                let outer = t.in_loop;
+                t.in_loop = true;
+                let (d1, t1) = self.stmt(stmt, t.clone());
+                t.in_loop = outer;
+                ok = cond.infer(&mut t) != Type::TyIllTyped && t1.is_some();
+                t = t1.unwrap_or_default();
+                t.in_loop = outer;
+                ("T-While", vec![d, d1])
+            }
+            Stmt::Step { body, step } => {
+                let (d1, t1) = self.stmt(body, t.clone());
+                match t1 {
+                    Some(t1) => {
+                        let (d2, t2) = self.stmt(step, t1);
+                        ok = t2.is_some();
+                        t = t2.unwrap_or_default();
+                        ("T-Step", vec![d1, d2])
+                    }
+                    None => {
+                        ok = false;
+                        ("T-Step", vec![d1])
+                    }
+                }
+            }
+            Stmt::Break | Stmt::Continue => {
+                ok = t.in_loop;
+                let rule = match st {
+                    Stmt::Break => "T-Break",
+                    _ => "T-Continue",
+                };
+                (rule, vec![])
+            }
+            Stmt::Read { .. } => {
+                ok = st.check(&mut t);
+                ("T-Read", vec![])
+            }
+            Stmt::Decl { rhs: e, .. }
+            | Stmt::Assign { rhs: e, .. }
+            | Stmt::Print { print_exp: e }
+            | Stmt::Assert { cond: e }
+            | Stmt::Assume { cond: e }
+            | Stmt::Return { exp: e } => {
+                let d = self.exp(e, &mut t);
+                ok = match st {
+                    Stmt::Assign { lhs, .. } if !t.contains_key(lhs) => false,
+                    _ => st.check(&mut t),
+                };
+                let rule = match st {
+                    Stmt::Decl { .. } => "T-Decl",
+                    Stmt::Assign { .. } => "T-Assign",
+                    Stmt::Print { .. } => "T-Print",
+                    Stmt::Assert { .. } => "T-Assert",
+                    Stmt::Return { .. } => "T-Return",
+                    _ => "T-Assume",
+                };
+                (rule, vec![d])
+            }
+            Stmt::ArrayAssign { index, rhs, .. } => {
+                let d1 = self.exp(index, &mut t);
+                let d2 = self.exp(rhs, &mut t);
+                ok = st.check(&mut t);
+                ("T-ArrayAssign", vec![d1, d2])
+            }
+            Stmt::FieldAssign { rhs, .. } => {
+                let d = self.exp(rhs, &mut t);
+                ok = st.check(&mut t);
+                ("T-FieldAssign", vec![d])
+            }
+        };
+        let after = if ok {
+            self.env(&t)
+        } else {
+            "\\bot".to_string()
+        };
+        let conclusion = format!("{} \\vdash {} \\Rightarrow {}", before, tex_stmt(st), after);
+        (
+            node(rule, conclusion, premises),
+            if ok { Some(t) } else { None },
+        )
+    }
+}
+
+fn bussproofs(d: &Derivation, out: &mut Vec<String>) {
+    if d.premises.is_empty() {
+        out.push("\\AxiomC{}".to_string());
+    }
+    for p in &d.premises {
+        bussproofs(p, out);
+    }
+    out.push(format!("\\RightLabel{{\\scriptsize {}}}", d.rule));
+    let inf = match d.premises.len() {
+        0 | 1 => "Unary",
+        2 => "Binary",
+        3 => "Trinary",
+        4 => "Quaternary",
+        _ => "Quinary",
+    };
+    out.push(format!("\\{}InfC{{${}$}}", inf, d.conclusion));
+}
+
+// A prooftree environment followed by the definitions of the names
+fn tex_tree(d: &Derivation, names: &Names) -> String {
+    let mut out = vec!["\\begin{prooftree}".to_string()];
+    bussproofs(d, &mut out);
+    out.push("\\end{prooftree}".to_string());
+    out.push("\\begin{align*}".to_string());
+    let defs: Vec<String> = names
+        .defs
+        .iter()
+        .enumerate()
+        .map(|(i, def)| format!("{}_{{{}}} &= {}", names.symbol, i, def))
+        .collect();
+    out.push(defs.join(" \\\\\n"));
+    out.push("\\end{align*}".to_string());
+    out.join("\n")
+}
+
+// Evaluation of stmt in the empty state
+pub fn eval_tree(stmt: &Stmt, procs: &[Proc]) -> Result<(Derivation, String), RuntimeError> {
+    let mut e = Eval {
+        states: Names {
+            symbol: "\\sigma",
+            defs: Vec::new(),
+        },
+        jump: None,
+    };
+    let (d, _) = e.stmt(stmt, ValState::with_procs(procs.to_vec()))?;
+    let tex = tex_tree(&d, &e.states);
+    Ok((d, tex))
+}
+
+// Typing of stmt in the environment with only the signatures of procs and
+// the declarations of records
+pub fn type_tree(stmt: &Stmt, procs: &[Proc], records: &[Record]) -> (Derivation, String) {
+    let mut t = Typing {
+        envs: Names {
+            symbol: "\\Gamma",
+            defs: Vec::new(),
+        },
+    };
+    let (d, _) = t.stmt(stmt, TyState::with_procs(procs).with_records(records));
+    let tex = tex_tree(&d, &t.envs);
+    (d, tex)
+}
+
+// A standalone document with the given trees
+pub fn document(trees: &[String]) -> String {
+    let mut out = vec![
+        "\\documentclass{article}".to_string(),
+        "\\usepackage{amsmath}".to_string(),
+        "\\usepackage{bussproofs}".to_string(),
+        "\\begin{document}".to_string(),
+    ];
+    out.extend(trees.iter().cloned());
+    out.push("\\end{document}".to_string());
+    out.join("\n") + "\n"
+}
@@ -0,0 +1,640 @@
+use super::parser::Program;
+use super::*;
+use std::collections::HashSet;
+
+// Type inference in the style of Hindley-Milner, for programs with lambdas
+// whose parameters have no type annotation.
+//
+// Every unknown type is a variable TyVar(n). An expression generates
+// equations between types, which are solved by unification as they come;
+// subst holds the solution found so far. A Decl whose right-hand side is a
+// lambda generalizes the variables which do not occur in the environment,
+// so id := fn(x) => x can be called with an int and with a bool. Variables
+// which are assigned anywhere in the program are not generalized, an
+// assignment could otherwise change the type of a polymorphic variable.
+//
+// +, < and == are defined for some types only, and len for arrays and
+// strings. Their operand types are checked at the end of the statement;
+// one still unknown then is int, for len an array.
+//
+// Procedures keep the types of their declaration. On programs whose lambdas
+// are all annotated the inference finds the same types as Exp::infer.
+
+// A type whose variables vars are instantiated with fresh ones at every use
+#[derive(Clone)]
+pub struct Scheme {
+    pub vars: Vec<usize>,
+    pub ty: Type,
+}
+
+pub struct TypeError {
+    // index of the statement
+    pub stmt: usize,
+    // the pretty printed expression the error was found in, None if it is
+    // the statement itself
+    pub exp: Option<String>,
+    pub msg: String,
+}
+
+#[derive(Clone, Copy)]
+enum Class {
+    Plus,
+    Less,
+    Equ,
+    Len,
+}
+
+// An operand type of +, <, == or len, checked at the end of the statement
+struct Pending {
+    ty: Type,
+    class: Class,
+    exp: String,
+}
+
+pub struct Infer {
+    // the solution of each type variable, None while it is unknown
+    subst: Vec<Option<Type>>,
+    pending: Vec<Pending>,
+    env: HashMap<String, Scheme>,
+    // signatures, records, the return type and whether in a loop
+    t: TyState,
+    assigned: HashSet<String>,
+    stmt: usize,
+    pub errors: Vec<TypeError>,
+    // the statement and the type of every declared variable
+    pub decls: Vec<(usize, String, Scheme)>,
+}
+
+fn int() -> Type {
+    Type::TyInt
+}
+
+fn fun(a: Type, b: Type) -> Type {
+    Type::TyFun(Box::new(a), Box::new(b))
+}
+
+fn array(ty: Type) -> Type {
+    Type::TyArray(Box::new(ty))
+}
+
+fn vars(ty: &Type, out: &mut Vec<usize>) {
+    match ty {
+        Type::TyVar(n) if !out.contains(n) => out.push(*n),
+        Type::TyFun(a, b) => {
+            vars(a, out);
+            vars(b, out);
+        }
+        Type::TyArray(ty) => vars(ty, out),
+        _ => {}
+    }
+}
+
+fn replace(ty: &Type, f: &impl Fn(usize) -> Type) -> Type {
+    match ty {
+        Type::TyVar(n) => f(*n),
+        Type::TyFun(a, b) => fun(replace(a, f), replace(b, f)),
+        Type::TyArray(ty) => array(replace(ty, f)),
+        ty => ty.clone(),
+    }
+}
+
+// The types with their variables numbered in the order they occur, so
+// they are shown as 'a, 'b, ..
+fn rename(tys: &[Type]) -> Vec<Type> {
+    let mut xs = Vec::new();
+    for ty in tys {
+        vars(ty, &mut xs);
+    }
+    let at = |n| Type::TyVar(xs.iter().position(|x| *x == n).unwrap());
+    tys.iter().map(|ty| replace(ty, &at)).collect()
+}
+
+// Statements which assign the variables
+fn assigned(stmt: &Stmt, out: &mut HashSet<String>) {
+    stmt.visit(&mut 0, &mut |_, s| match s {
+        Stmt::Assign { lhs, .. }
+        | Stmt::ArrayAssign { lhs, .. }
+        | Stmt::FieldAssign { lhs, .. } => {
+            out.insert(lhs.clone());
+        }
+        _ => {}
+    });
+}
+
+// True if some lambda of p has no annotation, only then the inference is
+// needed
+pub fn needs_inference(p: &Program) -> bool {
+    p.exps.iter().any(|e| {
+        let mut xs = Vec::new();
+        if let Exp::Lambda { ty, .. } = &e.exp {
+            vars(ty, &mut xs);
+        }
+        !xs.is_empty()
+    })
+}
+
+impl Infer {
+    fn fresh(&mut self) -> Type {
+        self.subst.push(None);
+        Type::TyVar(self.subst.len() - 1)
+    }
+    // ty with the solved variables replaced by their solutions
+    fn resolve(&self, ty: &Type) -> Type {
+        replace(ty, &|n| match &self.subst[n] {
+            Some(ty) => self.resolve(ty),
+            None => Type::TyVar(n),
+        })
+    }
+    fn error(&self, e: Option<&Exp>, msg: String) -> TypeError {
+        TypeError {
+            stmt: self.stmt,
+            exp: e.map(|e| e.pretty()),
+            msg,
+        }
+    }
+    // Solves expected = found, msg describes the mismatch of both types
+    fn unify_or(
+        &mut self,
+        expected: &Type,
+        found: &Type,
+        e: Option<&Exp>,
+        msg: impl Fn(&Type, &Type) -> String,
+    ) -> Result<(), TypeError> {
+        let (a, b) = (self.resolve(expected), self.resolve(found));
+        self.unify(&a, &b).map_err(|infinite| {
+            let tys = rename(&[a, b]);
+            let msg = if infinite {
+                format!("infinite type: {} occurs in {}", tys[0], tys[1])
+            } else {
+                msg(&tys[0], &tys[1])
+            };
+            self.error(e, msg)
+        })
+    }
+    fn expect(&mut self, expected: &Type, found: &Type, e: &Exp) -> Result<(), TypeError> {
+        self.unify_or(expected, found, Some(e), |a, b| {
+            format!("expected {}, found {}", a, b)
+        })
+    }
+    // Err(true) if a variable would have to contain itself
+    fn unify(&mut self, a: &Type, b: &Type) -> Result<(), bool> {
+        match (self.resolve(a), self.resolve(b)) {
+            (Type::TyVar(n), Type::TyVar(m)) if n == m => Ok(()),
+            (Type::TyVar(n), ty) | (ty, Type::TyVar(n)) => {
+                let mut xs = Vec::new();
+                vars(&ty, &mut xs);
+                if xs.contains(&n) {
+                    return Err(true);
+                }
+                self.subst[n] = Some(ty);
+                Ok(())
+            }
+            (Type::TyFun(a1, b1), Type::TyFun(a2, b2)) => {
+                self.unify(&a1, &a2)?;
+                self.unify(&b1, &b2)
+            }
+            (Type::TyArray(a), Type::TyArray(b)) => self.unify(&a, &b),
+            (a, b) if a == b => Ok(()),
+            _ => Err(false),
+        }
+    }
+    fn instantiate(&mut self, s: &Scheme) -> Type {
+        let fresh: Vec<(usize, Type)> = s.vars.iter().map(|n| (*n, self.fresh())).collect();
+        replace(&s.ty, &|n| match fresh.iter().find(|(m, _)| *m == n) {
+            Some((_, ty)) => ty.clone(),
+            None => Type::TyVar(n),
+        })
+    }
+    fn generalize(&self, ty: &Type) -> Scheme {
+        let ty = self.resolve(ty);
+        let mut free = Vec::new();
+        for s in self.env.values() {
+            let mut xs = Vec::new();
+            vars(&self.resolve(&s.ty), &mut xs);
+            free.extend(xs.into_iter().filter(|x| !s.vars.contains(x)));
+        }
+        let mut xs = Vec::new();
+        vars(&ty, &mut xs);
+        xs.retain(|x| !free.contains(x));
+        Scheme { vars: xs, ty }
+    }
+    fn mono(&self, ty: &Type) -> Scheme {
+        Scheme {
+            vars: Vec::new(),
+            ty: self.resolve(ty),
+        }
+    }
+    fn pending(&mut self, ty: &Type, class: Class, e: &Exp) {
+        self.pending.push(Pending {
+            ty: ty.clone(),
+            class,
+            exp: e.pretty(),
+        });
+    }
+    // Checks the operand types of the statement, unknown ones get their
+    // default
+    fn settle(&mut self) -> Result<(), TypeError> {
+        for p in std::mem::take(&mut self.pending) {
+            let ty = match (self.resolve(&p.ty), p.class) {
+                (Type::TyVar(n), Class::Len) => {
+                    let elem = self.fresh();
+                    self.subst[n] = Some(array(elem));
+                    continue;
+                }
+                (Type::TyVar(n), _) => {
+                    self.subst[n] = Some(int());
+                    continue;
+                }
+                (ty, _) => ty,
+            };
+            let (op, ok) = match p.class {
+                Class::Plus => ("+", matches!(ty, Type::TyInt | Type::TyStr)),
+                Class::Less => ("<", matches!(ty, Type::TyInt | Type::TyStr)),
+                Class::Equ => ("==", matches!(ty, Type::TyInt | Type::TyBool | Type::TyStr)),
+                Class::Len => ("len", matches!(ty, Type::TyArray(_) | Type::TyStr)),
+            };
+            if !ok {
+                return Err(TypeError {
+                    stmt: self.stmt,
+                    exp: Some(p.exp),
+                    msg: format!("{} is not defined for {}", op, rename(&[ty])[0]),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn exp(&mut self, e: &Exp) -> Result<Type, TypeError> {
+        let ty = match e {
+            Exp::Num { .. } => int(),
+            Exp::Bool { .. } => Type::TyBool,
+            Exp::Str { .. } => Type::TyStr,
+            Exp::Var { name } => match self.env.get(name).cloned() {
+                Some(s) => self.instantiate(&s),
+                None => return Err(self.error(Some(e), format!("undeclared variable {}", name))),
+            },
+            Exp::Grp { exp } => self.exp(exp)?,
+            Exp::Mult { left, right } => {
+                for x in [left, right] {
+                    let ty = self.exp(x)?;
+                    self.expect(&int(), &ty, x)?;
+                }
+                int()
+            }
+            Exp::And { left, right } | Exp::Or { left, right } => {
+                for x in [left, right] {
+                    let ty = self.exp(x)?;
+                    self.expect(&Type::TyBool, &ty, x)?;
+                }
+                Type::TyBool
+            }
+            Exp::Neg { exp } => {
+                let ty = self.exp(exp)?;
+                self.expect(&Type::TyBool, &ty, exp)?;
+                Type::TyBool
+            }
+            Exp::Plus { left, right } | Exp::Less { left, right } | Exp::Equ { left, right } => {
+                let a = self.exp(left)?;
+                let b = self.exp(right)?;
+                self.unify_or(&a, &b, Some(e), |a, b| {
+                    format!("operands of different types {} and {}", a, b)
+                })?;
+                let class = match e {
+                    Exp::Plus { .. } => Class::Plus,
+                    Exp::Less { .. } => Class::Less,
+                    _ => Class::Equ,
+                };
+                self.pending(&a, class, e);
+                match class {
+                    Class::Plus => a,
+                    _ => Type::TyBool,
+                }
+            }
+            Exp::Call { name, args } if self.env.contains_key(name) => {
+                let s = self.env[name].clone();
+                let f = self.instantiate(&s);
+                let [a] = args.as_slice() else {
+                    let msg = format!("closure {} takes 1 argument, found {}", name, args.len());
+                    return Err(self.error(Some(e), msg));
+                };
+                let ta = self.exp(a)?;
+                let ret = self.fresh();
+                self.unify_or(&f, &fun(ta, ret.clone()), Some(e), |f, call| {
+                    format!("{} of type {} cannot be called as {}", name, f, call)
+                })?;
+                ret
+            }
+            Exp::Call { name, args } => {
+                let (params, ret) = match self.t.signature(name) {
+                    Some(sig) => sig.clone(),
+                    None => return Err(self.error(Some(e), format!("unknown procedure {}", name))),
+                };
+                if params.len() != args.len() {
+                    let msg = format!(
+                        "{} takes {} arguments, found {}",
+                        name,
+                        params.len(),
+                        args.len()
+                    );
+                    return Err(self.error(Some(e), msg));
+                }
+                for (a, ty) in args.iter().zip(params) {
+                    let ta = self.exp(a)?;
+                    self.expect(&ty, &ta, a)?;
+                }
+                ret
+            }
+            Exp::Lambda { param, ty, body } => {
+                if !self.t.declared(ty) {
+                    return Err(self.error(Some(e), format!("unknown type {}", ty)));
+                }
+                // every variable of the annotation is a new unknown
+                let mut xs = Vec::new();
+                vars(ty, &mut xs);
+                let fresh: Vec<Type> = xs.iter().map(|_| self.fresh()).collect();
+                let ty = replace(ty, &|n| {
+                    fresh[xs.iter().position(|x| *x == n).unwrap()].clone()
+                });
+                let outer = self.env.insert(param.clone(), self.mono(&ty));
+                let ret = self.exp(body);
+                match outer {
+                    Some(s) => self.env.insert(param.clone(), s),
+                    None => self.env.remove(param),
+                };
+                fun(ty, ret?)
+            }
+            Exp::Array { elems } => {
+                let elem = self.fresh();
+                for x in elems {
+                    let ty = self.exp(x)?;
+                    self.unify_or(&elem, &ty, Some(x), |a, b| {
+                        format!("array of {} has an element of type {}", a, b)
+                    })?;
+                }
+                array(elem)
+            }
+            Exp::Index { array: a, index }
+            | Exp::Update {
+                array: a, index, ..
+            } => {
+                let ta = self.exp(a)?;
+                let elem = self.fresh();
+                self.expect(&array(elem.clone()), &ta, a)?;
+                let ti = self.exp(index)?;
+                self.expect(&int(), &ti, index)?;
+                match e {
+                    Exp::Update { value, .. } => {
+                        let tv = self.exp(value)?;
+                        self.expect(&elem, &tv, value)?;
+                        ta
+                    }
+                    _ => elem,
+                }
+            }
+            Exp::Len { exp } => {
+                let ty = self.exp(exp)?;
+                self.pending(&ty, Class::Len, e);
+                int()
+            }
+            Exp::Record { name, fields } => {
+                let decl = match self.t.fields(name) {
+                    Some(decl) => decl.clone(),
+                    None => return Err(self.error(Some(e), format!("unknown record {}", name))),
+                };
+                for (x, _) in fields {
+                    if !decl.iter().any(|(y, _)| y == x) {
+                        let msg = format!("record {} has no field {}", name, x);
+                        return Err(self.error(Some(e), msg));
+                    }
+                }
+                for (x, ty) in decl {
+                    let v = match fields.iter().find(|(y, _)| *y == x) {
+                        Some((_, v)) => v,
+                        None => {
+                            let msg = format!("field {} of {} is missing", x, name);
+                            return Err(self.error(Some(e), msg));
+                        }
+                    };
+                    let tv = self.exp(v)?;
+                    self.expect(&ty, &tv, v)?;
+                }
+                Type::TyRecord(name.clone())
+            }
+            Exp::Field { exp, field } | Exp::With { exp, field, .. } => {
+                let ty = self.exp(exp)?;
+                let tf = self.field(&ty, field, exp)?;
+                if let Exp::With { value, .. } = e {
+                    let tv = self.exp(value)?;
+                    self.expect(&tf, &tv, value)?;
+                    return Ok(ty);
+                }
+                tf
+            }
+        };
+        Ok(ty)
+    }
+    // The type of field in values of type ty, a record with the field if ty
+    // is unknown
+    fn field(&mut self, ty: &Type, field: &str, e: &Exp) -> Result<Type, TypeError> {
+        let ty = match self.resolve(ty) {
+            Type::TyVar(n) => match self.t.owner(field) {
+                Some(r) => {
+                    self.subst[n] = Some(r.clone());
+                    r
+                }
+                None => return Err(self.error(Some(e), format!("no record has a field {}", field))),
+            },
+            ty => ty,
+        };
+        match (&ty, self.t.field(&ty, field)) {
+            (_, Some(tf)) => Ok(tf),
+            (Type::TyRecord(name), None) => {
+                Err(self.error(Some(e), format!("record {} has no field {}", name, field)))
+            }
+            (ty, None) => Err(self.error(
+                Some(e),
+                format!(
+                    "{} of type {} is not a record",
+                    e.pretty(),
+                    rename(std::slice::from_ref(ty))[0]
+                ),
+            )),
+        }
+    }
+
+    fn stmt(&mut self, s: &Stmt, idx: usize) {
+        self.stmt = idx;
+        let r = match s {
+            Stmt::Seq {
+                first: body,
+                second: step,
+            }
+            | Stmt::Step { body, step } => {
+                self.stmt(body, idx);
+                self.stmt(step, idx + body.size());
+                return;
+            }
+            Stmt::IfThenElse {
+                cond,
+                then_stmt,
+                else_stmt,
+            } => {
+                let r = self.cond(cond);
+                self.report(r);
+                self.stmt(then_stmt, idx + 1);
+                self.stmt(else_stmt, idx + 1 + then_stmt.size());
+                return;
+            }
+            Stmt::While { cond, inv, stmt } => {
+                let r = self.cond(cond);
+                self.report(r);
+                if let Some(inv) = inv {
+                    let r = self.cond(inv);
+                    self.report(r);
+                }
+                let outer = std::mem::replace(&mut self.t.in_loop, true);
+                self.stmt(stmt, idx + 1);
+                self.t.in_loop = outer;
+                return;
+            }
+            Stmt::Decl { lhs, ty, rhs } => self.decl(lhs, ty, rhs, idx),
+            Stmt::Read { lhs, ty } => {
+                self.env.insert(lhs.clone(), self.mono(ty));
+                self.decls.push((idx, lhs.clone(), self.mono(ty)));
+                match input::readable(ty) {
+                    true => Ok(()),
+                    false => Err(self.error(None, format!("cannot read a value of type {}", ty))),
+                }
+            }
+            Stmt::Assign { lhs, rhs } => self.assign(lhs, rhs, |ty| ty),
+            Stmt::ArrayAssign { lhs, index, rhs } => (|| {
+                let ti = self.exp(index)?;
+                self.expect(&int(), &ti, index)?;
+                self.assign(lhs, rhs, array)
+            })(),
+            Stmt::FieldAssign { lhs, field, rhs } => match self.env.get(lhs).cloned() {
+                Some(s) => (|| {
+                    let ty = self.instantiate(&s);
+                    let tf = self.field(&ty, field, &Exp::Var { name: lhs.clone() })?;
+                    let tr = self.exp(rhs)?;
+                    self.unify_or(&tf, &tr, Some(rhs), |a, b| {
+                        format!("cannot assign {} to {}.{} of type {}", b, lhs, field, a)
+                    })
+                })(),
+                None => Err(self.error(None, format!("undeclared variable {}", lhs))),
+            },
+            Stmt::Print { print_exp } => self.exp(print_exp).map(|_| ()),
+            Stmt::Assert { cond } | Stmt::Assume { cond } => self.cond(cond),
+            Stmt::Return { exp } => match self.t.ret.clone() {
+                Some(ret) => (|| {
+                    let ty = self.exp(exp)?;
+                    self.unify_or(&ret, &ty, Some(exp), |a, b| {
+                        format!("return type must be {}, found {}", a, b)
+                    })
+                })(),
+                None => Err(self.error(None, "return outside of a procedure".to_string())),
+            },
+            Stmt::Break | Stmt::Continue if !self.t.in_loop => {
+                Err(self.error(None, format!("{} outside of a loop", s.pretty())))
+            }
+            Stmt::Skip | Stmt::Break | Stmt::Continue => Ok(()),
+        };
+        let r = r.and_then(|_| self.settle());
+        self.report(r);
+    }
+    fn report(&mut self, r: Result<(), TypeError>) {
+        if let Err(e) = r {
+            // the other operands of the statement are not checked
+            self.pending.clear();
+            self.errors.push(e);
+        }
+    }
+    fn cond(&mut self, cond: &Exp) -> Result<(), TypeError> {
+        let ty = self.exp(cond)?;
+        self.unify_or(&Type::TyBool, &ty, Some(cond), |_, b| {
+            format!("condition must be bool, found {}", b)
+        })?;
+        self.settle()
+    }
+    fn decl(
+        &mut self,
+        lhs: &str,
+        ann: &Option<Type>,
+        rhs: &Exp,
+        idx: usize,
+    ) -> Result<(), TypeError> {
+        let r = (|| {
+            let ty = self.exp(rhs)?;
+            if let Some(ann) = ann {
+                self.unify_or(ann, &ty, None, |a, b| {
+                    format!("{} is declared as {} but initialized with {}", lhs, a, b)
+                })?;
+            }
+            self.settle()?;
+            Ok(ty)
+        })();
+        // after an error lhs has its annotation or is unknown
+        let ty = match &r {
+            Ok(ty) => ty.clone(),
+            Err(_) => ann.clone().unwrap_or_else(|| self.fresh()),
+        };
+        let s = match rhs {
+            Exp::Lambda { .. } if r.is_ok() && !self.assigned.contains(lhs) => self.generalize(&ty),
+            _ => self.mono(&ty),
+        };
+        self.env.insert(lhs.to_string(), s.clone());
+        self.decls.push((idx, lhs.to_string(), s));
+        r.map(|_| ())
+    }
+    fn assign(
+        &mut self,
+        lhs: &str,
+        rhs: &Exp,
+        wrap: impl Fn(Type) -> Type,
+    ) -> Result<(), TypeError> {
+        let s = match self.env.get(lhs) {
+            Some(s) => s.clone(),
+            None => return Err(self.error(None, format!("undeclared variable {}", lhs))),
+        };
+        let ty = self.instantiate(&s);
+        let tr = self.exp(rhs)?;
+        self.unify_or(&ty, &wrap(tr), Some(rhs), |a, b| {
+            format!("cannot assign {} to {} of type {}", b, lhs, a)
+        })
+    }
+
+    // The type of a declared variable with what was found out after its
+    // declaration
+    pub fn show(&self, s: &Scheme) -> String {
+        rename(&[self.resolve(&s.ty)])[0].to_string()
+    }
+}
+
+// Infers the types of the main program and of the procedure bodies
+pub fn infer(p: &Program) -> Infer {
+    let t = TyState::with_procs(&p.procs).with_records(&p.records);
+    let mut inf = Infer {
+        subst: Vec::new(),
+        pending: Vec::new(),
+        env: HashMap::new(),
+        t: t.clone(),
+        assigned: HashSet::new(),
+        stmt: 0,
+        errors: Vec::new(),
+        decls: Vec::new(),
+    };
+    assigned(&p.stmt, &mut inf.assigned);
+    inf.stmt(&p.stmt, 0);
+    for proc in &p.procs {
+        inf.t = t.enter(proc);
+        inf.assigned.clear();
+        assigned(&proc.body, &mut inf.assigned);
+        inf.env = proc
+            .params
+            .iter()
+            .map(|(x, ty)| (x.clone(), inf.mono(ty)))
+            .collect();
+        inf.stmt(&proc.body, proc.start);
+    }
+    inf
+}
@@ -0,0 +1,86 @@
+use super::*;
+use std::collections::VecDeque;
+
+// Sources of the values of read statements. A value is requested with the
+// type of the variable it is read into, so sources of text parse each item
+// only when it is read.
+
+pub trait Input {
+    // The next value, which has to be of type ty
+    fn read(&mut self, ty: &Type) -> Result<Val, RuntimeError>;
+}
+
+// Values given in advance, e.g. by a test
+pub struct Queue {
+    vals: VecDeque<Val>,
+}
+
+impl Queue {
+    pub fn new(vals: Vec<Val>) -> Queue {
+        Queue { vals: vals.into() }
+    }
+}
+
+impl Input for Queue {
+    fn read(&mut self, ty: &Type) -> Result<Val, RuntimeError> {
+        let v = self.vals.pop_front().ok_or(RuntimeError::NoInput)?;
+        if !has_type(&v, ty) {
+            return Err(RuntimeError::BadInput {
+                text: v.to_string(),
+                ty: ty.clone(),
+            });
+        }
+        Ok(v)
+    }
+}
+
+// One value per item of text, e.g. the lines of stdin or the command-line
+// arguments after "--"
+pub struct Text<I: Iterator<Item = String>> {
+    items: I,
+}
+
+impl<I: Iterator<Item = String>> Text<I> {
+    pub fn new(items: I) -> Text<I> {
+        Text { items }
+    }
+}
+
+impl<I: Iterator<Item = String>> Input for Text<I> {
+    fn read(&mut self, ty: &Type) -> Result<Val, RuntimeError> {
+        let text = self.items.next().ok_or(RuntimeError::NoInput)?;
+        parse(&text, ty).ok_or(RuntimeError::BadInput {
+            text,
+            ty: ty.clone(),
+        })
+    }
+}
+
+// The lines of stdin
+pub fn stdin() -> Text<impl Iterator<Item = String>> {
+    Text::new(std::io::stdin().lines().map_while(Result::ok))
+}
+
+// A value of type ty written as text; strings are taken as they are
+pub fn parse(text: &str, ty: &Type) -> Option<Val> {
+    match ty {
+        Type::TyInt => text.trim().parse().ok().map(|n| Val::mk_int(&n)),
+        Type::TyBool => text.trim().parse().ok().map(|b| Val::mk_bool(&b)),
+        Type::TyStr => Some(Val::mk_str(text)),
+        _ => None,
+    }
+}
+
+// Types whose values can be read
+pub fn readable(ty: &Type) -> bool {
+    matches!(ty, Type::TyInt | Type::TyBool | Type::TyStr)
+}
+
+fn has_type(v: &Val, ty: &Type) -> bool {
+    matches!(
+        (&v.flag, ty),
+        (Kind::ValueInt, Type::TyInt)
+            | (Kind::ValueBool, Type::TyBool)
+            | (Kind::ValueStr, Type::TyStr)
+    )
+}
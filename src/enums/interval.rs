@@ -0,0 +1,570 @@
+use super::*;
+
+// Abstract interpretation of Stmt over intervals. The structure follows
+// Exp::eval and Stmt::eval, but values are abstract: an int is described by
+// the interval of its possible values, a bool by the truth values it may
+// take. An abstract state of None means the program point is unreachable.
+
+// The bounds of an interval are exact, None stands for -inf and +inf
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Interval {
+    pub lo: Option<i32>,
+    pub hi: Option<i32>,
+}
+
+// Computations on bounds take infinity as INF, beyond any sum or product of
+// two i32 values
+const INF: i128 = 1 << 64;
+
+// A computed bound, infinite if it lies near INF, otherwise clamped to i32
+fn bound(n: i128) -> Option<i32> {
+    if n.unsigned_abs() > (INF / 2) as u128 {
+        return None;
+    }
+    Some(n.clamp(i32::MIN.into(), i32::MAX.into()) as i32)
+}
+
+impl Interval {
+    fn new(lo: i128, hi: i128) -> Interval {
+        Interval {
+            lo: bound(lo),
+            hi: bound(hi),
+        }
+    }
+    fn exact(n: i32) -> Interval {
+        Interval {
+            lo: Some(n),
+            hi: Some(n),
+        }
+    }
+    fn top() -> Interval {
+        Interval { lo: None, hi: None }
+    }
+    // The bounds as numbers, with min and max for the infinite ones
+    fn ends(self, min: i128, max: i128) -> (i128, i128) {
+        (
+            self.lo.map_or(min, i128::from),
+            self.hi.map_or(max, i128::from),
+        )
+    }
+    fn join(self, other: Interval) -> Interval {
+        Interval {
+            lo: self.lo.zip(other.lo).map(|(a, b)| a.min(b)),
+            hi: self.hi.zip(other.hi).map(|(a, b)| a.max(b)),
+        }
+    }
+    // Bounds which keep growing become infinite
+    fn widen(self, next: Interval) -> Interval {
+        let (lo, hi) = self.ends(-INF, INF);
+        let (next_lo, next_hi) = next.ends(-INF, INF);
+        Interval {
+            lo: if next_lo < lo { None } else { self.lo },
+            hi: if next_hi > hi { None } else { self.hi },
+        }
+    }
+    // Bounds which were widened are taken from next
+    fn narrow(self, next: Interval) -> Interval {
+        Interval {
+            lo: self.lo.or(next.lo),
+            hi: self.hi.or(next.hi),
+        }
+    }
+}
+
+impl Display for Interval {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let lo = self.lo.map_or("-inf".to_string(), |n| n.to_string());
+        let hi = self.hi.map_or("+inf".to_string(), |n| n.to_string());
+        write!(f, "[{}, {}]", lo, hi)
+    }
+}
+
+// The bounds of the sum or product e of i1 and i2, see Interval::ends
+fn arith(e: &Exp, i1: Interval, i2: Interval, min: i128, max: i128) -> (i128, i128) {
+    let (lo1, hi1) = i1.ends(min, max);
+    let (lo2, hi2) = i2.ends(min, max);
+    if let Exp::Plus { .. } = e {
+        return (lo1 + lo2, hi1 + hi2);
+    }
+    let ps = [
+        lo1.saturating_mul(lo2),
+        lo1.saturating_mul(hi2),
+        hi1.saturating_mul(lo2),
+        hi1.saturating_mul(hi2),
+    ];
+    (*ps.iter().min().unwrap(), *ps.iter().max().unwrap())
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum AbsVal {
+    Int(Interval),
+    // the truth values the bool may take
+    Bool { t: bool, f: bool },
+    // undefined or ill-typed
+    Top,
+}
+
+impl AbsVal {
+    fn bool(b: bool) -> AbsVal {
+        AbsVal::Bool { t: b, f: !b }
+    }
+    fn join(self, other: AbsVal) -> AbsVal {
+        match (self, other) {
+            (AbsVal::Int(i1), AbsVal::Int(i2)) => AbsVal::Int(i1.join(i2)),
+            (AbsVal::Bool { t: t1, f: f1 }, AbsVal::Bool { t: t2, f: f2 }) => AbsVal::Bool {
+                t: t1 || t2,
+                f: f1 || f2,
+            },
+            _ => AbsVal::Top,
+        }
+    }
+    fn may_be(&self, b: bool) -> bool {
+        match self {
+            AbsVal::Bool { t, f } => (b && *t) || (!b && *f),
+            _ => true,
+        }
+    }
+}
+
+impl Display for AbsVal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AbsVal::Int(i) => write!(f, "{}", i),
+            AbsVal::Bool { t: true, f: false } => write!(f, "true"),
+            AbsVal::Bool { t: false, f: true } => write!(f, "false"),
+            AbsVal::Bool { .. } => write!(f, "bool"),
+            AbsVal::Top => write!(f, "undefined"),
+        }
+    }
+}
+
+pub type AbsState = HashMap<String, AbsVal>;
+
+fn join(s1: Option<AbsState>, s2: Option<AbsState>) -> Option<AbsState> {
+    match (s1, s2) {
+        (None, s) | (s, None) => s,
+        (Some(mut s1), Some(s2)) => {
+            for (x, v2) in s2 {
+                let v = match s1.get(&x) {
+                    Some(v1) => v1.join(v2),
+                    None => v2,
+                };
+                s1.insert(x, v);
+            }
+            Some(s1)
+        }
+    }
+}
+
+// Applies f to the intervals of variables bound in both states
+fn combine(
+    old: Option<AbsState>,
+    next: Option<AbsState>,
+    f: fn(Interval, Interval) -> Interval,
+) -> Option<AbsState> {
+    match (old, next) {
+        (Some(mut old), Some(next)) => {
+            for (x, v) in next {
+                let v = match (old.get(&x), v) {
+                    (Some(AbsVal::Int(i1)), AbsVal::Int(i2)) => AbsVal::Int(f(*i1, i2)),
+                    (Some(v1), v2) => v1.join(v2),
+                    (None, v2) => v2,
+                };
+                old.insert(x, v);
+            }
+            Some(old)
+        }
+        (old, next) => join(old, next),
+    }
+}
+
+// Narrows the interval of the variable x to lo..=hi, where limits at or
+// beyond those of i32 leave a side open, or returns None if it becomes empty
+fn refine(mut s: AbsState, e: &Exp, lo: i128, hi: i128) -> Option<AbsState> {
+    let x = match e {
+        Exp::Var { name } => name,
+        Exp::Grp { exp } => return refine(s, exp, lo, hi),
+        _ => return Some(s),
+    };
+    if let Some(AbsVal::Int(i)) = s.get(x) {
+        let lo = if lo <= i32::MIN.into() { -INF } else { lo };
+        let hi = if hi >= i32::MAX.into() { INF } else { hi };
+        let (ilo, ihi) = i.ends(-INF, INF);
+        let (lo, hi) = (lo.max(ilo), hi.min(ihi));
+        if lo.max(i32::MIN.into()) > hi.min(i32::MAX.into()) {
+            return None;
+        }
+        s.insert(x.clone(), AbsVal::Int(Interval::new(lo, hi)));
+    }
+    Some(s)
+}
+
+pub struct Overflow {
+    // index of the statement, see parser::Program
+    pub stmt: usize,
+    pub exp: String,
+}
+
+pub struct Analysis {
+    // abstract state before each statement
+    pub points: Vec<Option<AbsState>>,
+    pub overflows: Vec<Overflow>,
+    record: bool,
+    // states at the break and continue statements of the enclosing loops
+    jumps: Vec<(Option<AbsState>, Option<AbsState>)>,
+}
+
+impl Analysis {
+    fn eval(&mut self, e: &Exp, s: &AbsState, idx: usize) -> AbsVal {
+        match e {
+            Exp::Var { name } => match s.get(name) {
+                Some(v) => *v,
+                None => AbsVal::Top,
+            },
+            Exp::Num { val } => AbsVal::Int(Interval::exact(*val)),
+            Exp::Bool { val } => AbsVal::bool(*val),
+            Exp::Plus { left, right } | Exp::Mult { left, right } => {
+                let v1 = self.eval(left, s, idx);
+                let v2 = self.eval(right, s, idx);
+                if let (AbsVal::Int(i1), AbsVal::Int(i2)) = (v1, v2) {
+                    // an infinite bound may be reached by the i32 limit
+                    let (lo, hi) = arith(e, i1, i2, i32::MIN.into(), i32::MAX.into());
+                    if lo < i32::MIN.into() || hi > i32::MAX.into() {
+                        self.overflow(idx, e);
+                    }
+                    let (lo, hi) = arith(e, i1, i2, -INF, INF);
+                    return AbsVal::Int(Interval::new(lo, hi));
+                }
+                AbsVal::Top
+            }
+            Exp::And { left, right } | Exp::Or { left, right } => {
+                let v1 = self.eval(left, s, idx);
+                let v2 = self.eval(right, s, idx);
+                if let (AbsVal::Bool { t: t1, f: f1 }, AbsVal::Bool { t: t2, f: f2 }) = (v1, v2) {
+                    if let Exp::And { .. } = e {
+                        return AbsVal::Bool {
+                            t: t1 && t2,
+                            f: f1 || f2,
+                        };
+                    }
+                    return AbsVal::Bool {
+                        t: t1 || t2,
+                        f: f1 && f2,
+                    };
+                }
+                AbsVal::Top
+            }
+            Exp::Equ { left, right } => {
+                let v1 = self.eval(left, s, idx);
+                let v2 = self.eval(right, s, idx);
+                match (v1, v2) {
+                    (AbsVal::Int(i1), AbsVal::Int(i2)) => {
+                        let (lo1, hi1) = i1.ends(i32::MIN.into(), i32::MAX.into());
+                        let (lo2, hi2) = i2.ends(i32::MIN.into(), i32::MAX.into());
+                        AbsVal::Bool {
+                            t: lo1 <= hi2 && lo2 <= hi1,
+                            f: !(lo1 == hi1 && i1 == i2),
+                        }
+                    }
+                    (AbsVal::Bool { t: t1, f: f1 }, AbsVal::Bool { t: t2, f: f2 }) => {
+                        AbsVal::Bool {
+                            t: (t1 && t2) || (f1 && f2),
+                            f: (t1 && f2) || (f1 && t2),
+                        }
+                    }
+                    _ => AbsVal::Top,
+                }
+            }
+            Exp::Less { left, right } => {
+                let v1 = self.eval(left, s, idx);
+                let v2 = self.eval(right, s, idx);
+                if let (AbsVal::Int(i1), AbsVal::Int(i2)) = (v1, v2) {
+                    let (lo1, hi1) = i1.ends(i32::MIN.into(), i32::MAX.into());
+                    let (lo2, hi2) = i2.ends(i32::MIN.into(), i32::MAX.into());
+                    return AbsVal::Bool {
+                        t: lo1 < hi2,
+                        f: hi1 >= lo2,
+                    };
+                }
+                AbsVal::Top
+            }
+            Exp::Neg { exp } => match self.eval(exp, s, idx) {
+                AbsVal::Bool { t, f } => AbsVal::Bool { t: f, f: t },
+                _ => AbsVal::Top,
+            },
+            Exp::Grp { exp } => self.eval(exp, s, idx),
+            // procedure and closure bodies are not analysed
+            Exp::Call { args, .. } => {
+                for a in args {
+                    self.eval(a, s, idx);
+                }
+                AbsVal::Top
+            }
+            Exp::Str { .. } | Exp::Lambda { .. } => AbsVal::Top,
+            // array contents are not tracked, only their lengths are known to be small
+            Exp::Array { elems } => {
+                for e in elems {
+                    self.eval(e, s, idx);
+                }
+                AbsVal::Top
+            }
+            Exp::Index { array, index } => {
+                self.eval(array, s, idx);
+                self.eval(index, s, idx);
+                AbsVal::Top
+            }
+            Exp::Update {
+                array,
+                index,
+                value,
+            } => {
+                self.eval(array, s, idx);
+                self.eval(index, s, idx);
+                self.eval(value, s, idx);
+                AbsVal::Top
+            }
+            Exp::Len { exp } => {
+                self.eval(exp, s, idx);
+                AbsVal::Int(Interval {
+                    lo: Some(0),
+                    hi: None,
+                })
+            }
+            // neither are record contents
+            Exp::Record { fields, .. } => {
+                for (_, e) in fields {
+                    self.eval(e, s, idx);
+                }
+                AbsVal::Top
+            }
+            Exp::Field { exp, .. } => {
+                self.eval(exp, s, idx);
+                AbsVal::Top
+            }
+            Exp::With { exp, value, .. } => {
+                self.eval(exp, s, idx);
+                self.eval(value, s, idx);
+                AbsVal::Top
+            }
+        }
+    }
+    fn overflow(&mut self, idx: usize, e: &Exp) {
+        let exp = e.pretty();
+        if self.record && !self.overflows.iter().any(|o| o.stmt == idx && o.exp == exp) {
+            self.overflows.push(Overflow { stmt: idx, exp });
+        }
+    }
+    // Restricts s to the states in which cond evaluates to b
+    fn assume(&mut self, cond: &Exp, s: Option<AbsState>, b: bool, idx: usize) -> Option<AbsState> {
+        let s = s?;
+        match cond {
+            Exp::Grp { exp } => return self.assume(exp, Some(s), b, idx),
+            Exp::Neg { exp } => return self.assume(exp, Some(s), !b, idx),
+            Exp::And { left, right } if b => {
+                let s = self.assume(left, Some(s), true, idx);
+                return self.assume(right, s, true, idx);
+            }
+            Exp::Or { left, right } if !b => {
+                let s = self.assume(left, Some(s), false, idx);
+                return self.assume(right, s, false, idx);
+            }
+            Exp::And { left, right } | Exp::Or { left, right } => {
+                let s1 = self.assume(left, Some(s.clone()), b, idx);
+                let s2 = self.assume(right, Some(s), b, idx);
+                return join(s1, s2);
+            }
+            _ => {}
+        }
+        if !self.eval(cond, &s, idx).may_be(b) {
+            return None;
+        }
+        let (left, right) = match cond {
+            Exp::Less { left, right } | Exp::Equ { left, right } => (left, right),
+            Exp::Var { name } => {
+                let mut s = s;
+                if let Some(AbsVal::Bool { .. }) = s.get(name) {
+                    s.insert(name.clone(), AbsVal::bool(b));
+                }
+                return Some(s);
+            }
+            _ => return Some(s),
+        };
+        let (i1, i2) = match (self.eval(left, &s, idx), self.eval(right, &s, idx)) {
+            (AbsVal::Int(i1), AbsVal::Int(i2)) => (i1, i2),
+            _ => return Some(s),
+        };
+        // the values of an operand lie within i32, unlike the sides left open
+        let (lo1, hi1) = i1.ends(i32::MIN.into(), i32::MAX.into());
+        let (lo2, hi2) = i2.ends(i32::MIN.into(), i32::MAX.into());
+        match (cond, b) {
+            (Exp::Less { .. }, true) => {
+                let s = refine(s, left, -INF, hi2 - 1)?;
+                refine(s, right, lo1 + 1, INF)
+            }
+            (Exp::Less { .. }, false) => {
+                let s = refine(s, left, lo2, INF)?;
+                refine(s, right, -INF, hi1)
+            }
+            (Exp::Equ { .. }, true) => {
+                let s = refine(s, left, lo2, hi2)?;
+                refine(s, right, lo1, hi1)
+            }
+            _ => Some(s),
+        }
+    }
+    fn exec(&mut self, stmt: &Stmt, idx: usize, s: Option<AbsState>) -> Option<AbsState> {
+        if let Stmt::Seq { first, second } = stmt {
+            let s = self.exec(first, idx, s);
+            return self.exec(second, idx + first.size(), s);
+        }
+        // the states at continue in body go on with step
+        if let Stmt::Step { body, step } = stmt {
+            let outer = self.jumps.last_mut().and_then(|j| j.1.take());
+            let end = self.exec(body, idx, s);
+            let continues = match self.jumps.last_mut() {
+                Some(j) => std::mem::replace(&mut j.1, outer),
+                None => None,
+            };
+            return self.exec(step, idx + body.size(), join(end, continues));
+        }
+        if self.record {
+            self.points[idx] = join(self.points[idx].take(), s.clone());
+        }
+        let mut s = s?;
+        match stmt {
+            Stmt::Seq { .. } | Stmt::Step { .. } => unreachable!(),
+            Stmt::Skip => Some(s),
+            Stmt::Decl { lhs, rhs, .. } | Stmt::Assign { lhs, rhs } => {
+                let v = self.eval(rhs, &s, idx);
+                s.insert(lhs.clone(), v);
+                Some(s)
+            }
+            Stmt::Print { print_exp } => {
+                self.eval(print_exp, &s, idx);
+                Some(s)
+            }
+            // any value of the type may be read
+            Stmt::Read { lhs, ty } => {
+                let v = match ty {
+                    Type::TyInt => AbsVal::Int(Interval::top()),
+                    Type::TyBool => AbsVal::Bool { t: true, f: true },
+                    _ => AbsVal::Top,
+                };
+                s.insert(lhs.clone(), v);
+                Some(s)
+            }
+            Stmt::Return { exp } => {
+                self.eval(exp, &s, idx);
+                None
+            }
+            Stmt::Break => {
+                if let Some(j) = self.jumps.last_mut() {
+                    j.0 = join(j.0.take(), Some(s));
+                }
+                None
+            }
+            Stmt::Continue => {
+                if let Some(j) = self.jumps.last_mut() {
+                    j.1 = join(j.1.take(), Some(s));
+                }
+                None
+            }
+            Stmt::ArrayAssign { index, rhs, .. } => {
+                self.eval(index, &s, idx);
+                self.eval(rhs, &s, idx);
+                Some(s)
+            }
+            Stmt::FieldAssign { rhs, .. } => {
+                self.eval(rhs, &s, idx);
+                Some(s)
+            }
+            // execution only goes on if the condition holds
+            Stmt::Assert { cond } | Stmt::Assume { cond } => self.assume(cond, Some(s), true, idx),
+            Stmt::IfThenElse {
+                cond,
+                then_stmt,
+                else_stmt,
+            } => {
+                let s1 = self.assume(cond, Some(s.clone()), true, idx);
+                let s2 = self.assume(cond, Some(s), false, idx);
+                let s1 = self.exec(then_stmt, idx + 1, s1);
+                let s2 = self.exec(else_stmt, idx + 1 + then_stmt.size(), s2);
+                join(s1, s2)
+            }
+            Stmt::While {
+                cond, stmt: body, ..
+            } => {
+                let record = self.record;
+                self.record = false;
+                let init = Some(s);
+                let mut head = init.clone();
+                let mut round = 0;
+                loop {
+                    let (next, _) = self.iterate(cond, body, idx, &init, &head);
+                    // a few plain rounds before widening keep small loops precise
+                    let widened = if round < 3 {
+                        join(head.clone(), next)
+                    } else {
+                        combine(head.clone(), next, Interval::widen)
+                    };
+                    if widened == head {
+                        break;
+                    }
+                    head = widened;
+                    round += 1;
+                }
+                let (next, mut breaks) = self.iterate(cond, body, idx, &init, &head);
+                head = combine(head, next, Interval::narrow);
+                self.record = record;
+                if record {
+                    self.points[idx] = join(self.points[idx].take(), head.clone());
+                    breaks = self.iterate(cond, body, idx, &init, &head).1;
+                }
+                join(self.assume(cond, head, false, idx), breaks)
+            }
+        }
+    }
+    // One iteration of the loop at idx from head: the state at the next
+    // evaluation of cond, joined with init, and the state after break
+    fn iterate(
+        &mut self,
+        cond: &Exp,
+        body: &Stmt,
+        idx: usize,
+        init: &Option<AbsState>,
+        head: &Option<AbsState>,
+    ) -> (Option<AbsState>, Option<AbsState>) {
+        let inner = self.assume(cond, head.clone(), true, idx);
+        self.jumps.push((None, None));
+        let end = self.exec(body, idx + 1, inner);
+        let (breaks, continues) = self.jumps.pop().unwrap();
+        (join(init.clone(), join(end, continues)), breaks)
+    }
+}
+
+// Runs the analysis; the returned state is the one at the end of the program
+pub fn analyze(stmt: &Stmt) -> (Analysis, Option<AbsState>) {
+    let mut a = Analysis {
+        points: vec![None; stmt.size()],
+        overflows: Vec::new(),
+        record: true,
+        jumps: Vec::new(),
+    };
+    let end = a.exec(stmt, 0, Some(AbsState::new()));
+    (a, end)
+}
+
+pub fn show_state(s: &Option<AbsState>) -> String {
+    match s {
+        None => "unreachable".to_string(),
+        Some(s) => {
+            let mut xs: Vec<_> = s
+                .iter()
+                .filter(|(_, v)| matches!(v, AbsVal::Int(_)))
+                .collect();
+            xs.sort_by(|a, b| a.0.cmp(b.0));
+            let xs: Vec<String> = xs.iter().map(|(x, v)| format!("{}: {}", x, v)).collect();
+            format!("{{{}}}", xs.join(", "))
+        }
+    }
+}
@@ -0,0 +1,795 @@
+use super::input::Input;
+use super::*;
+
+// Three-address code. The main program and every procedure become a
+// Function, a control flow graph of blocks whose instructions apply one
+// operator to variables and constants. Subexpressions are held in the
+// temporaries %1, %2, .. which are assigned once. Block 0 is the entry,
+// a block ends with a jump, a branch or a return.
+//
+// Lambdas and calls of closures have no counterpart, lower rejects them.
+
+#[derive(Clone, Debug)]
+pub enum Operand {
+    Var(String),
+    Const(Val),
+}
+
+#[derive(Clone, Debug)]
+pub enum Instr {
+    Copy {
+        dst: String,
+        src: Operand,
+    },
+    Binary {
+        dst: String,
+        op: BinOp,
+        left: Operand,
+        right: Operand,
+    },
+    Not {
+        dst: String,
+        src: Operand,
+    },
+    Array {
+        dst: String,
+        elems: Vec<Operand>,
+    },
+    Index {
+        dst: String,
+        array: Operand,
+        index: Operand,
+    },
+    Update {
+        dst: String,
+        array: Operand,
+        index: Operand,
+        value: Operand,
+    },
+    Len {
+        dst: String,
+        src: Operand,
+    },
+    Record {
+        dst: String,
+        name: String,
+        fields: Vec<(String, Operand)>,
+    },
+    Field {
+        dst: String,
+        src: Operand,
+        field: String,
+    },
+    With {
+        dst: String,
+        src: Operand,
+        field: String,
+        value: Operand,
+    },
+    // a call of the procedure name
+    Call {
+        dst: String,
+        name: String,
+        args: Vec<Operand>,
+    },
+    Read {
+        dst: String,
+        ty: Type,
+    },
+    Print {
+        src: Operand,
+    },
+    // text is the pretty printed condition, vars the variables of it
+    // whose values a failure reports
+    Assert {
+        cond: Operand,
+        text: String,
+        vars: Vec<(String, Operand)>,
+    },
+    // the value of the operand for the block control came from
+    Phi {
+        dst: String,
+        args: Vec<(usize, Operand)>,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub enum Term {
+    Jump(usize),
+    Branch {
+        cond: Operand,
+        then: usize,
+        other: usize,
+    },
+    Return(Option<Operand>),
+}
+
+#[derive(Clone, Debug)]
+pub struct Block {
+    pub instrs: Vec<Instr>,
+    pub term: Term,
+}
+
+#[derive(Clone, Debug)]
+pub struct Function {
+    pub name: String,
+    pub params: Vec<String>,
+    pub blocks: Vec<Block>,
+}
+
+// The main program comes first
+#[derive(Clone, Debug)]
+pub struct Module {
+    pub funcs: Vec<Function>,
+}
+
+impl Instr {
+    // The variable assigned, None for Print and Assert
+    pub fn dst(&self) -> Option<&String> {
+        match self {
+            Instr::Copy { dst, .. }
+            | Instr::Binary { dst, .. }
+            | Instr::Not { dst, .. }
+            | Instr::Array { dst, .. }
+            | Instr::Index { dst, .. }
+            | Instr::Update { dst, .. }
+            | Instr::Len { dst, .. }
+            | Instr::Record { dst, .. }
+            | Instr::Field { dst, .. }
+            | Instr::With { dst, .. }
+            | Instr::Call { dst, .. }
+            | Instr::Read { dst, .. }
+            | Instr::Phi { dst, .. } => Some(dst),
+            Instr::Print { .. } | Instr::Assert { .. } => None,
+        }
+    }
+    pub fn dst_mut(&mut self) -> Option<&mut String> {
+        match self {
+            Instr::Copy { dst, .. }
+            | Instr::Binary { dst, .. }
+            | Instr::Not { dst, .. }
+            | Instr::Array { dst, .. }
+            | Instr::Index { dst, .. }
+            | Instr::Update { dst, .. }
+            | Instr::Len { dst, .. }
+            | Instr::Record { dst, .. }
+            | Instr::Field { dst, .. }
+            | Instr::With { dst, .. }
+            | Instr::Call { dst, .. }
+            | Instr::Read { dst, .. }
+            | Instr::Phi { dst, .. } => Some(dst),
+            Instr::Print { .. } | Instr::Assert { .. } => None,
+        }
+    }
+    // The operands read, those of a Phi included
+    pub fn operands_mut(&mut self) -> Vec<&mut Operand> {
+        match self {
+            Instr::Copy { src, .. }
+            | Instr::Not { src, .. }
+            | Instr::Len { src, .. }
+            | Instr::Field { src, .. }
+            | Instr::Print { src } => vec![src],
+            Instr::Binary { left, right, .. } => vec![left, right],
+            Instr::Array { elems, .. } => elems.iter_mut().collect(),
+            Instr::Index { array, index, .. } => vec![array, index],
+            Instr::Update {
+                array,
+                index,
+                value,
+                ..
+            } => vec![array, index, value],
+            Instr::Record { fields, .. } => fields.iter_mut().map(|(_, x)| x).collect(),
+            Instr::With { src, value, .. } => vec![src, value],
+            Instr::Call { args, .. } => args.iter_mut().collect(),
+            Instr::Read { .. } => vec![],
+            Instr::Assert { cond, vars, .. } => {
+                let mut xs = vec![cond];
+                xs.extend(vars.iter_mut().map(|(_, x)| x));
+                xs
+            }
+            Instr::Phi { args, .. } => args.iter_mut().map(|(_, x)| x).collect(),
+        }
+    }
+}
+
+impl Term {
+    pub fn succs(&self) -> Vec<usize> {
+        match self {
+            Term::Jump(b) => vec![*b],
+            Term::Branch { then, other, .. } => vec![*then, *other],
+            Term::Return(_) => vec![],
+        }
+    }
+    fn succs_mut(&mut self) -> Vec<&mut usize> {
+        match self {
+            Term::Jump(b) => vec![b],
+            Term::Branch { then, other, .. } => vec![then, other],
+            Term::Return(_) => vec![],
+        }
+    }
+    pub fn operands_mut(&mut self) -> Vec<&mut Operand> {
+        match self {
+            Term::Jump(_) | Term::Return(None) => vec![],
+            Term::Branch { cond, .. } => vec![cond],
+            Term::Return(Some(x)) => vec![x],
+        }
+    }
+}
+
+impl Function {
+    // The predecessors of every block
+    pub fn preds(&self) -> Vec<Vec<usize>> {
+        let mut preds = vec![Vec::new(); self.blocks.len()];
+        for (b, block) in self.blocks.iter().enumerate() {
+            for s in block.term.succs() {
+                if !preds[s].contains(&b) {
+                    preds[s].push(b);
+                }
+            }
+        }
+        preds
+    }
+}
+
+// Lowers the main program stmt and the procedures
+pub fn lower(stmt: &Stmt, procs: &[Proc]) -> Result<Module, String> {
+    let mut funcs = vec![Lower::function("main", Vec::new(), stmt, procs)?];
+    for p in procs {
+        let params = p.params.iter().map(|(x, _)| x.clone()).collect();
+        funcs.push(Lower::function(&p.name, params, &p.body, procs)?);
+    }
+    Ok(Module { funcs })
+}
+
+struct Lower<'a> {
+    procs: &'a [Proc],
+    blocks: Vec<Block>,
+    // the block instructions are appended to
+    cur: usize,
+    temps: usize,
+    // the targets of continue and break of the enclosing loops
+    loops: Vec<(usize, usize)>,
+}
+
+impl<'a> Lower<'a> {
+    fn function(
+        name: &str,
+        params: Vec<String>,
+        body: &Stmt,
+        procs: &'a [Proc],
+    ) -> Result<Function, String> {
+        let mut l = Lower {
+            procs,
+            blocks: Vec::new(),
+            cur: 0,
+            temps: 0,
+            loops: Vec::new(),
+        };
+        l.block();
+        l.stmt(body)?;
+        Ok(prune(Function {
+            name: name.to_string(),
+            params,
+            blocks: l.blocks,
+        }))
+    }
+    // A new block which returns unless it is ended otherwise
+    fn block(&mut self) -> usize {
+        self.blocks.push(Block {
+            instrs: Vec::new(),
+            term: Term::Return(None),
+        });
+        self.blocks.len() - 1
+    }
+    // Ends the current block with term and continues in next
+    fn end(&mut self, term: Term, next: usize) {
+        self.blocks[self.cur].term = term;
+        self.cur = next;
+    }
+    fn emit(&mut self, i: Instr) {
+        self.blocks[self.cur].instrs.push(i);
+    }
+    fn temp(&mut self) -> String {
+        self.temps += 1;
+        format!("%{}", self.temps)
+    }
+    fn stmt(&mut self, stmt: &Stmt) -> Result<(), String> {
+        match stmt {
+            Stmt::Skip | Stmt::Assume { .. } => {}
+            Stmt::Seq { first, second } => {
+                self.stmt(first)?;
+                self.stmt(second)?;
+            }
+            Stmt::Decl { lhs, rhs, .. } | Stmt::Assign { lhs, rhs } => {
+                let src = self.exp(rhs)?;
+                self.emit(Instr::Copy {
+                    dst: lhs.clone(),
+                    src,
+                });
+            }
+            Stmt::ArrayAssign { lhs, index, rhs } => {
+                let index = self.exp(index)?;
+                let value = self.exp(rhs)?;
+                self.emit(Instr::Update {
+                    dst: lhs.clone(),
+                    array: Operand::Var(lhs.clone()),
+                    index,
+                    value,
+                });
+            }
+            Stmt::FieldAssign { lhs, field, rhs } => {
+                let value = self.exp(rhs)?;
+                self.emit(Instr::With {
+                    dst: lhs.clone(),
+                    src: Operand::Var(lhs.clone()),
+                    field: field.clone(),
+                    value,
+                });
+            }
+            Stmt::Print { print_exp } => {
+                let src = self.exp(print_exp)?;
+                self.emit(Instr::Print { src });
+            }
+            Stmt::Assert { cond } => {
+                let c = self.exp(cond)?;
+                let vars = cond
+                    .vars()
+                    .into_iter()
+                    .filter(|x| !self.procs.iter().any(|p| p.name == *x))
+                    .map(|x| (x.clone(), Operand::Var(x)))
+                    .collect();
+                self.emit(Instr::Assert {
+                    cond: c,
+                    text: cond.pretty(),
+                    vars,
+                });
+            }
+            Stmt::Read { lhs, ty } => self.emit(Instr::Read {
+                dst: lhs.clone(),
+                ty: ty.clone(),
+            }),
+            Stmt::Return { exp } => {
+                let x = self.exp(exp)?;
+                let next = self.block();
+                self.end(Term::Return(Some(x)), next);
+            }
+            Stmt::Break | Stmt::Continue => {
+                let (cont, exit) = *self
+                    .loops
+                    .last()
+                    .ok_or("break or continue outside a loop")?;
+                let target = if let Stmt::Break = stmt { exit } else { cont };
+                let next = self.block();
+                self.end(Term::Jump(target), next);
+            }
+            Stmt::IfThenElse {
+                cond,
+                then_stmt,
+                else_stmt,
+            } => {
+                let cond = self.exp(cond)?;
+                let then = self.block();
+                let other = self.block();
+                let join = self.block();
+                self.end(Term::Branch { cond, then, other }, then);
+                self.stmt(then_stmt)?;
+                self.end(Term::Jump(join), other);
+                self.stmt(else_stmt)?;
+                self.end(Term::Jump(join), join);
+            }
+            Stmt::While { cond, stmt, .. } => {
+                let head = self.block();
+                self.end(Term::Jump(head), head);
+                let cond = self.exp(cond)?;
+                let body = self.block();
+                let exit = self.block();
+                self.end(
+                    Term::Branch {
+                        cond,
+                        then: body,
+                        other: exit,
+                    },
+                    body,
+                );
+                self.loops.push((head, exit));
+                self.stmt(stmt)?;
+                self.loops.pop();
+                self.end(Term::Jump(head), exit);
+            }
+            // continue in body jumps to the block of step
+            Stmt::Step { body, step } => {
+                let cont = self.block();
+                let (head, exit) = self.loops.pop().ok_or("step outside a loop")?;
+                self.loops.push((cont, exit));
+                self.stmt(body)?;
+                self.loops.pop();
+                self.loops.push((head, exit));
+                self.end(Term::Jump(cont), cont);
+                self.stmt(step)?;
+            }
+        }
+        Ok(())
+    }
+    // The operand holding the value of e
+    fn exp(&mut self, e: &Exp) -> Result<Operand, String> {
+        if let Some((op, left, right)) = e.binary() {
+            let left = self.exp(left)?;
+            let right = self.exp(right)?;
+            let dst = self.temp();
+            self.emit(Instr::Binary {
+                dst: dst.clone(),
+                op,
+                left,
+                right,
+            });
+            return Ok(Operand::Var(dst));
+        }
+        let mut i = match e {
+            Exp::Var { name } => return Ok(Operand::Var(name.clone())),
+            Exp::Num { val } => return Ok(Operand::Const(Val::mk_int(val))),
+            Exp::Bool { val } => return Ok(Operand::Const(Val::mk_bool(val))),
+            Exp::Str { val } => return Ok(Operand::Const(Val::mk_str(val))),
+            Exp::Grp { exp } => return self.exp(exp),
+            Exp::Neg { exp } => Instr::Not {
+                dst: String::new(),
+                src: self.exp(exp)?,
+            },
+            Exp::Call { name, args } if self.procs.iter().any(|p| p.name == *name) => {
+                let mut xs = Vec::new();
+                for a in args {
+                    xs.push(self.exp(a)?);
+                }
+                Instr::Call {
+                    dst: String::new(),
+                    name: name.clone(),
+                    args: xs,
+                }
+            }
+            Exp::Call { name, .. } => {
+                return Err(format!(
+                    "call of closure {} is not supported by the IR",
+                    name
+                ))
+            }
+            Exp::Lambda { .. } => return Err("lambdas are not supported by the IR".to_string()),
+            Exp::Array { elems } => {
+                let mut xs = Vec::new();
+                for e in elems {
+                    xs.push(self.exp(e)?);
+                }
+                Instr::Array {
+                    dst: String::new(),
+                    elems: xs,
+                }
+            }
+            Exp::Index { array, index } => Instr::Index {
+                dst: String::new(),
+                array: self.exp(array)?,
+                index: self.exp(index)?,
+            },
+            Exp::Update {
+                array,
+                index,
+                value,
+            } => Instr::Update {
+                dst: String::new(),
+                array: self.exp(array)?,
+                index: self.exp(index)?,
+                value: self.exp(value)?,
+            },
+            Exp::Len { exp } => Instr::Len {
+                dst: String::new(),
+                src: self.exp(exp)?,
+            },
+            Exp::Record { name, fields } => {
+                let mut xs = Vec::new();
+                for (x, e) in fields {
+                    xs.push((x.clone(), self.exp(e)?));
+                }
+                Instr::Record {
+                    dst: String::new(),
+                    name: name.clone(),
+                    fields: xs,
+                }
+            }
+            Exp::Field { exp, field } => Instr::Field {
+                dst: String::new(),
+                src: self.exp(exp)?,
+                field: field.clone(),
+            },
+            Exp::With { exp, field, value } => Instr::With {
+                dst: String::new(),
+                src: self.exp(exp)?,
+                field: field.clone(),
+                value: self.exp(value)?,
+            },
+            _ => unreachable!(),
+        };
+        // the temporary is numbered after those of the operands
+        let dst = self.temp();
+        *i.dst_mut().unwrap() = dst.clone();
+        self.emit(i);
+        Ok(Operand::Var(dst))
+    }
+}
+
+// Removes the blocks not reachable from the entry, e.g. those after a
+// return, and numbers the others in the order they were created
+fn prune(mut f: Function) -> Function {
+    let mut reached = vec![false; f.blocks.len()];
+    let mut todo = vec![0];
+    while let Some(b) = todo.pop() {
+        if !reached[b] {
+            reached[b] = true;
+            todo.extend(f.blocks[b].term.succs());
+        }
+    }
+    let mut number = vec![0; f.blocks.len()];
+    let mut n = 0;
+    for b in 0..f.blocks.len() {
+        number[b] = n;
+        if reached[b] {
+            n += 1;
+        }
+    }
+    let mut blocks = Vec::new();
+    for (b, mut block) in f.blocks.drain(..).enumerate() {
+        if reached[b] {
+            for s in block.term.succs_mut() {
+                *s = number[*s];
+            }
+            blocks.push(block);
+        }
+    }
+    f.blocks = blocks;
+    f
+}
+
+impl Display for Operand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Operand::Var(x) => write!(f, "{}", x),
+            Operand::Const(v) => write!(f, "{}", v),
+        }
+    }
+}
+
+fn list<T: Display>(xs: &[T]) -> String {
+    let xs: Vec<String> = xs.iter().map(|x| x.to_string()).collect();
+    xs.join(", ")
+}
+
+impl Display for Instr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Instr::Copy { dst, src } => write!(f, "{} = {}", dst, src),
+            Instr::Binary {
+                dst,
+                op,
+                left,
+                right,
+            } => write!(f, "{} = {} {} {}", dst, left, op.symbol(), right),
+            Instr::Not { dst, src } => write!(f, "{} = !{}", dst, src),
+            Instr::Array { dst, elems } => write!(f, "{} = [{}]", dst, list(elems)),
+            Instr::Index { dst, array, index } => write!(f, "{} = {}[{}]", dst, array, index),
+            Instr::Update {
+                dst,
+                array,
+                index,
+                value,
+            } => write!(f, "{} = {}[{} := {}]", dst, array, index, value),
+            Instr::Len { dst, src } => write!(f, "{} = len({})", dst, src),
+            Instr::Record { dst, name, fields } => {
+                let xs: Vec<String> = fields
+                    .iter()
+                    .map(|(x, v)| format!("{}: {}", x, v))
+                    .collect();
+                write!(f, "{} = {} {{ {} }}", dst, name, xs.join(", "))
+            }
+            Instr::Field { dst, src, field } => write!(f, "{} = {}.{}", dst, src, field),
+            Instr::With {
+                dst,
+                src,
+                field,
+                value,
+            } => write!(f, "{} = {}.{{{} := {}}}", dst, src, field, value),
+            Instr::Call { dst, name, args } => write!(f, "{} = {}({})", dst, name, list(args)),
+            Instr::Read { dst, ty } => write!(f, "{} = read {}", dst, ty),
+            Instr::Print { src } => write!(f, "print {}", src),
+            Instr::Assert { cond, text, .. } => write!(f, "assert {} // {}", cond, text),
+            Instr::Phi { dst, args } => {
+                let xs: Vec<String> = args.iter().map(|(b, x)| format!("b{}: {}", b, x)).collect();
+                write!(f, "{} = phi({})", dst, xs.join(", "))
+            }
+        }
+    }
+}
+
+impl Display for Term {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Term::Jump(b) => write!(f, "jump b{}", b),
+            Term::Branch { cond, then, other } => {
+                write!(f, "branch {}, b{}, b{}", cond, then, other)
+            }
+            Term::Return(None) => write!(f, "return"),
+            Term::Return(Some(x)) => write!(f, "return {}", x),
+        }
+    }
+}
+
+impl Display for Function {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}({}) {{", self.name, self.params.join(", "))?;
+        for (b, block) in self.blocks.iter().enumerate() {
+            writeln!(f, "b{}:", b)?;
+            for i in &block.instrs {
+                writeln!(f, "  {}", i)?;
+            }
+            writeln!(f, "  {}", block.term)?;
+        }
+        writeln!(f, "}}")
+    }
+}
+
+impl Display for Module {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (k, func) in self.funcs.iter().enumerate() {
+            if k > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", func)?;
+        }
+        Ok(())
+    }
+}
+
+// Runs main with the operators of Exp::eval and the same errors as exec.
+// Modules in and out of SSA form are both run.
+pub fn run(m: &Module, input: &mut dyn Input) -> Result<(), RuntimeError> {
+    Interp { m, input }.call(0, Vec::new(), 0).map(|_| ())
+}
+
+struct Interp<'a> {
+    m: &'a Module,
+    input: &'a mut dyn Input,
+}
+
+impl Interp<'_> {
+    // depth is the number of calls main is nested in
+    fn call(
+        &mut self,
+        f: usize,
+        args: Vec<Val>,
+        depth: usize,
+    ) -> Result<Option<Val>, RuntimeError> {
+        let func = &self.m.funcs[f];
+        let mut vars: HashMap<String, Val> = func.params.iter().cloned().zip(args).collect();
+        let (mut prev, mut b) = (0, 0);
+        loop {
+            let block = &func.blocks[b];
+            // the phis of a block read their operands at the same time
+            let mut phis = Vec::new();
+            for i in &block.instrs {
+                if let Instr::Phi { dst, args } = i {
+                    if let Some((_, x)) = args.iter().find(|(p, _)| *p == prev) {
+                        phis.push((dst.clone(), value(&vars, x)));
+                    }
+                }
+            }
+            vars.extend(phis);
+            for i in &block.instrs {
+                self.instr(i, &mut vars, depth)?;
+            }
+            let next = match &block.term {
+                Term::Jump(t) => *t,
+                // a condition which is no bool leaves the loop or takes the else branch
+                Term::Branch { cond, then, other } => match value(&vars, cond).val_b {
+                    Some(true) => *then,
+                    _ => *other,
+                },
+                Term::Return(x) => return Ok(x.as_ref().map(|x| value(&vars, x))),
+            };
+            prev = b;
+            b = next;
+        }
+    }
+    fn instr(
+        &mut self,
+        i: &Instr,
+        vars: &mut HashMap<String, Val>,
+        depth: usize,
+    ) -> Result<(), RuntimeError> {
+        let v = match i {
+            Instr::Phi { .. } => return Ok(()),
+            Instr::Copy { src, .. } => value(vars, src),
+            Instr::Binary {
+                op, left, right, ..
+            } => op.apply(&value(vars, left), &value(vars, right))?,
+            Instr::Not { src, .. } => value(vars, src).not(),
+            Instr::Array { elems, .. } => {
+                Val::mk_array(elems.iter().map(|x| value(vars, x)).collect())
+            }
+            Instr::Index { array, index, .. } => value(vars, array).at(&value(vars, index))?,
+            Instr::Update {
+                array,
+                index,
+                value: x,
+                ..
+            } => value(vars, array).set(&value(vars, index), value(vars, x))?,
+            Instr::Len { src, .. } => value(vars, src).length(),
+            Instr::Record { name, fields, .. } => Val::mk_record(RecordVal {
+                name: name.clone(),
+                fields: fields
+                    .iter()
+                    .map(|(x, v)| (x.clone(), value(vars, v)))
+                    .collect(),
+            }),
+            Instr::Field { src, field, .. } => value(vars, src).field(field),
+            Instr::With {
+                src,
+                field,
+                value: x,
+                ..
+            } => value(vars, src).with(field, value(vars, x)),
+            Instr::Call { name, args, .. } => {
+                let funcs = &self.m.funcs;
+                match (1..funcs.len()).find(|&f| funcs[f].name == *name) {
+                    Some(f) if funcs[f].params.len() == args.len() => {
+                        if depth >= MAX_CALL_DEPTH {
+                            return Err(RuntimeError::StackOverflow {
+                                depth: MAX_CALL_DEPTH,
+                            });
+                        }
+                        let args = args.iter().map(|x| value(vars, x)).collect();
+                        match self.call(f, args, depth + 1)? {
+                            Some(v) => v,
+                            None => return Err(RuntimeError::NoReturn { name: name.clone() }),
+                        }
+                    }
+                    _ => Val::mk_undefined(),
+                }
+            }
+            Instr::Read { ty, .. } => self.input.read(ty)?,
+            Instr::Print { src } => {
+                println!("{}", value(vars, src).output());
+                return Ok(());
+            }
+            Instr::Assert {
+                cond,
+                text,
+                vars: xs,
+            } => {
+                if value(vars, cond).val_b != Some(true) {
+                    let bindings = xs
+                        .iter()
+                        .map(|(x, v)| {
+                            let v = match v {
+                                Operand::Var(y) => vars.get(y),
+                                Operand::Const(_) => None,
+                            };
+                            (
+                                x.clone(),
+                                v.map_or("undeclared".to_string(), |v| v.to_string()),
+                            )
+                        })
+                        .collect();
+                    return Err(RuntimeError::AssertionFailed {
+                        cond: text.clone(),
+                        bindings,
+                    });
+                }
+                return Ok(());
+            }
+        };
+        vars.insert(i.dst().unwrap().clone(), v);
+        Ok(())
+    }
+}
+
+fn value(vars: &HashMap<String, Val>, x: &Operand) -> Val {
+    match x {
+        Operand::Var(x) => vars.get(x).cloned().unwrap_or_else(Val::mk_undefined),
+        Operand::Const(v) => v.clone(),
+    }
+}
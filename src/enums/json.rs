@@ -0,0 +1,252 @@
+use std::fmt;
+
+// A small JSON value with a parser and a printer, enough for the messages
+// of the language server
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Num(f64),
+    Str(String),
+    Arr(Vec<Json>),
+    // fields keep their order
+    Obj(Vec<(String, Json)>),
+}
+
+impl Json {
+    // The field k of an object, Null if there is none
+    pub fn get(&self, k: &str) -> &Json {
+        match self {
+            Json::Obj(fields) => fields
+                .iter()
+                .find(|(x, _)| x == k)
+                .map(|(_, v)| v)
+                .unwrap_or(&Json::Null),
+            _ => &Json::Null,
+        }
+    }
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+    pub fn as_usize(&self) -> Option<usize> {
+        match self {
+            Json::Num(n) if *n >= 0.0 && n.fract() == 0.0 => Some(*n as usize),
+            _ => None,
+        }
+    }
+}
+
+pub fn obj(fields: Vec<(&str, Json)>) -> Json {
+    Json::Obj(
+        fields
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v))
+            .collect(),
+    )
+}
+
+pub fn str(s: &str) -> Json {
+    Json::Str(s.to_string())
+}
+
+pub fn num(n: usize) -> Json {
+    Json::Num(n as f64)
+}
+
+fn write_str(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    write!(f, "\"")
+}
+
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Json::Null => write!(f, "null"),
+            Json::Bool(b) => write!(f, "{}", b),
+            Json::Num(n) if n.fract() == 0.0 && n.abs() < 1e15 => write!(f, "{}", *n as i64),
+            Json::Num(n) => write!(f, "{}", n),
+            Json::Str(s) => write_str(f, s),
+            Json::Arr(xs) => {
+                write!(f, "[")?;
+                for (i, x) in xs.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", x)?;
+                }
+                write!(f, "]")
+            }
+            Json::Obj(fields) => {
+                write!(f, "{{")?;
+                for (i, (k, v)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write_str(f, k)?;
+                    write!(f, ":{}", v)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+struct Reader {
+    chars: Vec<char>,
+    at: usize,
+}
+
+impl Reader {
+    fn ws(&mut self) {
+        while self.at < self.chars.len() && self.chars[self.at].is_whitespace() {
+            self.at += 1;
+        }
+    }
+    fn error<T>(&self, msg: &str) -> Result<T, String> {
+        Err(format!("{} at offset {}", msg, self.at))
+    }
+    fn eat(&mut self, s: &str) -> bool {
+        let n = s.chars().count();
+        if self.chars[self.at..].iter().take(n).copied().eq(s.chars()) {
+            self.at += n;
+            return true;
+        }
+        false
+    }
+    fn value(&mut self) -> Result<Json, String> {
+        self.ws();
+        let v = match self.chars.get(self.at).copied() {
+            None => return self.error("unexpected end of input"),
+            Some('{') => {
+                self.at += 1;
+                let mut fields = Vec::new();
+                self.ws();
+                if !self.eat("}") {
+                    loop {
+                        self.ws();
+                        let k = match self.value()? {
+                            Json::Str(k) => k,
+                            _ => return self.error("expected a field name"),
+                        };
+                        self.ws();
+                        if !self.eat(":") {
+                            return self.error("expected ':'");
+                        }
+                        fields.push((k, self.value()?));
+                        self.ws();
+                        if self.eat("}") {
+                            break;
+                        }
+                        if !self.eat(",") {
+                            return self.error("expected ',' or '}'");
+                        }
+                    }
+                }
+                Json::Obj(fields)
+            }
+            Some('[') => {
+                self.at += 1;
+                let mut xs = Vec::new();
+                self.ws();
+                if !self.eat("]") {
+                    loop {
+                        xs.push(self.value()?);
+                        self.ws();
+                        if self.eat("]") {
+                            break;
+                        }
+                        if !self.eat(",") {
+                            return self.error("expected ',' or ']'");
+                        }
+                    }
+                }
+                Json::Arr(xs)
+            }
+            Some('"') => {
+                self.at += 1;
+                let mut s = String::new();
+                loop {
+                    let c = match self.chars.get(self.at) {
+                        Some(c) => *c,
+                        None => return self.error("unterminated string"),
+                    };
+                    self.at += 1;
+                    match c {
+                        '"' => break,
+                        '\\' => {
+                            let e = self.chars.get(self.at).copied();
+                            self.at += 1;
+                            match e {
+                                Some('n') => s.push('\n'),
+                                Some('r') => s.push('\r'),
+                                Some('t') => s.push('\t'),
+                                Some('b') => s.push('\u{8}'),
+                                Some('f') => s.push('\u{c}'),
+                                Some('u') => {
+                                    let hex: String =
+                                        self.chars.iter().skip(self.at).take(4).collect();
+                                    self.at += 4;
+                                    match u32::from_str_radix(&hex, 16) {
+                                        Ok(n) => s.push(char::from_u32(n).unwrap_or('\u{fffd}')),
+                                        Err(_) => return self.error("invalid escape"),
+                                    }
+                                }
+                                Some(c) => s.push(c),
+                                None => return self.error("unterminated string"),
+                            }
+                        }
+                        c => s.push(c),
+                    }
+                }
+                Json::Str(s)
+            }
+            Some(c) if c == '-' || c.is_ascii_digit() => {
+                let start = self.at;
+                while self.at < self.chars.len()
+                    && (self.chars[self.at].is_ascii_digit()
+                        || "+-.eE".contains(self.chars[self.at]))
+                {
+                    self.at += 1;
+                }
+                let text: String = self.chars[start..self.at].iter().collect();
+                match text.parse() {
+                    Ok(n) => Json::Num(n),
+                    Err(_) => return self.error("invalid number"),
+                }
+            }
+            _ if self.eat("true") => Json::Bool(true),
+            _ if self.eat("false") => Json::Bool(false),
+            _ if self.eat("null") => Json::Null,
+            _ => return self.error("unexpected character"),
+        };
+        Ok(v)
+    }
+}
+
+pub fn parse(src: &str) -> Result<Json, String> {
+    let mut r = Reader {
+        chars: src.chars().collect(),
+        at: 0,
+    };
+    let v = r.value()?;
+    r.ws();
+    if r.at < r.chars.len() {
+        return r.error("trailing characters");
+    }
+    Ok(v)
+}
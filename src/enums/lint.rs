@@ -0,0 +1,322 @@
+use super::parser::Program;
+use super::*;
+use std::collections::HashSet;
+
+// Every warning has a stable code. A warning is suppressed by a comment
+// "lint: allow(<code or name>, ...)" on the statement's line or the line
+// directly above it, or by passing the code to lint_program.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Code {
+    UnusedVariable,
+    DeadStore,
+    UnreachableBranch,
+    InfiniteLoop,
+}
+
+impl Code {
+    pub fn id(&self) -> &'static str {
+        match self {
+            Code::UnusedVariable => "W001",
+            Code::DeadStore => "W002",
+            Code::UnreachableBranch => "W003",
+            Code::InfiniteLoop => "W004",
+        }
+    }
+    pub fn name(&self) -> &'static str {
+        match self {
+            Code::UnusedVariable => "unused-variable",
+            Code::DeadStore => "dead-store",
+            Code::UnreachableBranch => "unreachable-branch",
+            Code::InfiniteLoop => "infinite-loop",
+        }
+    }
+    fn matches(&self, s: &str) -> bool {
+        s == self.id() || s == self.name()
+    }
+}
+
+#[derive(Debug)]
+pub struct Warning {
+    pub code: Code,
+    // index of the statement, see parser::Program
+    pub stmt: usize,
+    pub msg: String,
+}
+
+impl Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "warning[{}] {}: {}",
+            self.code.id(),
+            self.code.name(),
+            self.msg
+        )
+    }
+}
+
+// Value of a bool condition that does not depend on the state
+fn constant(cond: &Exp) -> Option<bool> {
+    if !cond.vars().is_empty() || cond.infer(&mut TyState::new()) != Type::TyBool {
+        return None;
+    }
+    cond.eval(&mut ValState::new()).val_b
+}
+
+// Variables which are declared or assigned somewhere in stmt
+fn written(stmt: &Stmt) -> HashSet<String> {
+    let mut xs = HashSet::new();
+    stmt.visit(&mut 0, &mut |_, s| match s {
+        Stmt::Decl { lhs, .. }
+        | Stmt::Read { lhs, .. }
+        | Stmt::Assign { lhs, .. }
+        | Stmt::ArrayAssign { lhs, .. }
+        | Stmt::FieldAssign { lhs, .. } => {
+            xs.insert(lhs.clone());
+        }
+        _ => {}
+    });
+    xs
+}
+
+// Variables which are read somewhere in stmt
+fn read(stmt: &Stmt) -> HashSet<String> {
+    let mut xs = HashSet::new();
+    stmt.visit(&mut 0, &mut |_, s| {
+        let e = match s {
+            Stmt::Decl { rhs, .. } | Stmt::Assign { rhs, .. } | Stmt::FieldAssign { rhs, .. } => {
+                rhs
+            }
+            Stmt::While { cond, .. } | Stmt::IfThenElse { cond, .. } => cond,
+            Stmt::Print { print_exp } => print_exp,
+            Stmt::Assert { cond } | Stmt::Assume { cond } => cond,
+            Stmt::Return { exp } => exp,
+            Stmt::ArrayAssign { index, rhs, .. } => {
+                xs.extend(index.vars());
+                rhs
+            }
+            Stmt::Seq { .. }
+            | Stmt::Step { .. }
+            | Stmt::Skip
+            | Stmt::Break
+            | Stmt::Continue
+            | Stmt::Read { .. } => return,
+        };
+        xs.extend(e.vars());
+    });
+    xs
+}
+
+// Backwards liveness analysis. Returns the variables live before stmt,
+// given the ones live after it. If record is set, assignments whose value
+// is not live afterwards are collected as dead stores.
+struct Liveness {
+    dead: Vec<(usize, String)>,
+    // variables live after break and continue in the enclosing loops
+    loops: Vec<(HashSet<String>, HashSet<String>)>,
+}
+
+impl Liveness {
+    fn live(
+        &mut self,
+        stmt: &Stmt,
+        idx: usize,
+        out: HashSet<String>,
+        record: bool,
+    ) -> HashSet<String> {
+        match stmt {
+            Stmt::Skip => out,
+            Stmt::Seq { first, second } => {
+                let mid = self.live(second, idx + first.size(), out, record);
+                self.live(first, idx, mid, record)
+            }
+            // continue in body goes on with step
+            Stmt::Step { body, step } => {
+                let mid = self.live(step, idx + body.size(), out, record);
+                let outer = self.loops.pop();
+                let exit = outer.clone().map(|l| l.0).unwrap_or_default();
+                self.loops.push((exit, mid.clone()));
+                let live = self.live(body, idx, mid, record);
+                self.loops.pop();
+                self.loops.extend(outer);
+                live
+            }
+            Stmt::Decl { lhs, rhs, .. } => {
+                let mut live = out;
+                live.remove(lhs);
+                live.extend(rhs.vars());
+                live
+            }
+            // the input is consumed either way, so it is no dead store
+            Stmt::Read { lhs, .. } => {
+                let mut live = out;
+                live.remove(lhs);
+                live
+            }
+            Stmt::Assign { lhs, rhs } => {
+                if record && !out.contains(lhs) {
+                    self.dead.push((idx, lhs.clone()));
+                }
+                let mut live = out;
+                live.remove(lhs);
+                live.extend(rhs.vars());
+                live
+            }
+            Stmt::Print { print_exp: cond } | Stmt::Assert { cond } | Stmt::Assume { cond } => {
+                let mut live = out;
+                live.extend(cond.vars());
+                live
+            }
+            // the old array or record flows into the updated one
+            Stmt::ArrayAssign { lhs, index, rhs } => {
+                if record && !out.contains(lhs) {
+                    self.dead.push((idx, lhs.clone()));
+                }
+                let mut live = out;
+                live.extend(index.vars());
+                live.extend(rhs.vars());
+                live
+            }
+            Stmt::FieldAssign { lhs, rhs, .. } => {
+                if record && !out.contains(lhs) {
+                    self.dead.push((idx, lhs.clone()));
+                }
+                let mut live = out;
+                live.extend(rhs.vars());
+                live
+            }
+            // nothing after a return is executed
+            Stmt::Return { exp } => exp.vars().into_iter().collect(),
+            Stmt::Break => self.loops.last().map(|l| l.0.clone()).unwrap_or_default(),
+            Stmt::Continue => self.loops.last().map(|l| l.1.clone()).unwrap_or_default(),
+            Stmt::IfThenElse {
+                cond,
+                then_stmt,
+                else_stmt,
+            } => {
+                let mut live = self.live(then_stmt, idx + 1, out.clone(), record);
+                let else_idx = idx + 1 + then_stmt.size();
+                live.extend(self.live(else_stmt, else_idx, out, record));
+                live.extend(cond.vars());
+                live
+            }
+            Stmt::While {
+                cond, stmt: body, ..
+            } => {
+                let mut head = out.clone();
+                head.extend(cond.vars());
+                loop {
+                    self.loops.push((out.clone(), head.clone()));
+                    let mut next = self.live(body, idx + 1, head.clone(), false);
+                    self.loops.pop();
+                    next.extend(head.iter().cloned());
+                    if next == head {
+                        break;
+                    }
+                    head = next;
+                }
+                if record {
+                    self.loops.push((out, head.clone()));
+                    self.live(body, idx + 1, head.clone(), true);
+                    self.loops.pop();
+                }
+                head
+            }
+        }
+    }
+}
+
+pub fn lint(stmt: &Stmt) -> Vec<Warning> {
+    let mut warnings = Vec::new();
+    let read = read(stmt);
+    stmt.visit(&mut 0, &mut |idx, s| match s {
+        Stmt::Decl { lhs, .. } | Stmt::Read { lhs, .. } if !read.contains(lhs) => {
+            warnings.push(Warning {
+                code: Code::UnusedVariable,
+                stmt: idx,
+                msg: format!("variable {} is declared but never read", lhs),
+            })
+        }
+        Stmt::IfThenElse { cond, .. } => {
+            if let Some(b) = constant(cond) {
+                let branch = if b { "else" } else { "then" };
+                warnings.push(Warning {
+                    code: Code::UnreachableBranch,
+                    stmt: idx,
+                    msg: format!(
+                        "condition {} is always {}, the {} branch is unreachable",
+                        cond.pretty(),
+                        b,
+                        branch
+                    ),
+                });
+            }
+        }
+        Stmt::While {
+            cond, stmt: body, ..
+        } => {
+            // a loop left by break may well run on an unchanged condition
+            if constant(cond) == Some(false)
+                || body.jumps(0).iter().any(|(_, s)| matches!(s, Stmt::Break))
+            {
+                return;
+            }
+            let changed = written(body);
+            if !cond.vars().iter().any(|x| changed.contains(x)) {
+                warnings.push(Warning {
+                    code: Code::InfiniteLoop,
+                    stmt: idx,
+                    msg: format!(
+                        "condition {} never changes inside the loop body",
+                        cond.pretty()
+                    ),
+                });
+            }
+        }
+        _ => {}
+    });
+    let mut liveness = Liveness {
+        dead: Vec::new(),
+        loops: Vec::new(),
+    };
+    liveness.live(stmt, 0, HashSet::new(), true);
+    for (idx, x) in liveness.dead {
+        // never read at all is already reported as unused
+        if read.contains(&x) {
+            warnings.push(Warning {
+                code: Code::DeadStore,
+                stmt: idx,
+                msg: format!("value assigned to {} is never read", x),
+            });
+        }
+    }
+    warnings.sort_by_key(|w| w.stmt);
+    warnings
+}
+
+fn allowed(comment: &str) -> Vec<String> {
+    let list = comment
+        .strip_prefix("lint:")
+        .map(|s| s.trim())
+        .and_then(|s| s.strip_prefix("allow("))
+        .and_then(|s| s.strip_suffix(')'));
+    match list {
+        Some(list) => list.split(',').map(|s| s.trim().to_string()).collect(),
+        None => Vec::new(),
+    }
+}
+
+// Lints a parsed program, leaving out suppressed warnings and the codes in allow
+pub fn lint_program(p: &Program, allow: &[String]) -> Vec<Warning> {
+    lint(&p.stmt)
+        .into_iter()
+        .filter(|w| !allow.iter().any(|a| w.code.matches(a)))
+        .filter(|w| {
+            let line = p.pos[w.stmt].line;
+            !p.comments.iter().any(|c| {
+                (c.pos.line == line || c.pos.line + 1 == line)
+                    && allowed(&c.text).iter().any(|a| w.code.matches(a))
+            })
+        })
+        .collect()
+}
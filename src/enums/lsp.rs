@@ -0,0 +1,705 @@
+use super::json::{self, num, obj, str, Json};
+use super::parser::{self, Pos, Program, Span};
+use super::*;
+use std::io::{BufRead, Write};
+
+// A language server for .imp files speaking JSON-RPC over stdio. It
+// publishes the errors of the parser and the type checker, shows the type
+// of the expression under the cursor, jumps from a variable to its Decl and
+// formats documents with the pretty printer. The document is parsed again
+// for every request.
+
+pub struct Diagnostic {
+    pub span: Span,
+    pub msg: String,
+}
+
+// The expressions of statement i which are not part of another one, in
+// source order
+fn top_exps(p: &Program, i: usize) -> Vec<&parser::ExpInfo> {
+    let own: Vec<&parser::ExpInfo> = p.exps.iter().filter(|e| e.stmt == i).collect();
+    let mut tops: Vec<&parser::ExpInfo> = own
+        .iter()
+        .filter(|e| {
+            !own.iter()
+                .any(|o| o.span != e.span && e.span.within(&o.span))
+        })
+        .copied()
+        .collect();
+    tops.sort_by_key(|e| (e.span.start.line, e.span.start.col));
+    tops
+}
+
+// Range of the variable name at the start of statement i
+fn name_span(p: &Program, i: usize, name: &str) -> Span {
+    let start = p.pos[i];
+    Span {
+        start,
+        end: Pos {
+            line: start.line,
+            col: start.col + name.chars().count(),
+        },
+    }
+}
+
+// t with the parameters of the lambdas around e
+fn env_at(p: &Program, e: &parser::ExpInfo, t: &TyState) -> TyState {
+    let mut t = t.clone();
+    // outer lambdas come later, so inner parameters win
+    for l in p.exps.iter().rev() {
+        if let Exp::Lambda { param, ty, .. } = &l.exp {
+            if l.stmt == e.stmt && e.span.within(&l.span) && !std::ptr::eq(l, e) {
+                t.insert(param.clone(), ty.clone());
+            }
+        }
+    }
+    t
+}
+
+// The innermost ill-typed expression within top, undeclared variables
+// are reported as such
+fn ill_typed(p: &Program, top: &parser::ExpInfo, t: &TyState) -> Diagnostic {
+    let inside = |e: &&parser::ExpInfo| e.stmt == top.stmt && e.span.within(&top.span);
+    // inner expressions come first, so the first ill-typed one is innermost
+    let e = p
+        .exps
+        .iter()
+        .filter(inside)
+        .find(|e| e.exp.infer(&mut env_at(p, e, t)) == Type::TyIllTyped)
+        .unwrap_or(top);
+    let msg = match &e.exp {
+        Exp::Var { name } => format!("undeclared variable {}", name),
+        exp => format!("ill-typed expression {}", exp.pretty()),
+    };
+    Diagnostic { span: e.span, msg }
+}
+
+// Type environment before every statement and the type errors found by
+// Stmt::check, which continues with the next statement after an error
+fn analyze(p: &Program) -> (Vec<TyState>, Vec<Diagnostic>) {
+    let main = TyState::with_procs(&p.procs).with_records(&p.records);
+    let mut bodies = vec![(0, &*p.stmt, main.clone())];
+    for proc in &p.procs {
+        bodies.push((proc.start, &*proc.body, main.enter(proc)));
+    }
+    let mut envs = Vec::new();
+    let mut diags = Vec::new();
+    for (start, body, mut t) in bodies {
+        // the statements of the loop bodies
+        let mut loops = Vec::new();
+        body.visit(&mut start.clone(), &mut |i, st| {
+            if let Stmt::While { stmt, .. } = st {
+                loops.push(i + 1..i + 1 + stmt.size());
+            }
+        });
+        let mut idx = start;
+        body.visit(&mut idx, &mut |i, st| {
+            envs.push(t.clone());
+            let tops = top_exps(p, i);
+            let before = t.clone();
+            match st {
+                Stmt::IfThenElse { cond, .. } | Stmt::While { cond, .. } => {
+                    match cond.infer(&mut t) {
+                        Type::TyBool => {}
+                        Type::TyIllTyped => diags.push(ill_typed(p, tops[0], &before)),
+                        ty => diags.push(Diagnostic {
+                            span: tops[0].span,
+                            msg: format!("condition must be bool, found {}", ty),
+                        }),
+                    }
+                    if let Stmt::While { inv: Some(inv), .. } = st {
+                        match inv.infer(&mut t) {
+                            Type::TyBool => {}
+                            Type::TyIllTyped => diags.push(ill_typed(p, tops[1], &before)),
+                            ty => diags.push(Diagnostic {
+                                span: tops[1].span,
+                                msg: format!("invariant must be bool, found {}", ty),
+                            }),
+                        }
+                    }
+                }
+                Stmt::Skip | Stmt::Seq { .. } | Stmt::Step { .. } => {}
+                Stmt::Break | Stmt::Continue if loops.iter().any(|l| l.contains(&i)) => {}
+                Stmt::Break | Stmt::Continue => {
+                    let name = st.pretty();
+                    diags.push(Diagnostic {
+                        span: name_span(p, i, &name),
+                        msg: format!("{} outside of a loop", name),
+                    })
+                }
+                Stmt::Assign { lhs, .. }
+                | Stmt::ArrayAssign { lhs, .. }
+                | Stmt::FieldAssign { lhs, .. }
+                    if !t.contains_key(lhs) =>
+                {
+                    diags.push(Diagnostic {
+                        span: name_span(p, i, lhs),
+                        msg: format!("undeclared variable {}", lhs),
+                    })
+                }
+                _ if st.check(&mut t) => {}
+                Stmt::Read { ty, .. } => diags.push(Diagnostic {
+                    span: name_span(p, i, "read"),
+                    msg: format!("cannot read a value of type {}", ty),
+                }),
+                Stmt::ArrayAssign { lhs, index, rhs } => {
+                    let (ti, tr) = (index.infer(&mut t), rhs.infer(&mut t));
+                    let (span, msg) = match &t[lhs] {
+                        _ if ti == Type::TyIllTyped => {
+                            diags.push(ill_typed(p, tops[0], &before));
+                            return;
+                        }
+                        _ if tr == Type::TyIllTyped => {
+                            diags.push(ill_typed(p, tops[1], &before));
+                            return;
+                        }
+                        Type::TyArray(_) if ti != Type::TyInt => {
+                            (tops[0].span, format!("index must be int, found {}", ti))
+                        }
+                        Type::TyArray(_) => (
+                            tops[1].span,
+                            format!(
+                                "cannot assign {} to element of {} of type {}",
+                                tr, lhs, t[lhs]
+                            ),
+                        ),
+                        ty => (
+                            name_span(p, i, lhs),
+                            format!("{} of type {} is not an array", lhs, ty),
+                        ),
+                    };
+                    diags.push(Diagnostic { span, msg });
+                }
+                Stmt::FieldAssign { lhs, field, rhs } => {
+                    let tr = rhs.infer(&mut t);
+                    let (span, msg) = match (&t[lhs], t.field(&t[lhs], field)) {
+                        _ if tr == Type::TyIllTyped => {
+                            diags.push(ill_typed(p, tops[0], &before));
+                            return;
+                        }
+                        (_, Some(ty)) => (
+                            tops[0].span,
+                            format!("cannot assign {} to {}.{} of type {}", tr, lhs, field, ty),
+                        ),
+                        (Type::TyRecord(name), None) => (
+                            name_span(p, i, lhs),
+                            format!("record {} has no field {}", name, field),
+                        ),
+                        (ty, None) => (
+                            name_span(p, i, lhs),
+                            format!("{} of type {} is not a record", lhs, ty),
+                        ),
+                    };
+                    diags.push(Diagnostic { span, msg });
+                }
+                Stmt::Decl { rhs: e, .. }
+                | Stmt::Assign { rhs: e, .. }
+                | Stmt::Print { print_exp: e }
+                | Stmt::Assert { cond: e }
+                | Stmt::Assume { cond: e }
+                | Stmt::Return { exp: e } => {
+                    let ty = e.infer(&mut t);
+                    let msg = match st {
+                        _ if ty == Type::TyIllTyped => {
+                            diags.push(ill_typed(p, tops[0], &before));
+                            return;
+                        }
+                        Stmt::Decl {
+                            lhs, ty: Some(a), ..
+                        } => format!("{} is declared as {} but initialized with {}", lhs, a, ty),
+                        Stmt::Assign { lhs, .. } => {
+                            format!("cannot assign {} to {} of type {}", ty, lhs, t[lhs])
+                        }
+                        Stmt::Return { .. } => match &t.ret {
+                            Some(ret) => format!("return type must be {}, found {}", ret, ty),
+                            None => "return outside of a procedure".to_string(),
+                        },
+                        _ => format!("condition must be bool, found {}", ty),
+                    };
+                    diags.push(Diagnostic {
+                        span: tops[0].span,
+                        msg,
+                    });
+                }
+            }
+        });
+    }
+    (envs, diags)
+}
+
+// The errors in the records and the procedure headers, and procedures
+// which may end without return, as found by check_program
+fn declarations(p: &Program) -> Vec<Diagnostic> {
+    let t = TyState::with_procs(&p.procs).with_records(&p.records);
+    let mut diags = Vec::new();
+    let mut error = |pos: Pos, name: &str, msg: String| {
+        let end = Pos {
+            line: pos.line,
+            col: pos.col + name.chars().count(),
+        };
+        diags.push(Diagnostic {
+            span: Span { start: pos, end },
+            msg,
+        })
+    };
+    for (k, r) in p.records.iter().enumerate() {
+        let pos = p.record_pos[k];
+        if p.records[..k].iter().any(|o| o.name == r.name) {
+            error(pos, &r.name, format!("duplicate record {}", r.name));
+        }
+        for (j, (x, ty)) in r.fields.iter().enumerate() {
+            if r.fields[..j].iter().any(|(y, _)| y == x) {
+                error(
+                    pos,
+                    &r.name,
+                    format!("duplicate field {} in record {}", x, r.name),
+                );
+            }
+            if !t.declared(ty) {
+                error(
+                    pos,
+                    &r.name,
+                    format!("undeclared type {} in record {}", ty, r.name),
+                );
+            }
+        }
+    }
+    for (k, f) in p.procs.iter().enumerate() {
+        let pos = p.proc_pos[k];
+        if p.procs[..k].iter().any(|o| o.name == f.name) {
+            error(pos, &f.name, format!("duplicate procedure {}", f.name));
+        }
+        for (j, (x, ty)) in f.params.iter().enumerate() {
+            if f.params[..j].iter().any(|(y, _)| y == x) {
+                error(
+                    pos,
+                    &f.name,
+                    format!("duplicate parameter {} of {}", x, f.name),
+                );
+            }
+            if !t.declared(ty) {
+                error(
+                    pos,
+                    &f.name,
+                    format!("undeclared type {} in {}", ty, f.name),
+                );
+            }
+        }
+        if !t.declared(&f.ret) {
+            error(
+                pos,
+                &f.name,
+                format!("undeclared type {} in {}", f.ret, f.name),
+            );
+        }
+        if !f.body.always_returns() {
+            error(
+                pos,
+                &f.name,
+                format!("procedure {} may end without return", f.name),
+            );
+        }
+    }
+    diags
+}
+
+// The errors of hm if the program has lambdas without annotation, else
+// those of Stmt::check, after those of declarations
+pub fn diagnostics(p: &Program) -> Vec<Diagnostic> {
+    let mut diags = declarations(p);
+    if !hm::needs_inference(p) {
+        diags.extend(analyze(p).1);
+        return diags;
+    }
+    let mut stmts = Vec::new();
+    p.stmt.visit(&mut 0, &mut |_, s| stmts.push(s));
+    for proc in &p.procs {
+        proc.body
+            .visit(&mut proc.start.clone(), &mut |_, s| stmts.push(s));
+    }
+    let errors = hm::infer(p).errors.into_iter().map(|e| {
+        let inside = |x: &&parser::ExpInfo| x.stmt == e.stmt && Some(x.exp.pretty()) == e.exp;
+        // the expression or else the first name of the statement
+        let span = match p.exps.iter().find(inside) {
+            Some(x) => x.span,
+            None => {
+                let text = stmts[e.stmt].pretty();
+                let mut words = text.split(|c: char| !c.is_alphanumeric() && c != '_');
+                name_span(p, e.stmt, words.next().unwrap_or(""))
+            }
+        };
+        Diagnostic { span, msg: e.msg }
+    });
+    diags.extend(errors);
+    diags
+}
+
+// The innermost expression at pos with its type
+pub fn hover(p: &Program, pos: Pos) -> Option<(&parser::ExpInfo, Type)> {
+    let (envs, _) = analyze(p);
+    let e = p.exps.iter().find(|e| e.span.contains(pos))?;
+    let ty = e.exp.infer(&mut env_at(p, e, &envs[e.stmt]));
+    Some((e, ty))
+}
+
+// Range of the name in the Decl which introduced the variable at pos
+pub fn definition(p: &Program, pos: Pos) -> Option<Span> {
+    let mut stmts = Vec::new();
+    p.stmt.visit(&mut 0, &mut |i, s| stmts.push((i, s)));
+    for proc in &p.procs {
+        proc.body
+            .visit(&mut proc.start.clone(), &mut |i, s| stmts.push((i, s)));
+    }
+    // a variable in an expression or the left-hand side of a statement;
+    // the Decl of a use is an earlier statement, a Decl defines itself
+    let (name, at, last) = match p.exps.iter().find(|e| e.span.contains(pos)) {
+        // lambda parameters have no Decl
+        Some(
+            e @ parser::ExpInfo {
+                exp: Exp::Var { name },
+                stmt,
+                ..
+            },
+        ) if !env_at(p, e, &TyState::new()).contains_key(name) => (name, *stmt, *stmt),
+        Some(_) => return None,
+        None => stmts.iter().find_map(|(i, s)| match s {
+            Stmt::Decl { lhs, .. } if name_span(p, *i, lhs).contains(pos) => Some((lhs, *i, i + 1)),
+            Stmt::Assign { lhs, .. }
+            | Stmt::ArrayAssign { lhs, .. }
+            | Stmt::FieldAssign { lhs, .. }
+                if name_span(p, *i, lhs).contains(pos) =>
+            {
+                Some((lhs, *i, *i))
+            }
+            _ => None,
+        })?,
+    };
+    // the search stays in the procedure body or main program of the use
+    let start = p
+        .procs
+        .iter()
+        .map(|q| q.start)
+        .filter(|s| *s <= at)
+        .max()
+        .unwrap_or(0);
+    stmts[start..last].iter().rev().find_map(|(i, s)| match s {
+        Stmt::Decl { lhs, .. } if lhs == name => Some(name_span(p, *i, lhs)),
+        _ => None,
+    })
+}
+
+fn layout(st: &Stmt, depth: usize, out: &mut Vec<String>) {
+    let indent = "  ".repeat(depth);
+    match st {
+        Stmt::Seq { first, second } if second.is_for() => {
+            let Stmt::While { stmt, .. } = &**second else {
+                unreachable!()
+            };
+            out.push(format!("{}{} {{", indent, for_header(Some(first), second)));
+            layout(stmt, depth + 1, out);
+            out.push(format!("{}}}", indent));
+        }
+        Stmt::While { stmt, .. } if st.is_for() => {
+            out.push(format!("{}{} {{", indent, for_header(None, st)));
+            layout(stmt, depth + 1, out);
+            out.push(format!("{}}}", indent));
+        }
+        // the step is part of the for header
+        Stmt::Step { body, .. } => layout(body, depth, out),
+        Stmt::Seq { first, second } => {
+            layout(first, depth, out);
+            out.last_mut().unwrap().push(';');
+            layout(second, depth, out);
+        }
+        Stmt::IfThenElse {
+            cond,
+            then_stmt,
+            else_stmt,
+        } => {
+            out.push(format!("{}if {} {{", indent, cond.pretty()));
+            layout(then_stmt, depth + 1, out);
+            out.push(format!("{}}} else {{", indent));
+            layout(else_stmt, depth + 1, out);
+            out.push(format!("{}}}", indent));
+        }
+        Stmt::While {
+            cond, inv, stmt, ..
+        } => {
+            let inv = match inv {
+                Some(inv) => format!(" invariant {}", inv.pretty()),
+                None => String::new(),
+            };
+            out.push(format!("{}while {}{} {{", indent, cond.pretty(), inv));
+            layout(stmt, depth + 1, out);
+            out.push(format!("{}}}", indent));
+        }
+        st => out.push(format!("{}{}", indent, st.pretty())),
+    }
+}
+
+// The program laid out with one statement per line, None if it has
+// comments, which the pretty printer would drop
+pub fn format(p: &Program) -> Option<String> {
+    if !p.comments.is_empty() {
+        return None;
+    }
+    let mut out: Vec<String> = p.records.iter().map(|r| r.header()).collect();
+    for proc in &p.procs {
+        out.push(format!("{} {{", proc.header()));
+        layout(&proc.body, 1, &mut out);
+        out.push("}".to_string());
+    }
+    layout(&p.stmt, 0, &mut out);
+    Some(out.join("\n") + "\n")
+}
+
+// LSP positions count lines and characters from 0
+fn to_pos(j: &Json) -> Option<Pos> {
+    Some(Pos {
+        line: j.get("line").as_usize()? + 1,
+        col: j.get("character").as_usize()? + 1,
+    })
+}
+
+fn position(p: Pos) -> Json {
+    obj(vec![
+        ("line", num(p.line - 1)),
+        ("character", num(p.col - 1)),
+    ])
+}
+
+fn range(s: Span) -> Json {
+    obj(vec![("start", position(s.start)), ("end", position(s.end))])
+}
+
+// Reads a message with its Content-Length header, None at the end of input
+fn read_message(input: &mut impl BufRead) -> Option<String> {
+    let mut len = None;
+    loop {
+        let mut line = String::new();
+        if input.read_line(&mut line).ok()? == 0 {
+            return None;
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((k, v)) = line.split_once(':') {
+            if k.eq_ignore_ascii_case("Content-Length") {
+                len = v.trim().parse().ok();
+            }
+        }
+    }
+    let mut body = vec![0; len?];
+    input.read_exact(&mut body).ok()?;
+    String::from_utf8(body).ok()
+}
+
+pub struct Server<W: Write> {
+    out: W,
+    docs: HashMap<String, String>,
+    shutdown: bool,
+}
+
+impl<W: Write> Server<W> {
+    pub fn new(out: W) -> Self {
+        Server {
+            out,
+            docs: HashMap::new(),
+            shutdown: false,
+        }
+    }
+    fn send(&mut self, msg: Json) {
+        let body = msg.to_string();
+        write!(self.out, "Content-Length: {}\r\n\r\n{}", body.len(), body).unwrap();
+        self.out.flush().unwrap();
+    }
+    fn reply(&mut self, id: Json, result: Json) {
+        self.send(obj(vec![
+            ("jsonrpc", str("2.0")),
+            ("id", id),
+            ("result", result),
+        ]));
+    }
+    fn reply_error(&mut self, id: Json, code: i32, msg: String) {
+        let error = obj(vec![
+            ("code", Json::Num(code as f64)),
+            ("message", Json::Str(msg)),
+        ]);
+        self.send(obj(vec![
+            ("jsonrpc", str("2.0")),
+            ("id", id),
+            ("error", error),
+        ]));
+    }
+    fn publish(&mut self, uri: &str) {
+        let diags = match self.docs.get(uri).map(|src| parser::parse(src)) {
+            None => vec![],
+            Some(Err(e)) => {
+                let end = Pos {
+                    line: e.pos.line,
+                    col: e.pos.col + 1,
+                };
+                vec![(Span { start: e.pos, end }, e.msg)]
+            }
+            Some(Ok(p)) => diagnostics(&p)
+                .into_iter()
+                .map(|d| (d.span, d.msg))
+                .collect(),
+        };
+        let diags = diags
+            .into_iter()
+            .map(|(span, msg)| {
+                obj(vec![
+                    ("range", range(span)),
+                    ("severity", num(1)),
+                    ("source", str("imp")),
+                    ("message", Json::Str(msg)),
+                ])
+            })
+            .collect();
+        let params = obj(vec![("uri", str(uri)), ("diagnostics", Json::Arr(diags))]);
+        self.send(obj(vec![
+            ("jsonrpc", str("2.0")),
+            ("method", str("textDocument/publishDiagnostics")),
+            ("params", params),
+        ]));
+    }
+    // The parsed document and the position of a request
+    fn at(&self, params: &Json) -> Option<(String, Program, Pos)> {
+        let uri = params.get("textDocument").get("uri").as_str()?;
+        let p = parser::parse(self.docs.get(uri)?).ok()?;
+        Some((uri.to_string(), p, to_pos(params.get("position"))?))
+    }
+    fn request(&mut self, method: &str, params: &Json) -> Result<Json, (i32, String)> {
+        match method {
+            "initialize" => {
+                let caps = obj(vec![
+                    ("textDocumentSync", num(1)),
+                    ("hoverProvider", Json::Bool(true)),
+                    ("definitionProvider", Json::Bool(true)),
+                    ("documentFormattingProvider", Json::Bool(true)),
+                ]);
+                let info = obj(vec![("name", str("imp"))]);
+                Ok(obj(vec![("capabilities", caps), ("serverInfo", info)]))
+            }
+            "shutdown" => {
+                self.shutdown = true;
+                Ok(Json::Null)
+            }
+            "textDocument/hover" => Ok(match self.at(params) {
+                Some((_, p, pos)) => match hover(&p, pos) {
+                    Some((e, ty)) => {
+                        let value = format!("{}: {}", e.exp.pretty(), ty);
+                        let contents = obj(vec![
+                            ("kind", str("plaintext")),
+                            ("value", Json::Str(value)),
+                        ]);
+                        obj(vec![("contents", contents), ("range", range(e.span))])
+                    }
+                    None => Json::Null,
+                },
+                None => Json::Null,
+            }),
+            "textDocument/definition" => Ok(match self.at(params) {
+                Some((uri, p, pos)) => match definition(&p, pos) {
+                    Some(span) => obj(vec![("uri", Json::Str(uri)), ("range", range(span))]),
+                    None => Json::Null,
+                },
+                None => Json::Null,
+            }),
+            "textDocument/formatting" => {
+                let uri = params.get("textDocument").get("uri").as_str().unwrap_or("");
+                let src = match self.docs.get(uri) {
+                    Some(src) => src,
+                    None => return Ok(Json::Null),
+                };
+                let text = match parser::parse(src).ok().as_ref().and_then(format) {
+                    Some(text) => text,
+                    None => return Ok(Json::Null),
+                };
+                // replace everything up to a position after the last line
+                let end = Pos {
+                    line: src.lines().count() + 2,
+                    col: 1,
+                };
+                let all = Span {
+                    start: Pos { line: 1, col: 1 },
+                    end,
+                };
+                Ok(Json::Arr(vec![obj(vec![
+                    ("range", range(all)),
+                    ("newText", Json::Str(text)),
+                ])]))
+            }
+            _ => Err((-32601, format!("method not found: {}", method))),
+        }
+    }
+    fn notification(&mut self, method: &str, params: &Json) {
+        let doc = params.get("textDocument");
+        let uri = doc.get("uri").as_str().unwrap_or("").to_string();
+        match method {
+            "textDocument/didOpen" => {
+                let text = doc.get("text").as_str().unwrap_or("").to_string();
+                self.docs.insert(uri.clone(), text);
+                self.publish(&uri);
+            }
+            // full synchronisation, the last change has the whole text
+            "textDocument/didChange" => {
+                if let Json::Arr(changes) = params.get("contentChanges") {
+                    if let Some(text) = changes.last().and_then(|c| c.get("text").as_str()) {
+                        self.docs.insert(uri.clone(), text.to_string());
+                    }
+                }
+                self.publish(&uri);
+            }
+            "textDocument/didClose" => {
+                self.docs.remove(&uri);
+                self.publish(&uri);
+            }
+            _ => {}
+        }
+    }
+    // Handles one message, false after exit
+    pub fn handle(&mut self, msg: &str) -> bool {
+        let msg = match json::parse(msg) {
+            Ok(msg) => msg,
+            Err(e) => {
+                self.reply_error(Json::Null, -32700, e);
+                return true;
+            }
+        };
+        let method = msg.get("method").as_str().unwrap_or("").to_string();
+        let params = msg.get("params");
+        if method == "exit" {
+            return false;
+        }
+        match msg.get("id") {
+            Json::Null => self.notification(&method, params),
+            id if self.shutdown => {
+                self.reply_error(id.clone(), -32600, "server is shut down".to_string())
+            }
+            id => match self.request(&method, params) {
+                Ok(result) => self.reply(id.clone(), result),
+                Err((code, e)) => self.reply_error(id.clone(), code, e),
+            },
+        }
+        true
+    }
+}
+
+// Serves requests from input until exit, the exit code is 0 if the client
+// asked for a shutdown before
+pub fn serve(mut input: impl BufRead, out: impl Write) -> i32 {
+    let mut server = Server::new(out);
+    while let Some(msg) = read_message(&mut input) {
+        if !server.handle(&msg) {
+            break;
+        }
+    }
+    if server.shutdown {
+        0
+    } else {
+        1
+    }
+}
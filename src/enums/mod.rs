@@ -0,0 +1,2269 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::ops::{Deref, DerefMut};
+use std::rc::Rc;
+
+pub mod c;
+pub mod coverage;
+pub mod debug;
+pub mod derivation;
+pub mod hm;
+pub mod input;
+pub mod interval;
+pub mod ir;
+pub mod json;
+pub mod lint;
+pub mod lsp;
+pub mod parser;
+pub mod scalar;
+pub mod smallstep;
+pub mod smt;
+pub mod solver;
+pub mod ssa;
+pub mod symbolic;
+pub mod wat;
+pub mod wp;
+pub mod x86;
+
+//TODO Find way to use enum variants as types or add errors for mismatched variant
+#[derive(PartialEq, Debug, Clone)]
+pub enum Kind {
+    ValueInt,
+    ValueBool,
+    ValueStr,
+    Closure,
+    Array,
+    Record,
+    Undefined,
+}
+#[derive(Debug, Clone)]
+pub struct Val {
+    pub flag: Kind,
+    pub val_i: Option<i32>,
+    pub val_b: Option<bool>,
+    pub val_s: Option<String>,
+    pub val_c: Option<Rc<Closure>>,
+    // arrays are values, an update copies them unless they are not shared
+    pub val_a: Option<Rc<Vec<Val>>>,
+    // records are values like arrays
+    pub val_r: Option<Rc<RecordVal>>,
+}
+// The fields of a record in the order of the literal which created it
+#[derive(Debug, Clone)]
+pub struct RecordVal {
+    pub name: String,
+    pub fields: Vec<(String, Val)>,
+}
+impl RecordVal {
+    pub fn get(&self, field: &str) -> Option<&Val> {
+        self.fields.iter().find(|(x, _)| x == field).map(|(_, v)| v)
+    }
+    fn get_mut(&mut self, field: &str) -> Option<&mut Val> {
+        self.fields
+            .iter_mut()
+            .find(|(x, _)| x == field)
+            .map(|(_, v)| v)
+    }
+}
+// A lambda together with the variables of the frame it was evaluated in
+#[derive(Debug)]
+pub struct Closure {
+    pub param: String,
+    pub ty: Type,
+    pub body: Exp,
+    pub env: HashMap<String, Val>,
+}
+impl Val {
+    fn mk_int(x: &i32) -> Val {
+        Val {
+            flag: Kind::ValueInt,
+            val_i: Some(*x),
+            val_b: None,
+            val_s: None,
+            val_c: None,
+            val_a: None,
+            val_r: None,
+        }
+    }
+    fn mk_bool(x: &bool) -> Val {
+        Val {
+            flag: Kind::ValueBool,
+            val_i: None,
+            val_b: Some(*x),
+            val_s: None,
+            val_c: None,
+            val_a: None,
+            val_r: None,
+        }
+    }
+    fn mk_str(x: &str) -> Val {
+        Val {
+            flag: Kind::ValueStr,
+            val_i: None,
+            val_b: None,
+            val_s: Some(x.to_string()),
+            val_c: None,
+            val_a: None,
+            val_r: None,
+        }
+    }
+    fn mk_closure(c: Closure) -> Val {
+        Val {
+            flag: Kind::Closure,
+            val_i: None,
+            val_b: None,
+            val_s: None,
+            val_c: Some(Rc::new(c)),
+            val_a: None,
+            val_r: None,
+        }
+    }
+    fn mk_array(xs: Vec<Val>) -> Val {
+        Val {
+            flag: Kind::Array,
+            val_i: None,
+            val_b: None,
+            val_s: None,
+            val_c: None,
+            val_a: Some(Rc::new(xs)),
+            val_r: None,
+        }
+    }
+    fn mk_record(r: RecordVal) -> Val {
+        Val {
+            flag: Kind::Record,
+            val_i: None,
+            val_b: None,
+            val_s: None,
+            val_c: None,
+            val_a: None,
+            val_r: Some(Rc::new(r)),
+        }
+    }
+    fn mk_undefined() -> Val {
+        Val {
+            flag: Kind::Undefined,
+            val_i: None,
+            val_b: None,
+            val_s: None,
+            val_c: None,
+            val_a: None,
+            val_r: None,
+        }
+    }
+}
+// The operations on values of Exp::eval, which the IR shares. Operands of
+// the wrong kind give Undefined.
+impl Val {
+    fn not(&self) -> Val {
+        match self.val_b {
+            Some(b) if self.flag == Kind::ValueBool => Val::mk_bool(&!b),
+            _ => Val::mk_undefined(),
+        }
+    }
+    fn at(&self, i: &Val) -> Result<Val, RuntimeError> {
+        match (&self.val_a, i.val_i) {
+            (Some(xs), Some(i)) => Ok(xs[bound(i, xs.len())?].clone()),
+            _ => Ok(Val::mk_undefined()),
+        }
+    }
+    // A copy of the array with v at i
+    fn set(self, i: &Val, v: Val) -> Result<Val, RuntimeError> {
+        match (self.val_a, i.val_i) {
+            (Some(mut xs), Some(i)) => {
+                let k = bound(i, xs.len())?;
+                Rc::make_mut(&mut xs)[k] = v;
+                Ok(Val::mk_array(Rc::unwrap_or_clone(xs)))
+            }
+            _ => Ok(Val::mk_undefined()),
+        }
+    }
+    fn length(&self) -> Val {
+        match (&self.val_a, &self.val_s) {
+            (Some(xs), _) => Val::mk_int(&(xs.len() as i32)),
+            (_, Some(x)) => Val::mk_int(&(x.chars().count() as i32)),
+            _ => Val::mk_undefined(),
+        }
+    }
+    fn field(&self, field: &str) -> Val {
+        match self.val_r.as_ref().and_then(|r| r.get(field)) {
+            Some(v) => v.clone(),
+            None => Val::mk_undefined(),
+        }
+    }
+    // A copy of the record with v in field
+    fn with(self, field: &str, v: Val) -> Val {
+        match self.val_r {
+            Some(mut r) if r.get(field).is_some() => {
+                *Rc::make_mut(&mut r).get_mut(field).unwrap() = v;
+                Val::mk_record(Rc::unwrap_or_clone(r))
+            }
+            _ => Val::mk_undefined(),
+        }
+    }
+    // The line Print writes for the value
+    fn output(&self) -> String {
+        match self.flag {
+            Kind::ValueBool => format!("Output {}", self.val_b.unwrap()),
+            Kind::ValueInt => format!("Ouput {}", self.val_i.unwrap()),
+            Kind::ValueStr => format!("Output {}", self.val_s.as_ref().unwrap()),
+            Kind::Closure | Kind::Array | Kind::Record => format!("Output {}", self),
+            _ => "Output Undefined".to_string(),
+        }
+    }
+}
+
+// The operators of Exp with two operands
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum BinOp {
+    Plus,
+    Mult,
+    And,
+    Or,
+    Equ,
+    Less,
+}
+
+impl BinOp {
+    pub fn symbol(self) -> &'static str {
+        match self {
+            BinOp::Plus => "+",
+            BinOp::Mult => "*",
+            BinOp::And => "&&",
+            BinOp::Or => "||",
+            BinOp::Equ => "==",
+            BinOp::Less => "<",
+        }
+    }
+    // The result of + and * on ints, None if it is no i32
+    pub fn checked(self, left: i32, right: i32) -> Option<i32> {
+        match self {
+            BinOp::Plus => left.checked_add(right),
+            BinOp::Mult => left.checked_mul(right),
+            _ => None,
+        }
+    }
+    // Both operands are evaluated, && and || do not short-circuit
+    fn apply(self, v1: &Val, v2: &Val) -> Result<Val, RuntimeError> {
+        let v = match (self, &v1.flag, &v2.flag) {
+            (BinOp::Mult | BinOp::Plus, Kind::ValueInt, Kind::ValueInt) => {
+                let (left, right) = (v1.val_i.unwrap(), v2.val_i.unwrap());
+                match self.checked(left, right) {
+                    Some(x) => Val::mk_int(&x),
+                    None => {
+                        return Err(RuntimeError::Overflow {
+                            left,
+                            op: self,
+                            right,
+                        })
+                    }
+                }
+            }
+            (BinOp::Plus, Kind::ValueStr, Kind::ValueStr) => {
+                Val::mk_str(&(v1.val_s.clone().unwrap() + v2.val_s.as_ref().unwrap()))
+            }
+            (BinOp::And, Kind::ValueBool, Kind::ValueBool) => {
+                Val::mk_bool(&(v1.val_b.unwrap() && v2.val_b.unwrap()))
+            }
+            (BinOp::Or, Kind::ValueBool, Kind::ValueBool) => {
+                Val::mk_bool(&(v1.val_b.unwrap() || v2.val_b.unwrap()))
+            }
+            (BinOp::Equ, Kind::ValueInt, Kind::ValueInt) => {
+                Val::mk_bool(&(v1.val_i.unwrap() == v2.val_i.unwrap()))
+            }
+            (BinOp::Equ, Kind::ValueBool, Kind::ValueBool) => {
+                Val::mk_bool(&(v1.val_b.unwrap() == v2.val_b.unwrap()))
+            }
+            (BinOp::Equ, Kind::ValueStr, Kind::ValueStr) => Val::mk_bool(&(v1.val_s == v2.val_s)),
+            (BinOp::Less, Kind::ValueInt, Kind::ValueInt) => {
+                Val::mk_bool(&(v1.val_i.unwrap() < v2.val_i.unwrap()))
+            }
+            // lexicographic order
+            (BinOp::Less, Kind::ValueStr, Kind::ValueStr) => Val::mk_bool(&(v1.val_s < v2.val_s)),
+            _ => Val::mk_undefined(),
+        };
+        Ok(v)
+    }
+}
+
+fn show_val(v: Val) -> String {
+    match v.flag {
+        Kind::ValueInt => {
+            let x = Exp::Num {
+                val: v.val_i.unwrap(),
+            };
+            return x.pretty();
+        }
+        Kind::ValueBool => {
+            let x = Exp::Bool {
+                val: v.val_b.unwrap(),
+            };
+            return x.pretty();
+        }
+        Kind::ValueStr | Kind::Closure | Kind::Array | Kind::Record => return v.to_string(),
+        Kind::Undefined => return "undefined".to_string(),
+    }
+}
+impl Display for Val {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.flag {
+            Kind::ValueInt => write!(f, "{}", self.val_i.unwrap()),
+            Kind::ValueBool => write!(f, "{}", self.val_b.unwrap()),
+            Kind::ValueStr => write!(f, "{}", quote(self.val_s.as_ref().unwrap())),
+            Kind::Closure => {
+                let c = self.val_c.as_ref().unwrap();
+                match c.ty {
+                    Type::TyVar(_) => write!(f, "<closure fn({})>", c.param),
+                    _ => write!(f, "<closure fn({}: {})>", c.param, c.ty),
+                }
+            }
+            Kind::Array => {
+                let xs: Vec<String> = self
+                    .val_a
+                    .as_ref()
+                    .unwrap()
+                    .iter()
+                    .map(|x| x.to_string())
+                    .collect();
+                write!(f, "[{}]", xs.join(", "))
+            }
+            Kind::Record => {
+                let r = self.val_r.as_ref().unwrap();
+                let xs: Vec<String> = r
+                    .fields
+                    .iter()
+                    .map(|(x, v)| format!("{}: {}", x, v))
+                    .collect();
+                write!(f, "{} {{ {} }}", r.name, xs.join(", "))
+            }
+            Kind::Undefined => write!(f, "{}", "undefined"),
+        }
+    }
+}
+#[derive(Clone, PartialEq, Debug)]
+pub enum Type {
+    TyIllTyped,
+    TyInt,
+    TyBool,
+    TyStr,
+    // parameter and result type of a closure
+    TyFun(Box<Type>, Box<Type>),
+    // element type
+    TyArray(Box<Type>),
+    // a record type is identified by its name, not by its fields
+    TyRecord(String),
+    // an unknown of the inference in hm; the parameter of a lambda
+    // without annotation has the type TyVar(0)
+    TyVar(usize),
+}
+
+fn show_type(t: Type) -> String {
+    match t {
+        Type::TyInt => return "int".to_string(),
+        Type::TyBool => return "bool".to_string(),
+        Type::TyStr | Type::TyFun(..) | Type::TyArray(_) | Type::TyRecord(_) | Type::TyVar(_) => {
+            return t.to_string()
+        }
+        Type::TyIllTyped => return "Illtyped".to_string(),
+    }
+}
+
+impl Display for Type {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Type::TyInt => write!(f, "{}", "int"),
+            Type::TyBool => write!(f, "{}", "bool"),
+            Type::TyStr => write!(f, "string"),
+            // -> associates to the right
+            Type::TyFun(a, b) if matches!(**a, Type::TyFun(..)) => write!(f, "({}) -> {}", a, b),
+            Type::TyFun(a, b) => write!(f, "{} -> {}", a, b),
+            Type::TyArray(ty) => write!(f, "array<{}>", ty),
+            Type::TyRecord(name) => write!(f, "{}", name),
+            // 'a, 'b, .., 'z, 'a1, ..
+            Type::TyVar(n) => {
+                write!(f, "'{}", (b'a' + (n % 26) as u8) as char)?;
+                if *n >= 26 {
+                    write!(f, "{}", n / 26)?;
+                }
+                Ok(())
+            }
+            Type::TyIllTyped => write!(f, "{}", "illtyped"),
+        }
+    }
+}
+// The variables of one procedure call
+type Frame = HashMap<String, Val>;
+
+// Calls nested deeper than this fail with a stack overflow
+const MAX_CALL_DEPTH: usize = 1000;
+
+// The runtime state: the procedures of the program and a stack of call
+// frames. Statements only see the variables of the innermost frame, which
+// the state dereferences to.
+#[derive(Clone)]
+pub struct ValState {
+    procs: Rc<Vec<Proc>>,
+    frames: Vec<Frame>,
+    // where read statements take their values from, shared by all copies
+    input: Rc<RefCell<dyn input::Input>>,
+}
+
+impl ValState {
+    pub fn new() -> ValState {
+        ValState::with_procs(Vec::new())
+    }
+    pub fn with_procs(procs: Vec<Proc>) -> ValState {
+        ValState {
+            procs: Rc::new(procs),
+            frames: vec![Frame::new()],
+            input: Rc::new(RefCell::new(input::Queue::new(Vec::new()))),
+        }
+    }
+    pub fn with_input(mut self, input: impl input::Input + 'static) -> ValState {
+        self.input = Rc::new(RefCell::new(input));
+        self
+    }
+}
+
+impl Default for ValState {
+    fn default() -> Self {
+        ValState::new()
+    }
+}
+
+impl Deref for ValState {
+    type Target = Frame;
+    fn deref(&self) -> &Frame {
+        self.frames.last().unwrap()
+    }
+}
+
+impl DerefMut for ValState {
+    fn deref_mut(&mut self) -> &mut Frame {
+        self.frames.last_mut().unwrap()
+    }
+}
+
+// Parameter types and return type of a procedure
+pub type Signature = (Vec<Type>, Type);
+
+// The types of the variables in scope, which the state dereferences to,
+// the signatures of the procedures and the return type of the procedure
+// being checked, None in the main program. records has the fields of the
+// declared record types.
+#[derive(Clone, Default)]
+pub struct TyState {
+    vars: HashMap<String, Type>,
+    procs: Rc<HashMap<String, Signature>>,
+    records: Rc<HashMap<String, Vec<(String, Type)>>>,
+    ret: Option<Type>,
+    // whether break and continue are allowed
+    in_loop: bool,
+}
+
+impl TyState {
+    pub fn new() -> TyState {
+        TyState::default()
+    }
+    pub fn with_procs(procs: &[Proc]) -> TyState {
+        let sigs = procs
+            .iter()
+            .map(|p| {
+                let params = p.params.iter().map(|(_, ty)| ty.clone()).collect();
+                (p.name.clone(), (params, p.ret.clone()))
+            })
+            .collect();
+        TyState {
+            procs: Rc::new(sigs),
+            ..TyState::default()
+        }
+    }
+    pub fn with_records(self, records: &[Record]) -> TyState {
+        let defs = records
+            .iter()
+            .map(|r| (r.name.clone(), r.fields.clone()))
+            .collect();
+        TyState {
+            records: Rc::new(defs),
+            ..self
+        }
+    }
+    // The state for checking the body of p
+    fn enter(&self, p: &Proc) -> TyState {
+        TyState {
+            vars: p.params.iter().cloned().collect(),
+            procs: self.procs.clone(),
+            records: self.records.clone(),
+            ret: Some(p.ret.clone()),
+            in_loop: false,
+        }
+    }
+    pub fn signature(&self, name: &str) -> Option<&Signature> {
+        self.procs.get(name)
+    }
+    pub fn fields(&self, name: &str) -> Option<&Vec<(String, Type)>> {
+        self.records.get(name)
+    }
+    // Type of field in values of type ty
+    pub fn field(&self, ty: &Type, field: &str) -> Option<Type> {
+        match ty {
+            Type::TyRecord(name) => self
+                .fields(name)?
+                .iter()
+                .find(|(x, _)| x == field)
+                .map(|(_, ty)| ty.clone()),
+            _ => None,
+        }
+    }
+    // The record type with field, the first by name if there are several
+    pub fn owner(&self, field: &str) -> Option<Type> {
+        let mut names: Vec<&String> = self.records.keys().collect();
+        names.sort();
+        names
+            .into_iter()
+            .find(|r| self.records[*r].iter().any(|(x, _)| x == field))
+            .map(|r| Type::TyRecord(r.clone()))
+    }
+    // True if every record type in ty is declared
+    fn declared(&self, ty: &Type) -> bool {
+        match ty {
+            Type::TyRecord(name) => self.records.contains_key(name),
+            Type::TyFun(a, b) => self.declared(a) && self.declared(b),
+            Type::TyArray(ty) => self.declared(ty),
+            _ => true,
+        }
+    }
+}
+
+impl Deref for TyState {
+    type Target = HashMap<String, Type>;
+    fn deref(&self) -> &HashMap<String, Type> {
+        &self.vars
+    }
+}
+
+impl DerefMut for TyState {
+    fn deref_mut(&mut self) -> &mut HashMap<String, Type> {
+        &mut self.vars
+    }
+}
+
+#[derive(Debug)]
+pub enum RuntimeError {
+    // the pretty printed condition and the values of its variables
+    AssertionFailed {
+        cond: String,
+        bindings: Vec<(String, String)>,
+    },
+    // a Hook ended the execution
+    Stopped,
+    // the step limit of Fuel was reached
+    OutOfFuel {
+        steps: usize,
+    },
+    // more than MAX_CALL_DEPTH nested calls
+    StackOverflow {
+        depth: usize,
+    },
+    // the procedure ended without return
+    NoReturn {
+        name: String,
+    },
+    IndexOutOfBounds {
+        index: i32,
+        len: usize,
+    },
+    // the result of + or * is no i32
+    Overflow {
+        left: i32,
+        op: BinOp,
+        right: i32,
+    },
+    // a variable which was never declared
+    Unbound {
+        name: String,
+    },
+    // a read statement found its input exhausted
+    NoInput,
+    // the input text is no value of the type read
+    BadInput {
+        text: String,
+        ty: Type,
+    },
+}
+impl Display for RuntimeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RuntimeError::AssertionFailed { cond, bindings } => {
+                write!(f, "assertion failed: {}", cond)?;
+                let xs: Vec<String> = bindings
+                    .iter()
+                    .map(|(x, v)| format!("{} = {}", x, v))
+                    .collect();
+                if !xs.is_empty() {
+                    write!(f, " with {}", xs.join(", "))?;
+                }
+                Ok(())
+            }
+            RuntimeError::Stopped => write!(f, "execution stopped"),
+            RuntimeError::OutOfFuel { steps } => write!(f, "out of fuel after {} steps", steps),
+            RuntimeError::StackOverflow { depth } => {
+                write!(f, "stack overflow after {} nested calls", depth)
+            }
+            RuntimeError::NoReturn { name } => write!(f, "procedure {} ended without return", name),
+            RuntimeError::IndexOutOfBounds { index, len } => {
+                write!(
+                    f,
+                    "index {} out of bounds for array of length {}",
+                    index, len
+                )
+            }
+            RuntimeError::Overflow { left, op, right } => {
+                write!(f, "integer overflow in {} {} {}", left, op.symbol(), right)
+            }
+            RuntimeError::Unbound { name } => write!(f, "variable {} is not declared", name),
+            RuntimeError::NoInput => write!(f, "no more input to read"),
+            RuntimeError::BadInput { text, ty } => {
+                write!(f, "cannot read {} as {}", quote(text), ty)
+            }
+        }
+    }
+}
+// Called by the interpreter before each statement other than Seq is
+// executed, and before every further evaluation of a While condition
+pub trait Hook {
+    fn before(
+        &mut self,
+        idx: usize,
+        depth: usize,
+        stmt: &Stmt,
+        s: &ValState,
+    ) -> Result<(), RuntimeError>;
+}
+
+struct NoHook;
+
+// Limits the number of steps of an execution. A step is the execution of a
+// statement other than Seq or a further evaluation of a While condition,
+// that is every call of the Hook.
+pub struct Fuel {
+    pub steps: usize,
+    pub max: Option<usize>,
+}
+impl Fuel {
+    pub fn unlimited() -> Fuel {
+        Fuel {
+            steps: 0,
+            max: None,
+        }
+    }
+    pub fn limit(max: usize) -> Fuel {
+        Fuel {
+            steps: 0,
+            max: Some(max),
+        }
+    }
+}
+impl Hook for Fuel {
+    fn before(&mut self, _: usize, _: usize, _: &Stmt, _: &ValState) -> Result<(), RuntimeError> {
+        if self.max.is_some_and(|max| self.steps >= max) {
+            return Err(RuntimeError::OutOfFuel { steps: self.steps });
+        }
+        self.steps += 1;
+        Ok(())
+    }
+}
+
+impl Hook for NoHook {
+    fn before(&mut self, _: usize, _: usize, _: &Stmt, _: &ValState) -> Result<(), RuntimeError> {
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum Exp {
+    Var {
+        name: String,
+    },
+    Num {
+        val: i32,
+    },
+    Bool {
+        val: bool,
+    },
+    Str {
+        val: String,
+    },
+    // addition of ints or concatenation of strings
+    Plus {
+        left: Box<Exp>,
+        right: Box<Exp>,
+    },
+    Mult {
+        left: Box<Exp>,
+        right: Box<Exp>,
+    },
+    And {
+        left: Box<Exp>,
+        right: Box<Exp>,
+    },
+    Or {
+        left: Box<Exp>,
+        right: Box<Exp>,
+    },
+    Neg {
+        exp: Box<Exp>,
+    },
+    Equ {
+        left: Box<Exp>,
+        right: Box<Exp>,
+    },
+    Less {
+        left: Box<Exp>,
+        right: Box<Exp>,
+    },
+    Grp {
+        exp: Box<Exp>,
+    },
+    // a call of a procedure or of a closure stored in the variable name
+    Call {
+        name: String,
+        args: Vec<Exp>,
+    },
+    Lambda {
+        param: String,
+        ty: Type,
+        body: Box<Exp>,
+    },
+    Array {
+        elems: Vec<Exp>,
+    },
+    Index {
+        array: Box<Exp>,
+        index: Box<Exp>,
+    },
+    // a copy of array with value at index
+    Update {
+        array: Box<Exp>,
+        index: Box<Exp>,
+        value: Box<Exp>,
+    },
+    Len {
+        exp: Box<Exp>,
+    },
+    // a literal of the record type name
+    Record {
+        name: String,
+        fields: Vec<(String, Exp)>,
+    },
+    Field {
+        exp: Box<Exp>,
+        field: String,
+    },
+    // a copy of the record exp with value in field
+    With {
+        exp: Box<Exp>,
+        field: String,
+        value: Box<Exp>,
+    },
+}
+
+#[derive(Clone)]
+pub enum Stmt {
+    Skip,
+    Seq {
+        first: Box<Stmt>,
+        second: Box<Stmt>,
+    },
+    // ty is the annotation in x: ty := e
+    Decl {
+        lhs: String,
+        ty: Option<Type>,
+        rhs: Box<Exp>,
+    },
+    While {
+        cond: Box<Exp>,
+        inv: Option<Box<Exp>>,
+        stmt: Box<Stmt>,
+    },
+    // The body of a for loop: step runs after body, also when body is left
+    // by continue, but not after break
+    Step {
+        body: Box<Stmt>,
+        step: Box<Stmt>,
+    },
+    IfThenElse {
+        cond: Box<Exp>,
+        then_stmt: Box<Stmt>,
+        else_stmt: Box<Stmt>,
+    },
+    Assign {
+        lhs: String,
+        rhs: Box<Exp>,
+    },
+    Print {
+        print_exp: Box<Exp>,
+    },
+    Assert {
+        cond: Box<Exp>,
+    },
+    Assume {
+        cond: Box<Exp>,
+    },
+    Return {
+        exp: Box<Exp>,
+    },
+    ArrayAssign {
+        lhs: String,
+        index: Box<Exp>,
+        rhs: Box<Exp>,
+    },
+    FieldAssign {
+        lhs: String,
+        field: String,
+        rhs: Box<Exp>,
+    },
+    Break,
+    Continue,
+    // declares lhs with a value of type ty from the input
+    Read {
+        lhs: String,
+        ty: Type,
+    },
+}
+
+// A procedure definition. The statements of the body are numbered after
+// those of the main program, start is the index of the first one.
+#[derive(Clone)]
+pub struct Proc {
+    pub name: String,
+    pub params: Vec<(String, Type)>,
+    pub ret: Type,
+    pub body: Box<Stmt>,
+    pub start: usize,
+}
+
+// A record type declaration. Record types are nominal: records with the
+// same fields but different names have different types, and a literal
+// has the type it names.
+#[derive(Clone)]
+pub struct Record {
+    pub name: String,
+    pub fields: Vec<(String, Type)>,
+}
+
+// The initial value of a variable declared as var x: ty, None for
+// arrays, functions and records which contain themselves
+pub fn default_value(ty: &Type, records: &[Record]) -> Option<Box<Exp>> {
+    default_in(ty, records, &mut Vec::new())
+}
+
+fn default_in(ty: &Type, records: &[Record], open: &mut Vec<String>) -> Option<Box<Exp>> {
+    match ty {
+        Type::TyInt => Some(number(0)),
+        Type::TyBool => Some(boolean(false)),
+        Type::TyStr => Some(string(String::new())),
+        Type::TyRecord(name) if !open.contains(name) => {
+            let r = records.iter().find(|r| r.name == *name)?;
+            open.push(name.clone());
+            let mut fields = Vec::new();
+            for (x, ty) in &r.fields {
+                fields.push((x.clone(), *default_in(ty, records, open)?));
+            }
+            open.pop();
+            Some(record(name.clone(), fields))
+        }
+        _ => None,
+    }
+}
+
+// How the execution of a statement ended
+pub enum Flow {
+    Normal,
+    Return(Val),
+    Break,
+    Continue,
+}
+impl Exp {
+    // The operator and operands of a binary expression
+    pub fn binary(&self) -> Option<(BinOp, &Exp, &Exp)> {
+        let (op, left, right) = match self {
+            Exp::Plus { left, right } => (BinOp::Plus, left, right),
+            Exp::Mult { left, right } => (BinOp::Mult, left, right),
+            Exp::And { left, right } => (BinOp::And, left, right),
+            Exp::Or { left, right } => (BinOp::Or, left, right),
+            Exp::Equ { left, right } => (BinOp::Equ, left, right),
+            Exp::Less { left, right } => (BinOp::Less, left, right),
+            _ => return None,
+        };
+        Some((op, left, right))
+    }
+    fn eval(&self, s: &mut ValState) -> Val {
+        self.eval_in(s, 0, &mut NoHook)
+            .unwrap_or_else(|_| Val::mk_undefined())
+    }
+    // The value of a closed expression, None if the evaluation fails, e.g.
+    // when + or * overflows
+    fn fold(&self) -> Option<Val> {
+        self.eval_in(&mut ValState::new(), 0, &mut NoHook).ok()
+    }
+    // eval which runs the bodies of called procedures with exec, depth is
+    // that of the enclosing statement
+    fn eval_in(
+        &self,
+        s: &mut ValState,
+        depth: usize,
+        hook: &mut dyn Hook,
+    ) -> Result<Val, RuntimeError> {
+        let mut vals = Vec::new();
+        for e in self.operands() {
+            vals.push(e.eval_in(s, depth, hook)?);
+        }
+        self.apply_in(vals, s, depth, hook)
+    }
+    // The direct subexpressions in the order of their evaluation, the body
+    // of a lambda is only evaluated when it is called
+    fn operands(&self) -> Vec<&Exp> {
+        match self {
+            Exp::Var { .. }
+            | Exp::Num { .. }
+            | Exp::Bool { .. }
+            | Exp::Str { .. }
+            | Exp::Lambda { .. } => vec![],
+            Exp::Mult { left, right }
+            | Exp::Plus { left, right }
+            | Exp::And { left, right }
+            | Exp::Or { left, right }
+            | Exp::Equ { left, right }
+            | Exp::Less { left, right } => vec![left, right],
+            Exp::Neg { exp } | Exp::Grp { exp } | Exp::Len { exp } | Exp::Field { exp, .. } => {
+                vec![exp]
+            }
+            Exp::Call { args, .. } => args.iter().collect(),
+            Exp::Array { elems } => elems.iter().collect(),
+            Exp::Index { array, index } => vec![array, index],
+            Exp::Update {
+                array,
+                index,
+                value,
+            } => vec![array, index, value],
+            Exp::Record { fields, .. } => fields.iter().map(|(_, e)| e).collect(),
+            Exp::With { exp, value, .. } => vec![exp, value],
+        }
+    }
+    // The value of self from the values of its operands, a call runs the
+    // body of the procedure or closure
+    fn apply_in(
+        &self,
+        vals: Vec<Val>,
+        s: &mut ValState,
+        depth: usize,
+        hook: &mut dyn Hook,
+    ) -> Result<Val, RuntimeError> {
+        let mut vals = vals.into_iter();
+        let mut next = || vals.next().unwrap();
+        let v = match self {
+            Exp::Var { name } => {
+                let x = s
+                    .get(name)
+                    .ok_or_else(|| RuntimeError::Unbound { name: name.clone() })?;
+                match x.flag {
+                    Kind::ValueInt => Val::mk_int(&x.val_i.unwrap()),
+                    Kind::ValueBool => Val::mk_bool(&x.val_b.unwrap()),
+                    Kind::ValueStr | Kind::Closure | Kind::Array | Kind::Record => x.clone(),
+                    Kind::Undefined => Val::mk_undefined(),
+                }
+            }
+            Exp::Num { val } => Val::mk_int(val),
+            Exp::Bool { val } => Val::mk_bool(val),
+            Exp::Str { val } => Val::mk_str(val),
+            Exp::Mult { .. }
+            | Exp::Plus { .. }
+            | Exp::And { .. }
+            | Exp::Or { .. }
+            | Exp::Equ { .. }
+            | Exp::Less { .. } => {
+                let (v1, v2) = (next(), next());
+                self.binary().unwrap().0.apply(&v1, &v2)?
+            }
+            Exp::Neg { .. } => next().not(),
+            Exp::Grp { .. } => {
+                let v = next();
+                match v.flag {
+                    Kind::ValueBool => Val::mk_bool(&(v.val_b.unwrap())),
+                    Kind::ValueInt => Val::mk_int(&(v.val_i.unwrap())),
+                    Kind::ValueStr | Kind::Closure | Kind::Array | Kind::Record => v,
+                    _ => Val::mk_undefined(),
+                }
+            }
+            Exp::Call { name, args } if s.contains_key(name) => {
+                let f = s[name.as_str()].clone();
+                let c = match (&f.val_c, args.len()) {
+                    (Some(c), 1) => c,
+                    _ => return Ok(Val::mk_undefined()),
+                };
+                if s.frames.len() > MAX_CALL_DEPTH {
+                    return Err(RuntimeError::StackOverflow {
+                        depth: MAX_CALL_DEPTH,
+                    });
+                }
+                let mut frame = c.env.clone();
+                frame.insert(c.param.clone(), next());
+                s.frames.push(frame);
+                let r = c.body.eval_in(s, depth + 1, hook);
+                s.frames.pop();
+                r?
+            }
+            Exp::Call { name, args } => {
+                let procs = s.procs.clone();
+                let p = match procs.iter().find(|p| p.name == *name) {
+                    Some(p) if p.params.len() == args.len() => p,
+                    _ => return Ok(Val::mk_undefined()),
+                };
+                if s.frames.len() > MAX_CALL_DEPTH {
+                    return Err(RuntimeError::StackOverflow {
+                        depth: MAX_CALL_DEPTH,
+                    });
+                }
+                let frame = p.params.iter().map(|(x, _)| x.clone()).zip(vals).collect();
+                s.frames.push(frame);
+                let r = p.body.exec(p.start, depth + 1, s, hook);
+                s.frames.pop();
+                match r? {
+                    Flow::Return(v) => v,
+                    _ => return Err(RuntimeError::NoReturn { name: name.clone() }),
+                }
+            }
+            Exp::Lambda { param, ty, body } => Val::mk_closure(Closure {
+                param: param.clone(),
+                ty: ty.clone(),
+                body: (**body).clone(),
+                env: (**s).clone(),
+            }),
+            Exp::Array { .. } => Val::mk_array(vals.collect()),
+            Exp::Index { .. } => {
+                let (a, i) = (next(), next());
+                a.at(&i)?
+            }
+            Exp::Update { .. } => {
+                let (a, i, v) = (next(), next(), next());
+                a.set(&i, v)?
+            }
+            Exp::Len { .. } => next().length(),
+            Exp::Record { name, fields } => Val::mk_record(RecordVal {
+                name: name.clone(),
+                fields: fields.iter().map(|(x, _)| x.clone()).zip(vals).collect(),
+            }),
+            Exp::Field { field, .. } => next().field(field),
+            Exp::With { field, .. } => {
+                let (r, v) = (next(), next());
+                r.with(field, v)
+            }
+        };
+        Ok(v)
+    }
+    fn pretty(&self) -> String {
+        match self {
+            Exp::Var { name } => name.to_string(),
+            Exp::Num { val } => val.to_string(),
+            Exp::Bool { val } => val.to_string(),
+            Exp::Str { val } => quote(val),
+            Exp::Mult { left, right } => {
+                let mut x = String::new();
+                x.push_str("(");
+                x.push_str(&left.pretty());
+                x.push_str("*");
+                x.push_str(&right.pretty());
+                x.push_str(")");
+                x
+            }
+
+            Exp::Plus { left, right } => {
+                let mut x = String::new();
+                x.push_str("(");
+                x.push_str(&left.pretty());
+                x.push_str("+");
+                x.push_str(&right.pretty());
+                x.push_str(")");
+                x
+            }
+
+            Exp::And { left, right } => {
+                let mut x = String::new();
+                x.push_str("(");
+                x.push_str(&left.pretty());
+                x.push_str("&&");
+                x.push_str(&right.pretty());
+                x.push_str(")");
+                x
+            }
+
+            Exp::Or { left, right } => {
+                let mut x = String::new();
+                x.push_str("(");
+                x.push_str(&left.pretty());
+                x.push_str("||");
+                x.push_str(&right.pretty());
+                x.push_str(")");
+                x
+            }
+            Exp::Equ { left, right } => {
+                let mut x = String::new();
+                x.push_str("(");
+                x.push_str(&left.pretty());
+                x.push_str("==");
+                x.push_str(&right.pretty());
+                x.push_str(")");
+                x
+            }
+
+            Exp::Less { left, right } => {
+                let mut x = String::new();
+                x.push_str("(");
+                x.push_str(&left.pretty());
+                x.push_str("<");
+                x.push_str(&right.pretty());
+                x.push_str(")");
+                x
+            }
+            Exp::Neg { exp } => {
+                let mut x = String::new();
+                x.push_str("(");
+                x.push_str("!");
+                x.push_str(&exp.pretty());
+                x.push_str(")");
+                x
+            }
+            Exp::Grp { exp } => {
+                let mut x = String::new();
+                x.push_str("(");
+                x.push_str(&exp.pretty());
+                x.push_str(")");
+                x
+            }
+            Exp::Call { name, args } => {
+                let args: Vec<String> = args.iter().map(|a| a.pretty()).collect();
+                format!("{}({})", name, args.join(", "))
+            }
+            Exp::Lambda {
+                param,
+                ty: Type::TyVar(_),
+                body,
+            } => format!("(fn({}) => {})", param, body.pretty()),
+            Exp::Lambda { param, ty, body } => {
+                format!("(fn({}: {}) => {})", param, ty, body.pretty())
+            }
+            Exp::Array { elems } => {
+                let elems: Vec<String> = elems.iter().map(|e| e.pretty()).collect();
+                format!("[{}]", elems.join(", "))
+            }
+            Exp::Index { array, index } => format!("{}[{}]", array.pretty(), index.pretty()),
+            Exp::Update {
+                array,
+                index,
+                value,
+            } => format!(
+                "{}[{} := {}]",
+                array.pretty(),
+                index.pretty(),
+                value.pretty()
+            ),
+            Exp::Len { exp } => format!("len({})", exp.pretty()),
+            Exp::Record { name, fields } => {
+                let fields: Vec<String> = fields
+                    .iter()
+                    .map(|(x, e)| format!("{}: {}", x, e.pretty()))
+                    .collect();
+                format!("{} {{ {} }}", name, fields.join(", "))
+            }
+            Exp::Field { exp, field } => format!("{}.{}", exp.pretty(), field),
+            Exp::With { exp, field, value } => {
+                format!("{}.{{{} := {}}}", exp.pretty(), field, value.pretty())
+            }
+        }
+    }
+    // Names of the variables read by the expression, in order of occurrence.
+    // The name of a call counts as read, it may be a closure.
+    fn vars(&self) -> Vec<String> {
+        let mut xs = Vec::new();
+        self.collect_vars(&mut xs);
+        xs
+    }
+    fn collect_vars(&self, xs: &mut Vec<String>) {
+        match self {
+            Exp::Var { name } => {
+                if !xs.contains(name) {
+                    xs.push(name.clone());
+                }
+            }
+            Exp::Num { .. } | Exp::Bool { .. } | Exp::Str { .. } => {}
+            Exp::Plus { left, right }
+            | Exp::Mult { left, right }
+            | Exp::And { left, right }
+            | Exp::Or { left, right }
+            | Exp::Equ { left, right }
+            | Exp::Less { left, right } => {
+                left.collect_vars(xs);
+                right.collect_vars(xs);
+            }
+            Exp::Neg { exp } | Exp::Grp { exp } => exp.collect_vars(xs),
+            Exp::Call { name, args } => {
+                if !xs.contains(name) {
+                    xs.push(name.clone());
+                }
+                for a in args {
+                    a.collect_vars(xs);
+                }
+            }
+            Exp::Lambda { param, body, .. } => {
+                for x in body.vars() {
+                    if x != *param && !xs.contains(&x) {
+                        xs.push(x);
+                    }
+                }
+            }
+            Exp::Array { elems } => {
+                for e in elems {
+                    e.collect_vars(xs);
+                }
+            }
+            Exp::Index { array, index } => {
+                array.collect_vars(xs);
+                index.collect_vars(xs);
+            }
+            Exp::Update {
+                array,
+                index,
+                value,
+            } => {
+                array.collect_vars(xs);
+                index.collect_vars(xs);
+                value.collect_vars(xs);
+            }
+            Exp::Len { exp } | Exp::Field { exp, .. } => exp.collect_vars(xs),
+            Exp::Record { fields, .. } => {
+                for (_, e) in fields {
+                    e.collect_vars(xs);
+                }
+            }
+            Exp::With { exp, value, .. } => {
+                exp.collect_vars(xs);
+                value.collect_vars(xs);
+            }
+        }
+    }
+    fn infer(&self, t: &mut TyState) -> Type {
+        match self {
+            Exp::Var { name } => {
+                if let Some(x) = t.get(name) {
+                    return x.clone();
+                } else {
+                    return Type::TyIllTyped;
+                }
+            }
+            Exp::Bool { .. } => Type::TyBool,
+            Exp::Num { .. } => Type::TyInt,
+            Exp::Str { .. } => Type::TyStr,
+            Exp::Mult { left, right } => {
+                let t1 = left.infer(t);
+                let t2 = right.infer(t);
+                if let (Type::TyInt, Type::TyInt) = (t1, t2) {
+                    return Type::TyInt;
+                }
+                Type::TyIllTyped
+            }
+            // + is resolved by the types of its operands
+            Exp::Plus { left, right } => {
+                let t1 = left.infer(t);
+                let t2 = right.infer(t);
+                match (t1, t2) {
+                    (Type::TyInt, Type::TyInt) => Type::TyInt,
+                    (Type::TyStr, Type::TyStr) => Type::TyStr,
+                    _ => Type::TyIllTyped,
+                }
+            }
+            Exp::And { left, right } => {
+                let t1 = left.infer(t);
+                let t2 = right.infer(t);
+                if let (Type::TyBool, Type::TyBool) = (t1, t2) {
+                    return Type::TyBool;
+                }
+                Type::TyIllTyped
+            }
+            Exp::Or { left, right } => {
+                let t1 = left.infer(t);
+                let t2 = right.infer(t);
+                if let (Type::TyBool, Type::TyBool) = (t1, t2) {
+                    return Type::TyBool;
+                }
+                Type::TyIllTyped
+            }
+            Exp::Equ { left, right } => {
+                let t1 = left.infer(t);
+                let t2 = right.infer(t);
+                match (t1, t2) {
+                    (Type::TyBool, Type::TyBool) => return Type::TyBool,
+                    (Type::TyInt, Type::TyInt) => return Type::TyBool,
+                    (Type::TyStr, Type::TyStr) => return Type::TyBool,
+                    _ => Type::TyIllTyped,
+                }
+            }
+            Exp::Less { left, right } => {
+                let t1 = left.infer(t);
+                let t2 = right.infer(t);
+                match (t1, t2) {
+                    (Type::TyInt, Type::TyInt) | (Type::TyStr, Type::TyStr) => Type::TyBool,
+                    _ => Type::TyIllTyped,
+                }
+            }
+            Exp::Neg { exp } => {
+                let t = exp.infer(t);
+                if let Type::TyBool = t {
+                    return Type::TyBool;
+                }
+                Type::TyIllTyped
+            }
+            Exp::Grp { exp } => {
+                let t = exp.infer(t);
+                match t {
+                    Type::TyBool => Type::TyBool,
+                    Type::TyInt => Type::TyInt,
+                    ty @ (Type::TyStr | Type::TyFun(..) | Type::TyArray(_) | Type::TyRecord(_)) => {
+                        ty
+                    }
+                    _ => Type::TyIllTyped,
+                }
+            }
+            Exp::Call { name, args } if t.contains_key(name) => match (&t[name], args.as_slice()) {
+                (Type::TyFun(param, ret), [a]) => {
+                    let (param, ret) = ((**param).clone(), (**ret).clone());
+                    if a.infer(t) == param {
+                        ret
+                    } else {
+                        Type::TyIllTyped
+                    }
+                }
+                _ => Type::TyIllTyped,
+            },
+            Exp::Call { name, args } => {
+                let (params, ret) = match t.signature(name) {
+                    Some(sig) => sig.clone(),
+                    None => return Type::TyIllTyped,
+                };
+                if params.len() != args.len() {
+                    return Type::TyIllTyped;
+                }
+                for (a, ty) in args.iter().zip(params) {
+                    if a.infer(t) != ty {
+                        return Type::TyIllTyped;
+                    }
+                }
+                ret
+            }
+            // the body sees the variables in scope and the parameter
+            Exp::Lambda { ty, .. } if !t.declared(ty) => Type::TyIllTyped,
+            Exp::Lambda { param, ty, body } => {
+                let mut inner = t.clone();
+                inner.insert(param.clone(), ty.clone());
+                match body.infer(&mut inner) {
+                    Type::TyIllTyped => Type::TyIllTyped,
+                    ret => Type::TyFun(Box::new(ty.clone()), Box::new(ret)),
+                }
+            }
+            // the elements of an array all have the type of the first one
+            Exp::Array { elems } => {
+                let tys: Vec<Type> = elems.iter().map(|e| e.infer(t)).collect();
+                match tys.first() {
+                    Some(ty) if *ty != Type::TyIllTyped && tys.iter().all(|x| x == ty) => {
+                        Type::TyArray(Box::new(ty.clone()))
+                    }
+                    _ => Type::TyIllTyped,
+                }
+            }
+            Exp::Index { array, index } => match (array.infer(t), index.infer(t)) {
+                (Type::TyArray(ty), Type::TyInt) => *ty,
+                _ => Type::TyIllTyped,
+            },
+            Exp::Update {
+                array,
+                index,
+                value,
+            } => match (array.infer(t), index.infer(t), value.infer(t)) {
+                (Type::TyArray(ty), Type::TyInt, v) if *ty == v => Type::TyArray(ty),
+                _ => Type::TyIllTyped,
+            },
+            Exp::Len { exp } => match exp.infer(t) {
+                Type::TyArray(_) | Type::TyStr => Type::TyInt,
+                _ => Type::TyIllTyped,
+            },
+            // every field of the declaration exactly once, in any order
+            Exp::Record { name, fields } => {
+                let decl = match t.fields(name) {
+                    Some(decl) if decl.len() == fields.len() => decl.clone(),
+                    _ => return Type::TyIllTyped,
+                };
+                for (x, ty) in decl {
+                    match fields.iter().find(|(y, _)| *y == x) {
+                        Some((_, e)) if e.infer(t) == ty => {}
+                        _ => return Type::TyIllTyped,
+                    }
+                }
+                Type::TyRecord(name.clone())
+            }
+            Exp::Field { exp, field } => {
+                let ty = exp.infer(t);
+                t.field(&ty, field).unwrap_or(Type::TyIllTyped)
+            }
+            Exp::With { exp, field, value } => {
+                let ty = exp.infer(t);
+                match t.field(&ty, field) {
+                    Some(f) if value.infer(t) == f => ty,
+                    _ => Type::TyIllTyped,
+                }
+            }
+        }
+    }
+}
+
+// A string literal with the escape sequences of the parser
+fn quote(x: &str) -> String {
+    let mut q = String::from("\"");
+    for c in x.chars() {
+        match c {
+            '"' => q.push_str("\\\""),
+            '\\' => q.push_str("\\\\"),
+            '\n' => q.push_str("\\n"),
+            '\t' => q.push_str("\\t"),
+            c => q.push(c),
+        }
+    }
+    q.push('"');
+    q
+}
+
+// Position of index in an array of length len
+fn bound(index: i32, len: usize) -> Result<usize, RuntimeError> {
+    if index < 0 || index as usize >= len {
+        return Err(RuntimeError::IndexOutOfBounds { index, len });
+    }
+    Ok(index as usize)
+}
+impl Stmt {
+    // Visits all statements except Seq in pre-order together with their index
+    fn visit<'a>(&'a self, idx: &mut usize, f: &mut impl FnMut(usize, &'a Stmt)) {
+        match self {
+            Stmt::Seq { first, second } => {
+                first.visit(idx, f);
+                second.visit(idx, f);
+            }
+            Stmt::IfThenElse {
+                then_stmt,
+                else_stmt,
+                ..
+            } => {
+                f(*idx, self);
+                *idx += 1;
+                then_stmt.visit(idx, f);
+                else_stmt.visit(idx, f);
+            }
+            Stmt::While { stmt, .. } => {
+                f(*idx, self);
+                *idx += 1;
+                stmt.visit(idx, f);
+            }
+            // like Seq no statement of its own
+            Stmt::Step { body, step } => {
+                body.visit(idx, f);
+                step.visit(idx, f);
+            }
+            _ => {
+                f(*idx, self);
+                *idx += 1;
+            }
+        }
+    }
+    // Number of statements visited by visit
+    fn size(&self) -> usize {
+        let mut n = 0;
+        self.visit(&mut n, &mut |_, _| {});
+        n
+    }
+    fn pretty(&self) -> String {
+        match self {
+            Stmt::Skip => "skip".to_string(),
+            Stmt::Seq { first, second } if second.is_for() => {
+                for_header(Some(first), second) + " " + &second.pretty_body()
+            }
+            Stmt::While { .. } if self.is_for() => {
+                for_header(None, self) + " " + &self.pretty_body()
+            }
+            Stmt::Seq {
+                first: body,
+                second: step,
+            }
+            | Stmt::Step { body, step } => {
+                let mut x = String::new();
+                x.push_str(&body.pretty());
+                x.push_str(";");
+                x.push_str(&step.pretty());
+                x
+            }
+            Stmt::Decl { lhs, ty, rhs } => {
+                let mut x = String::new();
+                x.push_str(&lhs);
+                if let Some(ty) = ty {
+                    x.push_str(&format!(": {} ", ty));
+                }
+                x.push_str(":= ");
+                x.push_str(&rhs.pretty());
+                x
+            }
+            Stmt::IfThenElse {
+                cond,
+                then_stmt,
+                else_stmt,
+            } => {
+                let mut x = String::new();
+                x.push_str("if ");
+                x.push_str(&cond.pretty());
+                x.push_str(" then ");
+                x.push_str(&mut then_stmt.pretty());
+                x.push_str(" else ");
+                x.push_str(&mut else_stmt.pretty());
+                x
+            }
+            Stmt::Assign { lhs, rhs } => {
+                let mut x = String::new();
+                x.push_str(&lhs);
+                x.push_str(" = ");
+                x.push_str(&rhs.pretty());
+                x
+            }
+            Stmt::While {
+                cond, inv, stmt, ..
+            } => {
+                let mut x = String::new();
+                x.push_str("while ");
+                x.push_str(&cond.pretty());
+                if let Some(inv) = inv {
+                    x.push_str(" invariant ");
+                    x.push_str(&inv.pretty());
+                }
+                x.push_str(" ");
+                x.push_str(&stmt.pretty());
+                x
+            }
+            Stmt::Print { print_exp } => {
+                let mut x = String::new();
+                x.push_str("print ");
+                x.push_str(&print_exp.pretty());
+                x
+            }
+            Stmt::Assert { cond } => {
+                let mut x = String::new();
+                x.push_str("assert ");
+                x.push_str(&cond.pretty());
+                x
+            }
+            Stmt::Assume { cond } => {
+                let mut x = String::new();
+                x.push_str("assume ");
+                x.push_str(&cond.pretty());
+                x
+            }
+            Stmt::Return { exp } => {
+                let mut x = String::new();
+                x.push_str("return ");
+                x.push_str(&exp.pretty());
+                x
+            }
+            Stmt::ArrayAssign { lhs, index, rhs } => {
+                format!("{}[{}] = {}", lhs, index.pretty(), rhs.pretty())
+            }
+            Stmt::FieldAssign { lhs, field, rhs } => {
+                format!("{}.{} = {}", lhs, field, rhs.pretty())
+            }
+            Stmt::Break => "break".to_string(),
+            Stmt::Continue => "continue".to_string(),
+            Stmt::Read { lhs, ty } => format!("read {}: {}", lhs, ty),
+        }
+    }
+    // The break and continue statements in self, with self at start, which
+    // belong to an enclosing loop and not to one within self
+    fn jumps(&self, start: usize) -> Vec<(usize, &Stmt)> {
+        let mut all = Vec::new();
+        self.visit(&mut start.clone(), &mut |i, s| all.push((i, s)));
+        // the statements before end are in a loop within self
+        let mut end = start;
+        all.into_iter()
+            .filter(|(i, s)| {
+                if *i < end {
+                    return false;
+                }
+                if let Stmt::While { .. } = s {
+                    end = i + s.size();
+                }
+                matches!(s, Stmt::Break | Stmt::Continue)
+            })
+            .collect()
+    }
+    // The body of a for loop without its step
+    fn pretty_body(&self) -> String {
+        match self {
+            Stmt::While { stmt, .. } => match &**stmt {
+                Stmt::Step { body, .. } => body.pretty(),
+                _ => unreachable!(),
+            },
+            _ => unreachable!(),
+        }
+    }
+    // A while loop whose body is a Step, which is what a for loop desugars
+    // into
+    fn is_for(&self) -> bool {
+        matches!(self, Stmt::While { stmt, .. } if matches!(**stmt, Stmt::Step { .. }))
+    }
+    fn eval(&self, s: &mut ValState) -> Result<(), RuntimeError> {
+        self.exec(0, 0, s, &mut NoHook)?;
+        Ok(())
+    }
+    // Runs a statement without substatements, the values of its expressions
+    // are given in the order of exps
+    fn perform(&self, vals: Vec<Val>, s: &mut ValState) -> Result<Flow, RuntimeError> {
+        let mut vals = vals.into_iter();
+        let mut next = || vals.next().unwrap();
+        match self {
+            Stmt::Decl { lhs, .. } => {
+                let x = next();
+                s.insert(lhs.clone(), x);
+            }
+            Stmt::Assign { lhs, .. } => {
+                //TODO there should be a cleaner solution
+                let v = Exp::Var {
+                    name: lhs.to_string(),
+                }
+                .eval(s);
+                let val = next();
+                if let Kind::Undefined = val.flag {
+                    println!("value is undefined");
+                    return Ok(Flow::Normal);
+                }
+                if let Kind::Undefined = v.flag {
+                    println!("var is undefined");
+                    return Ok(Flow::Normal);
+                }
+                if v.flag != val.flag {
+                    println!("var and value not the same Kind");
+                    return Ok(Flow::Normal);
+                }
+                *s.get_mut(lhs).unwrap() = val;
+            }
+            Stmt::ArrayAssign { lhs, .. } => {
+                let (i, val) = (next(), next());
+                let xs = match s.get_mut(lhs) {
+                    Some(Val {
+                        val_a: Some(xs), ..
+                    }) => xs,
+                    _ => {
+                        println!("var is no array");
+                        return Ok(Flow::Normal);
+                    }
+                };
+                let i = match i.val_i {
+                    Some(i) => bound(i, xs.len())?,
+                    None => {
+                        println!("index is no int");
+                        return Ok(Flow::Normal);
+                    }
+                };
+                if xs[i].flag != val.flag {
+                    println!("element and value not the same Kind");
+                    return Ok(Flow::Normal);
+                }
+                Rc::make_mut(xs)[i] = val;
+            }
+            Stmt::FieldAssign { lhs, field, .. } => {
+                let val = next();
+                let r = match s.get_mut(lhs) {
+                    Some(Val { val_r: Some(r), .. }) => r,
+                    _ => {
+                        println!("var is no record");
+                        return Ok(Flow::Normal);
+                    }
+                };
+                let v = match Rc::make_mut(r).get_mut(field) {
+                    Some(v) => v,
+                    None => {
+                        println!("record has no field {}", field);
+                        return Ok(Flow::Normal);
+                    }
+                };
+                if v.flag != val.flag {
+                    println!("field and value not the same Kind");
+                    return Ok(Flow::Normal);
+                }
+                *v = val;
+            }
+            Stmt::Print { .. } => {
+                println!("{}", next().output());
+            }
+            Stmt::Assert { cond } => {
+                if next().val_b != Some(true) {
+                    let bindings = cond
+                        .vars()
+                        .into_iter()
+                        .filter(|x| s.contains_key(x) || !s.procs.iter().any(|p| p.name == *x))
+                        .map(|x| {
+                            let v = s
+                                .get(&x)
+                                .map_or("undeclared".to_string(), |v| v.to_string());
+                            (x, v)
+                        })
+                        .collect();
+                    return Err(RuntimeError::AssertionFailed {
+                        cond: cond.pretty(),
+                        bindings,
+                    });
+                }
+            }
+            Stmt::Return { .. } => return Ok(Flow::Return(next())),
+            Stmt::Assume { .. } => {}
+            _ => unreachable!(),
+        }
+        Ok(Flow::Normal)
+    }
+    // The expressions of a statement without substatements, in the order of
+    // their evaluation
+    fn exps(&self) -> Vec<&Exp> {
+        match self {
+            Stmt::Decl { rhs, .. } | Stmt::Assign { rhs, .. } | Stmt::FieldAssign { rhs, .. } => {
+                vec![rhs]
+            }
+            Stmt::ArrayAssign { index, rhs, .. } => vec![index, rhs],
+            Stmt::Print { print_exp: e }
+            | Stmt::Assert { cond: e }
+            | Stmt::Assume { cond: e }
+            | Stmt::Return { exp: e } => vec![e],
+            _ => vec![],
+        }
+    }
+    // eval which calls hook before every statement; idx is the index of self
+    // and depth the number of enclosing if and while statements and calls
+    fn exec(
+        &self,
+        idx: usize,
+        depth: usize,
+        s: &mut ValState,
+        hook: &mut dyn Hook,
+    ) -> Result<Flow, RuntimeError> {
+        if let Stmt::Seq { first, second } = self {
+            match first.exec(idx, depth, s, hook)? {
+                Flow::Normal => {}
+                flow => return Ok(flow),
+            }
+            return second.exec(idx + first.size(), depth, s, hook);
+        }
+        if let Stmt::Step { body, step } = self {
+            match body.exec(idx, depth, s, hook)? {
+                Flow::Normal | Flow::Continue => {}
+                flow => return Ok(flow),
+            }
+            return step.exec(idx + body.size(), depth, s, hook);
+        }
+        hook.before(idx, depth, self, s)?;
+        match self {
+            Stmt::Skip | Stmt::Seq { .. } | Stmt::Step { .. } => {}
+            Stmt::Decl { .. }
+            | Stmt::Assign { .. }
+            | Stmt::ArrayAssign { .. }
+            | Stmt::FieldAssign { .. }
+            | Stmt::Print { .. }
+            | Stmt::Assert { .. }
+            | Stmt::Return { .. } => {
+                let mut vals = Vec::new();
+                for e in self.exps() {
+                    vals.push(e.eval_in(s, depth, hook)?);
+                }
+                return self.perform(vals, s);
+            }
+            Stmt::IfThenElse {
+                cond,
+                then_stmt,
+                else_stmt,
+            } => {
+                let v = cond.eval_in(s, depth, hook)?;
+                if let Kind::ValueBool = v.flag {
+                    if v.val_b.unwrap() {
+                        return then_stmt.exec(idx + 1, depth + 1, s, hook);
+                    } else {
+                        let else_idx = idx + 1 + then_stmt.size();
+                        return else_stmt.exec(else_idx, depth + 1, s, hook);
+                    }
+                } else {
+                    println!("Error Parsing IfThenElse");
+                }
+            }
+            Stmt::While { cond, stmt, .. } => {
+                let mut cond_i = cond.eval_in(s, depth, hook)?;
+                if let Kind::ValueBool = cond_i.flag {
+                    while cond_i.val_b.unwrap() {
+                        match stmt.exec(idx + 1, depth + 1, s, hook)? {
+                            Flow::Return(v) => return Ok(Flow::Return(v)),
+                            Flow::Break => break,
+                            Flow::Normal | Flow::Continue => {}
+                        }
+                        hook.before(idx, depth, self, s)?;
+                        cond_i = cond.eval_in(s, depth, hook)?;
+                    }
+                } else {
+                    println!("cond is no bool");
+                }
+            }
+            // only used by the verifier
+            Stmt::Assume { .. } => {}
+            Stmt::Read { lhs, ty } => {
+                let v = s.input.borrow_mut().read(ty)?;
+                s.insert(lhs.clone(), v);
+            }
+            Stmt::Break => return Ok(Flow::Break),
+            Stmt::Continue => return Ok(Flow::Continue),
+        }
+        Ok(Flow::Normal)
+    }
+    fn check(&self, t: &mut TyState) -> bool {
+        match self {
+            Stmt::Skip => true,
+            Stmt::Seq { first, second } => {
+                if !first.check(t) {
+                    return false;
+                }
+                second.check(t)
+            }
+            Stmt::Decl { lhs, ty, rhs } => {
+                let found = rhs.infer(t);
+                if let Type::TyIllTyped = found {
+                    return false;
+                }
+                // later statements see the annotated type also if rhs
+                // disagrees with it
+                let ok = ty.as_ref().is_none_or(|ty| *ty == found);
+                t.insert(lhs.clone(), ty.clone().unwrap_or(found));
+                return ok;
+            }
+            Stmt::While { cond, inv, stmt } => {
+                if cond.infer(t) != Type::TyBool {
+                    return false;
+                }
+                if let Some(inv) = inv {
+                    if inv.infer(t) != Type::TyBool {
+                        return false;
+                    }
+                }
+                let outer = std::mem::replace(&mut t.in_loop, true);
+                let ok = stmt.check(t);
+                t.in_loop = outer;
+                ok
+            }
+            Stmt::Step { body, step } => body.check(t) && step.check(t),
+            Stmt::Assign { lhs, rhs } => {
+                let x = lhs;
+                let y = rhs.infer(t);
+                let w = t.get(x).unwrap();
+                *w == y
+            }
+            Stmt::IfThenElse {
+                cond,
+                then_stmt,
+                else_stmt,
+            } => {
+                if cond.infer(t) != Type::TyBool {
+                    return false;
+                }
+                then_stmt.check(t) && else_stmt.check(t)
+            }
+            Stmt::Print { print_exp } => {
+                let ty = print_exp.infer(t);
+                if let Type::TyIllTyped = ty {
+                    return false;
+                }
+                true
+            }
+            Stmt::Assert { cond } | Stmt::Assume { cond } => cond.infer(t) == Type::TyBool,
+            Stmt::Return { exp } => match t.ret.clone() {
+                Some(ret) => exp.infer(t) == ret,
+                None => false,
+            },
+            Stmt::ArrayAssign { lhs, index, rhs } => match t.get(lhs).cloned() {
+                Some(Type::TyArray(ty)) => index.infer(t) == Type::TyInt && rhs.infer(t) == *ty,
+                _ => false,
+            },
+            Stmt::FieldAssign { lhs, field, rhs } => match t.get(lhs).cloned() {
+                Some(ty) => t.field(&ty, field).is_some_and(|f| rhs.infer(t) == f),
+                None => false,
+            },
+            Stmt::Break | Stmt::Continue => t.in_loop,
+            Stmt::Read { lhs, ty } => {
+                t.insert(lhs.clone(), ty.clone());
+                input::readable(ty)
+            }
+        }
+    }
+    // True if every execution of the statement which ends ends with return
+    fn always_returns(&self) -> bool {
+        match self {
+            Stmt::Return { .. } => true,
+            Stmt::Seq { first, second } => first.always_returns() || second.always_returns(),
+            Stmt::IfThenElse {
+                then_stmt,
+                else_stmt,
+                ..
+            } => then_stmt.always_returns() && else_stmt.always_returns(),
+            _ => false,
+        }
+    }
+}
+
+// for init; cond; step invariant inv of the while loop w, which is a for
+// loop, where init may be missing
+fn for_header(init: Option<&Stmt>, w: &Stmt) -> String {
+    let Stmt::While { cond, inv, stmt } = w else {
+        unreachable!()
+    };
+    let Stmt::Step { step, .. } = &**stmt else {
+        unreachable!()
+    };
+    let mut x = String::from("for");
+    if let Some(init) = init {
+        x.push(' ');
+        x.push_str(&init.pretty());
+    }
+    x.push_str("; ");
+    x.push_str(&cond.pretty());
+    x.push_str("; ");
+    x.push_str(&step.pretty());
+    if let Some(inv) = inv {
+        x.push_str(" invariant ");
+        x.push_str(&inv.pretty());
+    }
+    x
+}
+
+impl Proc {
+    // proc f(x: int): int, without the body
+    pub fn header(&self) -> String {
+        let params: Vec<String> = self
+            .params
+            .iter()
+            .map(|(x, ty)| format!("{}: {}", x, ty))
+            .collect();
+        format!("proc {}({}): {}", self.name, params.join(", "), self.ret)
+    }
+    // Checks the body in an environment with the parameters; t has the
+    // signatures of the procedures
+    fn check(&self, t: &TyState) -> bool {
+        let mut names: Vec<&String> = self.params.iter().map(|(x, _)| x).collect();
+        names.sort();
+        names.dedup();
+        names.len() == self.params.len()
+            && self.body.check(&mut t.enter(self))
+            && self.body.always_returns()
+    }
+}
+
+impl Record {
+    // record Point { x: int, y: int }
+    pub fn header(&self) -> String {
+        let fields: Vec<String> = self
+            .fields
+            .iter()
+            .map(|(x, ty)| format!("{}: {}", x, ty))
+            .collect();
+        format!("record {} {{ {} }}", self.name, fields.join(", "))
+    }
+}
+
+// Type checks the procedures and then the main program stmt. Procedure
+// and record names must be unique, a procedure may call itself and every
+// other one. The field names of a record must be unique and the types in
+// the declarations declared.
+pub fn check_program(records: &[Record], procs: &[Proc], stmt: &Stmt) -> bool {
+    let t = TyState::with_procs(procs).with_records(records);
+    if t.procs.len() != procs.len() || t.records.len() != records.len() {
+        return false;
+    }
+    for r in records {
+        let mut names: Vec<&String> = r.fields.iter().map(|(x, _)| x).collect();
+        names.sort();
+        names.dedup();
+        if names.len() != r.fields.len() {
+            return false;
+        }
+    }
+    let fields = records
+        .iter()
+        .flat_map(|r| r.fields.iter().map(|(_, ty)| ty));
+    let sigs = procs
+        .iter()
+        .flat_map(|p| p.params.iter().map(|(_, ty)| ty).chain([&p.ret]));
+    if !fields.chain(sigs).all(|ty| t.declared(ty)) {
+        return false;
+    }
+    procs.iter().all(|p| p.check(&t)) && stmt.check(&mut t.clone())
+}
+
+pub fn number(val: i32) -> Box<Exp> {
+    return Box::new(Exp::Num { val });
+}
+pub fn boolean(val: bool) -> Box<Exp> {
+    return Box::new(Exp::Bool { val });
+}
+pub fn string(val: String) -> Box<Exp> {
+    Box::new(Exp::Str { val })
+}
+pub fn mult(left: Box<Exp>, right: Box<Exp>) -> Box<Exp> {
+    Box::new(Exp::Mult { left, right })
+}
+pub fn plus(left: Box<Exp>, right: Box<Exp>) -> Box<Exp> {
+    Box::new(Exp::Plus { left, right })
+}
+pub fn and(left: Box<Exp>, right: Box<Exp>) -> Box<Exp> {
+    Box::new(Exp::And { left, right })
+}
+pub fn or(left: Box<Exp>, right: Box<Exp>) -> Box<Exp> {
+    Box::new(Exp::Or { left, right })
+}
+pub fn less(left: Box<Exp>, right: Box<Exp>) -> Box<Exp> {
+    Box::new(Exp::Less { left, right })
+}
+pub fn equal(left: Box<Exp>, right: Box<Exp>) -> Box<Exp> {
+    Box::new(Exp::Equ { left, right })
+}
+pub fn not(exp: Box<Exp>) -> Box<Exp> {
+    Box::new(Exp::Neg { exp })
+}
+pub fn group(exp: Box<Exp>) -> Box<Exp> {
+    Box::new(Exp::Grp { exp })
+}
+pub fn skip() -> Box<Stmt> {
+    Box::new(Stmt::Skip)
+}
+pub fn seq(first: Box<Stmt>, second: Box<Stmt>) -> Box<Stmt> {
+    Box::new(Stmt::Seq { first, second })
+}
+pub fn decl(lhs: String, rhs: Box<Exp>) -> Box<Stmt> {
+    Box::new(Stmt::Decl { lhs, ty: None, rhs })
+}
+pub fn decl_ty(lhs: String, ty: Type, rhs: Box<Exp>) -> Box<Stmt> {
+    Box::new(Stmt::Decl {
+        lhs,
+        ty: Some(ty),
+        rhs,
+    })
+}
+pub fn variable(name: String) -> Box<Exp> {
+    let x = Exp::Var { name };
+    Box::new(x)
+}
+pub fn assign(lhs: String, rhs: Box<Exp>) -> Box<Stmt> {
+    Box::new(Stmt::Assign { lhs, rhs })
+}
+pub fn ifthenelse(cond: Box<Exp>, then_stmt: Box<Stmt>, else_stmt: Box<Stmt>) -> Box<Stmt> {
+    Box::new(Stmt::IfThenElse {
+        cond,
+        then_stmt,
+        else_stmt,
+    })
+}
+pub fn _while(cond: Box<Exp>, stmt: Box<Stmt>) -> Box<Stmt> {
+    Box::new(Stmt::While {
+        cond,
+        inv: None,
+        stmt,
+    })
+}
+pub fn while_inv(cond: Box<Exp>, inv: Box<Exp>, stmt: Box<Stmt>) -> Box<Stmt> {
+    Box::new(Stmt::While {
+        cond,
+        inv: Some(inv),
+        stmt,
+    })
+}
+// for init; cond; step stmt is init; while cond { stmt; step }, where
+// step also runs after continue
+pub fn _for(
+    init: Option<Box<Stmt>>,
+    cond: Box<Exp>,
+    inv: Option<Box<Exp>>,
+    step: Box<Stmt>,
+    stmt: Box<Stmt>,
+) -> Box<Stmt> {
+    let w = Box::new(Stmt::While {
+        cond,
+        inv,
+        stmt: Box::new(Stmt::Step { body: stmt, step }),
+    });
+    match init {
+        Some(init) => seq(init, w),
+        None => w,
+    }
+}
+pub fn read(lhs: String, ty: Type) -> Box<Stmt> {
+    Box::new(Stmt::Read { lhs, ty })
+}
+pub fn _break() -> Box<Stmt> {
+    Box::new(Stmt::Break)
+}
+pub fn _continue() -> Box<Stmt> {
+    Box::new(Stmt::Continue)
+}
+pub fn print(print_exp: Box<Exp>) -> Box<Stmt> {
+    Box::new(Stmt::Print { print_exp })
+}
+pub fn assert(cond: Box<Exp>) -> Box<Stmt> {
+    Box::new(Stmt::Assert { cond })
+}
+pub fn assume(cond: Box<Exp>) -> Box<Stmt> {
+    Box::new(Stmt::Assume { cond })
+}
+pub fn call(name: String, args: Vec<Exp>) -> Box<Exp> {
+    Box::new(Exp::Call { name, args })
+}
+pub fn lambda(param: String, ty: Type, body: Box<Exp>) -> Box<Exp> {
+    Box::new(Exp::Lambda { param, ty, body })
+}
+pub fn array(elems: Vec<Exp>) -> Box<Exp> {
+    Box::new(Exp::Array { elems })
+}
+pub fn index(array: Box<Exp>, index: Box<Exp>) -> Box<Exp> {
+    Box::new(Exp::Index { array, index })
+}
+pub fn update(array: Box<Exp>, index: Box<Exp>, value: Box<Exp>) -> Box<Exp> {
+    Box::new(Exp::Update {
+        array,
+        index,
+        value,
+    })
+}
+pub fn len(exp: Box<Exp>) -> Box<Exp> {
+    Box::new(Exp::Len { exp })
+}
+pub fn array_assign(lhs: String, index: Box<Exp>, rhs: Box<Exp>) -> Box<Stmt> {
+    Box::new(Stmt::ArrayAssign { lhs, index, rhs })
+}
+pub fn record(name: String, fields: Vec<(String, Exp)>) -> Box<Exp> {
+    Box::new(Exp::Record { name, fields })
+}
+pub fn field(exp: Box<Exp>, field: String) -> Box<Exp> {
+    Box::new(Exp::Field { exp, field })
+}
+pub fn with(exp: Box<Exp>, field: String, value: Box<Exp>) -> Box<Exp> {
+    Box::new(Exp::With { exp, field, value })
+}
+pub fn field_assign(lhs: String, field: String, rhs: Box<Exp>) -> Box<Stmt> {
+    Box::new(Stmt::FieldAssign { lhs, field, rhs })
+}
+pub fn _return(exp: Box<Exp>) -> Box<Stmt> {
+    Box::new(Stmt::Return { exp })
+}
+
+// Runs stmt in an empty state
+pub fn exec(stmt: &Stmt) -> Result<(), RuntimeError> {
+    stmt.eval(&mut ValState::new())
+}
+
+// Runs stmt in the state s with the given fuel. The state reached is
+// returned also if the execution fails.
+pub fn exec_fuel(
+    stmt: &Stmt,
+    mut s: ValState,
+    fuel: &mut Fuel,
+) -> (ValState, Result<(), RuntimeError>) {
+    let r = exec_hooked(stmt, &mut s, fuel);
+    (s, r)
+}
+
+// Runs stmt in the state s and calls hook before each statement. The
+// procedures are those of s, a return in stmt ends the program.
+pub fn exec_hooked(stmt: &Stmt, s: &mut ValState, hook: &mut dyn Hook) -> Result<(), RuntimeError> {
+    stmt.exec(0, 0, s, hook)?;
+    Ok(())
+}
+
+fn run_exp(e: Box<Exp>) {
+    let mut s = ValState::new();
+    let mut t = TyState::new();
+    println!("*******");
+    println!("{}", e.pretty());
+    println!("{}", e.eval(&mut s));
+    println!("{}", e.infer(&mut t));
+}
+fn run_stmt(stmt: Box<Stmt>, fuel: Fuel) {
+    run_stmt_in(stmt, ValState::new(), fuel)
+}
+fn run_stmt_in(stmt: Box<Stmt>, mut s: ValState, mut fuel: Fuel) {
+    let mut t = TyState::new();
+    println!("*******");
+    println!("{}", stmt.pretty());
+    if let Err(e) = exec_hooked(&stmt, &mut s, &mut fuel) {
+        println!("error: {}", e);
+    }
+    println!("state: {:?}", *s);
+    println!("type checker: {:?}", stmt.check(&mut t))
+}
+pub fn run() {
+    let mut ast = number(5);
+    run_exp(ast);
+    ast = plus(mult(number(1), number(2)), number(0));
+
+    run_exp(ast);
+    ast = and(boolean(false), number(0));
+    run_exp(ast);
+
+    ast = or(boolean(false), number(0));
+    run_exp(ast);
+    ast = less(number(0), number(1));
+    run_exp(ast);
+    let ast_stmt = seq(
+        seq(
+            decl("x".to_string(), number(1)),
+            decl("y".to_string(), plus(number(6), variable("x".to_string()))),
+        ),
+        assert(equal(variable("y".to_string()), number(7))),
+    );
+    run_stmt(ast_stmt, Fuel::unlimited());
+    let ast_stmt = seq(
+        decl("x".to_string(), number(0)),
+        _while(
+            boolean(true),
+            assign("x".to_string(), plus(variable("x".to_string()), number(1))),
+        ),
+    );
+    run_stmt(ast_stmt, Fuel::limit(100));
+    let ast_stmt = seq(
+        seq(
+            decl(
+                "s".to_string(),
+                plus(string("Hello, ".to_string()), string("world".to_string())),
+            ),
+            print(variable("s".to_string())),
+        ),
+        assert(and(
+            equal(len(variable("s".to_string())), number(12)),
+            less(string("Hello".to_string()), variable("s".to_string())),
+        )),
+    );
+    run_stmt(ast_stmt, Fuel::unlimited());
+    let ast_stmt = seq(
+        seq(
+            read("n".to_string(), Type::TyInt),
+            read("b".to_string(), Type::TyBool),
+        ),
+        print(variable("n".to_string())),
+    );
+    let input = input::Queue::new(vec![Val::mk_int(&5), Val::mk_bool(&true)]);
+    run_stmt_in(
+        ast_stmt,
+        ValState::new().with_input(input),
+        Fuel::unlimited(),
+    );
+}
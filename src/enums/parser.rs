@@ -0,0 +1,859 @@
+use super::*;
+use std::fmt;
+
+// Concrete syntax of the enums model
+//
+// prog  ::= record* proc* (stmts | "{" stmts "}")
+// record::= "record" name "{" [param ("," param)*] "}"
+// proc  ::= "proc" name "(" [param ("," param)*] ")" ":" type block
+// param ::= name ":" type
+// type  ::= ("int" | "bool" | "string" | "array" "<" type ">" | name | "(" type ")")
+//           ["->" type]
+// stmts ::= stmt (";" stmt)* [";"]
+// stmt  ::= "skip" | "print" exp | name [":" type] ":=" exp | "var" name ":" type
+//         | name "=" exp | name "[" exp "]" "=" exp | name "." name "=" exp
+//         | "while" exp ["invariant" exp] block | "if" exp block "else" block
+//         | "for" [simple] ";" exp ";" simple ["invariant" exp] block
+//         | "assert" exp | "assume" exp | "return" exp | "break" | "continue"
+//         | "read" name ":" type
+// simple::= name [":" type] ":=" exp | name "=" exp | name "[" exp "]" "=" exp | name "." name "=" exp
+// block ::= "{" stmts "}"
+// exp   ::= and ("||" and)*
+// and   ::= cmp ("&&" cmp)*
+// cmp   ::= sum [("==" | "<") sum]
+// sum   ::= prod ("+" prod)*
+// prod  ::= unary ("*" unary)*
+// unary ::= "!" unary | post
+// post  ::= atom ("[" exp [":=" exp] "]" | "." name | "." "{" name ":=" exp "}")*
+// atom  ::= int | string | "true" | "false" | name | name "(" [exp ("," exp)*] ")"
+//         | "(" exp ")" | "fn" "(" name [":" type] ")" "=>" exp | "[" exp ("," exp)* "]"
+//         | "len" "(" exp ")" | name "{" [name ":" exp ("," name ":" exp)*] "}"
+//
+// A name in a type is that of a record. name "{" starts a literal only if
+// a record of that name has been declared, otherwise it is a variable
+// followed by a block as in "if b { .. }".
+//
+// var x: ty declares x with the default value of ty, 0, false, "" or a
+// record of defaults, as x: ty := value. The position of the statement is
+// that of x.
+//
+// A for loop is its init followed by a While whose step runs after the
+// body, so the init is numbered before the loop and the step after the body.
+//
+// A string is written in double quotes, \" \\ \n and \t are its escape
+// sequences. Everything after "//" up to the end of the line is a comment.
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token {
+    Int(i32),
+    Str(String),
+    Ident(String),
+    Sym(&'static str),
+    Eof,
+}
+
+impl Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Int(n) => write!(f, "{}", n),
+            Token::Str(x) => write!(f, "{}", quote(x)),
+            Token::Ident(x) => write!(f, "{}", x),
+            Token::Sym(s) => write!(f, "{}", s),
+            Token::Eof => write!(f, "end of input"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub struct Pos {
+    pub line: usize,
+    pub col: usize,
+}
+
+#[derive(Debug)]
+pub struct ParseError {
+    pub pos: Pos,
+    pub msg: String,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.pos.line, self.pos.col, self.msg)
+    }
+}
+
+// Source range, end is the position just after the last character
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Span {
+    pub start: Pos,
+    pub end: Pos,
+}
+
+impl Span {
+    pub fn contains(&self, p: Pos) -> bool {
+        self.start <= p && p < self.end
+    }
+    pub fn within(&self, o: &Span) -> bool {
+        o.start <= self.start && self.end <= o.end
+    }
+}
+
+// An expression of the program with its range and the index of the
+// statement it belongs to
+pub struct ExpInfo {
+    pub span: Span,
+    pub stmt: usize,
+    pub exp: Exp,
+}
+
+pub struct Comment {
+    pub pos: Pos,
+    pub text: String,
+}
+
+// A parsed program. Statements are numbered in pre-order, leaving out Seq
+// nodes, which is the order they appear in the source text; the main
+// program comes first, then the bodies of the procedures (see Proc::start).
+// pos[i] is the position of the i-th statement. exps has every
+// subexpression, inner ones before the expressions containing them.
+// record_pos and proc_pos have the positions of the names of the records
+// and procedures.
+pub struct Program {
+    pub records: Vec<Record>,
+    pub procs: Vec<Proc>,
+    pub stmt: Box<Stmt>,
+    pub pos: Vec<Pos>,
+    pub record_pos: Vec<Pos>,
+    pub proc_pos: Vec<Pos>,
+    pub exps: Vec<ExpInfo>,
+    pub comments: Vec<Comment>,
+}
+
+const SYMBOLS: [&str; 22] = [
+    ":=", "==", "&&", "||", "->", "=>", "=", "<", ">", "+", "*", "!", "(", ")", "{", "}", "[", "]",
+    ";", ":", ",", ".",
+];
+
+type Tokens = Vec<(Token, Pos)>;
+
+pub fn tokenize(src: &str) -> Result<(Tokens, Vec<Comment>), ParseError> {
+    let chars: Vec<char> = src.chars().collect();
+    let mut tokens = Vec::new();
+    let mut comments = Vec::new();
+    let mut i = 0;
+    let mut line = 1;
+    let mut col = 1;
+    while i < chars.len() {
+        let c = chars[i];
+        let pos = Pos { line, col };
+        if c == '\n' {
+            line += 1;
+            col = 1;
+            i += 1;
+            continue;
+        }
+        if c.is_whitespace() {
+            i += 1;
+            col += 1;
+            continue;
+        }
+        if c == '/' && chars.get(i + 1) == Some(&'/') {
+            let start = i + 2;
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().collect();
+            comments.push(Comment {
+                pos,
+                text: text.trim().to_string(),
+            });
+            continue;
+        }
+        if c.is_ascii_digit() {
+            let start = i;
+            while i < chars.len() && chars[i].is_ascii_digit() {
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().collect();
+            let n = text.parse::<i32>().map_err(|_| ParseError {
+                pos,
+                msg: format!("integer literal {} out of range", text),
+            })?;
+            col += i - start;
+            tokens.push((Token::Int(n), pos));
+            continue;
+        }
+        if c == '"' {
+            let start = i;
+            let mut x = String::new();
+            i += 1;
+            loop {
+                let c = match chars.get(i) {
+                    Some('\n') | None => {
+                        return Err(ParseError {
+                            pos,
+                            msg: "unterminated string".to_string(),
+                        })
+                    }
+                    Some(c) => *c,
+                };
+                i += 1;
+                match c {
+                    '"' => break,
+                    '\\' => {
+                        let e = chars.get(i).copied();
+                        x.push(match e {
+                            Some('"') => '"',
+                            Some('\\') => '\\',
+                            Some('n') => '\n',
+                            Some('t') => '\t',
+                            _ => {
+                                return Err(ParseError {
+                                    pos: Pos {
+                                        line,
+                                        col: col + i - 1 - start,
+                                    },
+                                    msg: format!(
+                                        "unknown escape sequence '\\{}'",
+                                        e.unwrap_or(' ')
+                                    ),
+                                })
+                            }
+                        });
+                        i += 1;
+                    }
+                    c => x.push(c),
+                }
+            }
+            col += i - start;
+            tokens.push((Token::Str(x), pos));
+            continue;
+        }
+        if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            col += i - start;
+            tokens.push((Token::Ident(chars[start..i].iter().collect()), pos));
+            continue;
+        }
+        let rest: String = chars[i..chars.len().min(i + 2)].iter().collect();
+        match SYMBOLS.iter().find(|s| rest.starts_with(**s)) {
+            Some(s) => {
+                i += s.len();
+                col += s.len();
+                tokens.push((Token::Sym(s), pos));
+            }
+            None => {
+                return Err(ParseError {
+                    pos,
+                    msg: format!("unexpected character '{}'", c),
+                })
+            }
+        }
+    }
+    tokens.push((Token::Eof, Pos { line, col }));
+    Ok((tokens, comments))
+}
+
+const KEYWORDS: [&str; 20] = [
+    "true",
+    "false",
+    "print",
+    "while",
+    "if",
+    "else",
+    "assert",
+    "assume",
+    "invariant",
+    "skip",
+    "proc",
+    "return",
+    "fn",
+    "len",
+    "record",
+    "for",
+    "break",
+    "continue",
+    "read",
+    "var",
+];
+
+pub struct Parser {
+    tokens: Tokens,
+    at: usize,
+    stmt_pos: Vec<Pos>,
+    exps: Vec<ExpInfo>,
+    // procedures with the positions of their statements and the range of
+    // their expressions in exps, numbered from 0 until the main program
+    // has been parsed
+    procs: Vec<(Proc, Vec<Pos>, std::ops::Range<usize>)>,
+    records: Vec<Record>,
+    // the positions of the names of the records and procedures
+    record_pos: Vec<Pos>,
+    proc_pos: Vec<Pos>,
+}
+
+impl Parser {
+    pub fn new(tokens: Tokens) -> Parser {
+        Parser {
+            tokens,
+            at: 0,
+            stmt_pos: Vec::new(),
+            exps: Vec::new(),
+            procs: Vec::new(),
+            records: Vec::new(),
+            record_pos: Vec::new(),
+            proc_pos: Vec::new(),
+        }
+    }
+    fn peek(&self) -> &Token {
+        &self.tokens[self.at].0
+    }
+    fn pos(&self) -> Pos {
+        self.tokens[self.at].1
+    }
+    fn next(&mut self) -> Token {
+        let t = self.tokens[self.at].0.clone();
+        if self.at + 1 < self.tokens.len() {
+            self.at += 1;
+        }
+        t
+    }
+    // Position after the last consumed token
+    fn end(&self) -> Pos {
+        let (t, p) = &self.tokens[self.at.saturating_sub(1)];
+        Pos {
+            line: p.line,
+            col: p.col + t.to_string().chars().count(),
+        }
+    }
+    // Records e as an expression starting at start and ending here
+    fn span(&mut self, start: Pos, e: Box<Exp>) -> Box<Exp> {
+        self.exps.push(ExpInfo {
+            span: Span {
+                start,
+                end: self.end(),
+            },
+            stmt: self.stmt_pos.len().saturating_sub(1),
+            exp: (*e).clone(),
+        });
+        e
+    }
+    fn error<T>(&self, msg: String) -> Result<T, ParseError> {
+        Err(ParseError {
+            pos: self.pos(),
+            msg,
+        })
+    }
+    fn is_sym(&self, s: &str) -> bool {
+        matches!(self.peek(), Token::Sym(x) if *x == s)
+    }
+    fn is_record(&self, name: &str) -> bool {
+        self.records.iter().any(|r| r.name == name)
+    }
+    fn is_keyword(&self, k: &str) -> bool {
+        matches!(self.peek(), Token::Ident(x) if x == k)
+    }
+    fn expect_sym(&mut self, s: &str) -> Result<(), ParseError> {
+        if self.is_sym(s) {
+            self.next();
+            return Ok(());
+        }
+        self.error(format!("expected '{}', found '{}'", s, self.peek()))
+    }
+    fn expect_keyword(&mut self, k: &str) -> Result<(), ParseError> {
+        if self.is_keyword(k) {
+            self.next();
+            return Ok(());
+        }
+        self.error(format!("expected '{}', found '{}'", k, self.peek()))
+    }
+    fn name(&mut self) -> Result<String, ParseError> {
+        match self.peek().clone() {
+            Token::Ident(x) if !KEYWORDS.contains(&x.as_str()) => {
+                self.next();
+                Ok(x)
+            }
+            t => self.error(format!("expected a variable name, found '{}'", t)),
+        }
+    }
+
+    pub fn program(&mut self) -> Result<Box<Stmt>, ParseError> {
+        while self.is_keyword("record") {
+            self.record()?;
+        }
+        while self.is_keyword("proc") {
+            self.proc()?;
+        }
+        let stmt = if self.is_sym("{") {
+            self.block()?
+        } else {
+            self.stmts()?
+        };
+        if *self.peek() != Token::Eof {
+            return self.error(format!("unexpected '{}'", self.peek()));
+        }
+        Ok(stmt)
+    }
+    // -> associates to the right
+    fn ty(&mut self) -> Result<Type, ParseError> {
+        let ty = match self.peek() {
+            Token::Ident(x) if x == "int" => Type::TyInt,
+            Token::Ident(x) if x == "bool" => Type::TyBool,
+            Token::Ident(x) if x == "string" => Type::TyStr,
+            Token::Ident(x) if x == "array" => {
+                self.next();
+                self.expect_sym("<")?;
+                let ty = self.ty()?;
+                self.expect_sym(">")?;
+                return self.fun_ty(Type::TyArray(Box::new(ty)));
+            }
+            Token::Sym("(") => {
+                self.next();
+                let ty = self.ty()?;
+                self.expect_sym(")")?;
+                return self.fun_ty(ty);
+            }
+            Token::Ident(_) => {
+                let name = self.name()?;
+                return self.fun_ty(Type::TyRecord(name));
+            }
+            t => return self.error(format!("expected a type, found '{}'", t)),
+        };
+        self.next();
+        self.fun_ty(ty)
+    }
+    fn fun_ty(&mut self, param: Type) -> Result<Type, ParseError> {
+        if !self.is_sym("->") {
+            return Ok(param);
+        }
+        self.next();
+        Ok(Type::TyFun(Box::new(param), Box::new(self.ty()?)))
+    }
+    fn record(&mut self) -> Result<(), ParseError> {
+        self.expect_keyword("record")?;
+        self.record_pos.push(self.pos());
+        let name = self.name()?;
+        self.expect_sym("{")?;
+        let mut fields = Vec::new();
+        while !self.is_sym("}") {
+            if !fields.is_empty() {
+                self.expect_sym(",")?;
+            }
+            let x = self.name()?;
+            self.expect_sym(":")?;
+            fields.push((x, self.ty()?));
+        }
+        self.next();
+        self.records.push(Record { name, fields });
+        Ok(())
+    }
+    fn proc(&mut self) -> Result<(), ParseError> {
+        self.expect_keyword("proc")?;
+        self.proc_pos.push(self.pos());
+        let name = self.name()?;
+        self.expect_sym("(")?;
+        let mut params = Vec::new();
+        while !self.is_sym(")") {
+            if !params.is_empty() {
+                self.expect_sym(",")?;
+            }
+            let x = self.name()?;
+            self.expect_sym(":")?;
+            params.push((x, self.ty()?));
+        }
+        self.next();
+        self.expect_sym(":")?;
+        let ret = self.ty()?;
+        let main_pos = std::mem::take(&mut self.stmt_pos);
+        let first_exp = self.exps.len();
+        let body = self.block()?;
+        let pos = std::mem::replace(&mut self.stmt_pos, main_pos);
+        let proc = Proc {
+            name,
+            params,
+            ret,
+            body,
+            start: 0,
+        };
+        self.procs.push((proc, pos, first_exp..self.exps.len()));
+        Ok(())
+    }
+    // Numbers the statements of the procedures after those of the main
+    // program
+    fn finish(&mut self) -> Vec<Proc> {
+        let mut procs = Vec::new();
+        for (mut proc, pos, exps) in std::mem::take(&mut self.procs) {
+            proc.start = self.stmt_pos.len();
+            for e in &mut self.exps[exps] {
+                e.stmt += proc.start;
+            }
+            self.stmt_pos.extend(pos);
+            procs.push(proc);
+        }
+        procs
+    }
+    fn block(&mut self) -> Result<Box<Stmt>, ParseError> {
+        self.expect_sym("{")?;
+        let stmt = self.stmts()?;
+        self.expect_sym("}")?;
+        Ok(stmt)
+    }
+    fn stmts(&mut self) -> Result<Box<Stmt>, ParseError> {
+        let first = self.stmt()?;
+        if self.is_sym(";") {
+            self.next();
+            if self.is_sym("}") || *self.peek() == Token::Eof {
+                return Ok(first);
+            }
+            return Ok(seq(first, self.stmts()?));
+        }
+        Ok(first)
+    }
+    fn stmt(&mut self) -> Result<Box<Stmt>, ParseError> {
+        self.stmt_pos.push(self.pos());
+        if self.is_keyword("skip") {
+            self.next();
+            return Ok(skip());
+        }
+        if self.is_keyword("print") {
+            self.next();
+            return Ok(print(self.exp()?));
+        }
+        if self.is_keyword("assert") {
+            self.next();
+            return Ok(assert(self.exp()?));
+        }
+        if self.is_keyword("assume") {
+            self.next();
+            return Ok(assume(self.exp()?));
+        }
+        if self.is_keyword("return") {
+            self.next();
+            return Ok(_return(self.exp()?));
+        }
+        if self.is_keyword("read") {
+            self.next();
+            let lhs = self.name()?;
+            self.expect_sym(":")?;
+            return Ok(read(lhs, self.ty()?));
+        }
+        if self.is_keyword("var") {
+            self.next();
+            self.stmt_pos.pop();
+            self.stmt_pos.push(self.pos());
+            let lhs = self.name()?;
+            self.expect_sym(":")?;
+            let ty = self.ty()?;
+            return match default_value(&ty, &self.records) {
+                Some(v) => Ok(decl_ty(lhs, ty, v)),
+                None => self.error(format!(
+                    "no default value of type {}, use {}: {} := ..",
+                    ty, lhs, ty
+                )),
+            };
+        }
+        if self.is_keyword("while") {
+            self.next();
+            let cond = self.exp()?;
+            if self.is_keyword("invariant") {
+                self.next();
+                let inv = self.exp()?;
+                return Ok(while_inv(cond, inv, self.block()?));
+            }
+            return Ok(_while(cond, self.block()?));
+        }
+        if self.is_keyword("break") {
+            self.next();
+            return Ok(_break());
+        }
+        if self.is_keyword("continue") {
+            self.next();
+            return Ok(_continue());
+        }
+        if self.is_keyword("for") {
+            self.next();
+            let for_pos = self.stmt_pos.pop().unwrap();
+            let init = if self.is_sym(";") {
+                None
+            } else {
+                Some(self.simple()?)
+            };
+            self.expect_sym(";")?;
+            self.stmt_pos.push(for_pos);
+            let cond = self.exp()?;
+            self.expect_sym(";")?;
+            let first_exp = self.exps.len();
+            let step = self.simple()?;
+            // the step is numbered again once the body is done
+            let step_pos = self.stmt_pos.pop().unwrap();
+            let step_exps = first_exp..self.exps.len();
+            let inv = if self.is_keyword("invariant") {
+                self.next();
+                Some(self.exp()?)
+            } else {
+                None
+            };
+            let body = self.block()?;
+            let step_idx = self.stmt_pos.len();
+            self.stmt_pos.push(step_pos);
+            for e in &mut self.exps[step_exps] {
+                e.stmt = step_idx;
+            }
+            return Ok(_for(init, cond, inv, step, body));
+        }
+        if self.is_keyword("if") {
+            self.next();
+            let cond = self.exp()?;
+            let then_stmt = self.block()?;
+            self.expect_keyword("else")?;
+            return Ok(ifthenelse(cond, then_stmt, self.block()?));
+        }
+        let lhs = self.name()?;
+        if self.is_sym("[") {
+            self.next();
+            let index = self.exp()?;
+            self.expect_sym("]")?;
+            self.expect_sym("=")?;
+            return Ok(array_assign(lhs, index, self.exp()?));
+        }
+        if self.is_sym(".") {
+            self.next();
+            let f = self.name()?;
+            self.expect_sym("=")?;
+            return Ok(field_assign(lhs, f, self.exp()?));
+        }
+        if self.is_sym(":") {
+            self.next();
+            let ty = self.ty()?;
+            self.expect_sym(":=")?;
+            return Ok(decl_ty(lhs, ty, self.exp()?));
+        }
+        if self.is_sym(":=") {
+            self.next();
+            return Ok(decl(lhs, self.exp()?));
+        }
+        if self.is_sym("=") {
+            self.next();
+            return Ok(assign(lhs, self.exp()?));
+        }
+        self.error(format!(
+            "expected ':=', ':', '=', '[' or '.', found '{}'",
+            self.peek()
+        ))
+    }
+    // The init and step of a for loop
+    fn simple(&mut self) -> Result<Box<Stmt>, ParseError> {
+        if matches!(self.peek(), Token::Ident(x) if KEYWORDS.contains(&x.as_str())) {
+            return self.error(format!("expected an assignment, found '{}'", self.peek()));
+        }
+        self.stmt()
+    }
+    pub fn exp(&mut self) -> Result<Box<Exp>, ParseError> {
+        let start = self.pos();
+        let mut left = self.and()?;
+        while self.is_sym("||") {
+            self.next();
+            let e = or(left, self.and()?);
+            left = self.span(start, e);
+        }
+        Ok(left)
+    }
+    fn and(&mut self) -> Result<Box<Exp>, ParseError> {
+        let start = self.pos();
+        let mut left = self.cmp()?;
+        while self.is_sym("&&") {
+            self.next();
+            let e = and(left, self.cmp()?);
+            left = self.span(start, e);
+        }
+        Ok(left)
+    }
+    fn cmp(&mut self) -> Result<Box<Exp>, ParseError> {
+        let start = self.pos();
+        let left = self.sum()?;
+        if self.is_sym("==") {
+            self.next();
+            let e = equal(left, self.sum()?);
+            return Ok(self.span(start, e));
+        }
+        if self.is_sym("<") {
+            self.next();
+            let e = less(left, self.sum()?);
+            return Ok(self.span(start, e));
+        }
+        Ok(left)
+    }
+    fn sum(&mut self) -> Result<Box<Exp>, ParseError> {
+        let start = self.pos();
+        let mut left = self.prod()?;
+        while self.is_sym("+") {
+            self.next();
+            let e = plus(left, self.prod()?);
+            left = self.span(start, e);
+        }
+        Ok(left)
+    }
+    fn prod(&mut self) -> Result<Box<Exp>, ParseError> {
+        let start = self.pos();
+        let mut left = self.unary()?;
+        while self.is_sym("*") {
+            self.next();
+            let e = mult(left, self.unary()?);
+            left = self.span(start, e);
+        }
+        Ok(left)
+    }
+    fn unary(&mut self) -> Result<Box<Exp>, ParseError> {
+        let start = self.pos();
+        if self.is_sym("!") {
+            self.next();
+            let e = not(self.unary()?);
+            return Ok(self.span(start, e));
+        }
+        self.post()
+    }
+    // Indexing, field access and updates bind tighter than any operator
+    fn post(&mut self) -> Result<Box<Exp>, ParseError> {
+        let start = self.pos();
+        let mut e = self.atom()?;
+        loop {
+            let post = if self.is_sym("[") {
+                self.next();
+                let i = self.exp()?;
+                let post = if self.is_sym(":=") {
+                    self.next();
+                    update(e, i, self.exp()?)
+                } else {
+                    index(e, i)
+                };
+                self.expect_sym("]")?;
+                post
+            } else if self.is_sym(".") {
+                self.next();
+                if !self.is_sym("{") {
+                    field(e, self.name()?)
+                } else {
+                    self.next();
+                    let f = self.name()?;
+                    self.expect_sym(":=")?;
+                    let post = with(e, f, self.exp()?);
+                    self.expect_sym("}")?;
+                    post
+                }
+            } else {
+                return Ok(e);
+            };
+            e = self.span(start, post);
+        }
+    }
+    fn atom(&mut self) -> Result<Box<Exp>, ParseError> {
+        let start = self.pos();
+        let e = match self.peek().clone() {
+            Token::Int(n) => {
+                self.next();
+                number(n)
+            }
+            Token::Str(x) => {
+                self.next();
+                string(x)
+            }
+            Token::Ident(x) if x == "true" || x == "false" => {
+                self.next();
+                boolean(x == "true")
+            }
+            Token::Ident(x) if x == "fn" => {
+                self.next();
+                self.expect_sym("(")?;
+                let param = self.name()?;
+                // the type of the parameter is inferred by hm
+                let ty = if self.is_sym(":") {
+                    self.next();
+                    self.ty()?
+                } else {
+                    Type::TyVar(0)
+                };
+                self.expect_sym(")")?;
+                self.expect_sym("=>")?;
+                lambda(param, ty, self.exp()?)
+            }
+            Token::Ident(x) if x == "len" => {
+                self.next();
+                self.expect_sym("(")?;
+                let e = self.exp()?;
+                self.expect_sym(")")?;
+                len(e)
+            }
+            Token::Ident(x)
+                if self.is_record(&x) && self.tokens[self.at + 1].0 == Token::Sym("{") =>
+            {
+                self.next();
+                self.next();
+                let mut fields = Vec::new();
+                while !self.is_sym("}") {
+                    if !fields.is_empty() {
+                        self.expect_sym(",")?;
+                    }
+                    let f = self.name()?;
+                    self.expect_sym(":")?;
+                    fields.push((f, *self.exp()?));
+                }
+                self.next();
+                record(x, fields)
+            }
+            Token::Ident(_) => {
+                let name = self.name()?;
+                if !self.is_sym("(") {
+                    return Ok(self.span(start, variable(name)));
+                }
+                self.next();
+                let mut args = Vec::new();
+                while !self.is_sym(")") {
+                    if !args.is_empty() {
+                        self.expect_sym(",")?;
+                    }
+                    args.push(*self.exp()?);
+                }
+                self.next();
+                call(name, args)
+            }
+            Token::Sym("(") => {
+                self.next();
+                let e = self.exp()?;
+                self.expect_sym(")")?;
+                group(e)
+            }
+            Token::Sym("[") => {
+                self.next();
+                let mut elems = vec![*self.exp()?];
+                while self.is_sym(",") {
+                    self.next();
+                    elems.push(*self.exp()?);
+                }
+                self.expect_sym("]")?;
+                array(elems)
+            }
+            t => return self.error(format!("expected an expression, found '{}'", t)),
+        };
+        Ok(self.span(start, e))
+    }
+}
+
+pub fn parse(src: &str) -> Result<Program, ParseError> {
+    let (tokens, comments) = tokenize(src)?;
+    let mut p = Parser::new(tokens);
+    let stmt = p.program()?;
+    let procs = p.finish();
+    Ok(Program {
+        records: p.records,
+        procs,
+        stmt,
+        pos: p.stmt_pos,
+        record_pos: p.record_pos,
+        proc_pos: p.proc_pos,
+        exps: p.exps,
+        comments,
+    })
+}
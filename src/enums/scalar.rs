@@ -0,0 +1,91 @@
+use super::*;
+
+// The variables of a procedure with their types, for the backends which
+// only translate programs with ints and bools. target names the backend in
+// the errors about everything else.
+pub struct Vars<'a> {
+    procs: &'a [Proc],
+    target: &'static str,
+    // the parameters and then the variables in the order of their
+    // declarations
+    vars: Vec<(String, Type)>,
+    params: usize,
+}
+
+impl<'a> Vars<'a> {
+    pub fn new(procs: &'a [Proc], params: &[(String, Type)], target: &'static str) -> Vars<'a> {
+        Vars {
+            procs,
+            target,
+            vars: params.to_vec(),
+            params: params.len(),
+        }
+    }
+    // The variables declared so far which are not parameters
+    pub fn locals(&self) -> &[(String, Type)] {
+        &self.vars[self.params..]
+    }
+    // The number of x among the parameters and then the other variables
+    pub fn position(&self, x: &str) -> Option<usize> {
+        self.vars.iter().position(|(y, _)| y == x)
+    }
+    pub fn contains(&self, x: &str) -> bool {
+        self.vars.iter().any(|(y, _)| y == x)
+    }
+    // Declares x, which may be declared again with the same type
+    pub fn declare(&mut self, x: &str, ty: Type) -> Result<(), String> {
+        self.check(&ty)?;
+        match self.vars.iter().find(|(y, _)| y == x) {
+            Some((_, t)) if *t != ty => Err(format!(
+                "{} is declared as {} and as {}, which the {} backend does not allow",
+                x, t, ty, self.target
+            )),
+            Some(_) => Ok(()),
+            None => {
+                self.vars.push((x.to_string(), ty));
+                Ok(())
+            }
+        }
+    }
+    pub fn check(&self, ty: &Type) -> Result<(), String> {
+        match ty {
+            Type::TyInt | Type::TyBool => Ok(()),
+            _ => Err(format!(
+                "values of type {} are not supported by the {} backend",
+                ty, self.target
+            )),
+        }
+    }
+    pub fn unsupported(&self, x: &str) -> String {
+        format!("{} is not supported by the {} backend", x, self.target)
+    }
+    // The type of e, an error if e is no int or bool expression
+    pub fn ty(&self, e: &Exp) -> Result<Type, String> {
+        match e {
+            Exp::Num { .. } | Exp::Mult { .. } => Ok(Type::TyInt),
+            Exp::Bool { .. }
+            | Exp::And { .. }
+            | Exp::Or { .. }
+            | Exp::Neg { .. }
+            | Exp::Equ { .. }
+            | Exp::Less { .. } => Ok(Type::TyBool),
+            Exp::Plus { left, .. } => self.ty(left),
+            Exp::Grp { exp } => self.ty(exp),
+            Exp::Var { name } => match self.vars.iter().find(|(x, _)| x == name) {
+                Some((_, ty)) => Ok(ty.clone()),
+                None => Err(format!("{} is not declared", name)),
+            },
+            Exp::Call { name, .. } => match self.procs.iter().find(|p| p.name == *name) {
+                Some(p) if !self.contains(name) => Ok(p.ret.clone()),
+                _ => Err(self.unsupported(&e.pretty())),
+            },
+            _ => Err(self.unsupported(&e.pretty())),
+        }
+    }
+    // Whether e calls a procedure
+    pub fn has_call(&self, e: &Exp) -> bool {
+        e.vars()
+            .iter()
+            .any(|x| self.procs.iter().any(|p| p.name == *x))
+    }
+}
@@ -0,0 +1,150 @@
+use super::*;
+
+// Small-step semantics of Stmt. A configuration is the residual program
+// together with the state; skip is the final configuration. Each step
+// rewrites the leftmost redex:
+//
+// skip; s2, σ                     -> s2, σ
+// s1; s2, σ                       -> s1'; s2, σ'      if s1, σ -> s1', σ'
+// x := e, σ                       -> skip, σ[x ↦ e]
+// if c s1 else s2, σ              -> s1, σ            if c is true in σ
+// if c s1 else s2, σ              -> s2, σ            if c is false in σ
+// while c s, σ                    -> if c (s; while c s) else skip, σ
+//
+// A step t of a while loop (from a for loop) runs after s, so it unrolls
+// into if c (s; t; while c s) else skip. When the body has a break or
+// continue of the loop, the rest of the body and the next iteration are
+// moved into the branches around it, then break becomes skip and continue
+// t; while c s.
+//
+// Assign, Print, Assert and Assume take a single step like Decl, their
+// expressions are evaluated at once, including the bodies of the procedures
+// they call. So is the condition of an if; a runtime error in any of them
+// ends the trace as it ends Stmt::eval.
+
+pub struct Config {
+    pub stmt: Box<Stmt>,
+    pub state: ValState,
+}
+
+impl Config {
+    pub fn new(stmt: &Stmt, state: ValState) -> Config {
+        Config {
+            stmt: Box::new(stmt.clone()),
+            state,
+        }
+    }
+}
+
+// The configuration after one step, None if c is final
+pub fn step(c: Config) -> Option<Result<Config, RuntimeError>> {
+    let Config { stmt, mut state } = c;
+    let stmt = match *stmt {
+        Stmt::Skip => return None,
+        Stmt::Seq { first, second } => match *first {
+            Stmt::Skip => second,
+            first => {
+                let c = match step(Config {
+                    stmt: Box::new(first),
+                    state,
+                })? {
+                    Ok(c) => c,
+                    Err(e) => return Some(Err(e)),
+                };
+                state = c.state;
+                seq(c.stmt, second)
+            }
+        },
+        Stmt::IfThenElse {
+            cond,
+            then_stmt,
+            else_stmt,
+        } => match cond.eval_in(&mut state, 0, &mut NoHook) {
+            Err(e) => return Some(Err(e)),
+            Ok(v) => match v.val_b {
+                Some(true) => then_stmt,
+                Some(false) => else_stmt,
+                None => {
+                    println!("Error Parsing IfThenElse");
+                    skip()
+                }
+            },
+        },
+        Stmt::While { cond, inv, stmt } => {
+            let again = Box::new(Stmt::While {
+                cond: cond.clone(),
+                inv,
+                stmt: stmt.clone(),
+            });
+            // continue in the body of a for loop goes on with its step
+            match *stmt {
+                Stmt::Step { body, step } => {
+                    let again = seq(step, again);
+                    ifthenelse(cond, unjump(*body, &again, &again), skip())
+                }
+                body => ifthenelse(cond, unjump(body, &again, &again), skip()),
+            }
+        }
+        Stmt::Step { body, step } => seq(body, step),
+        s => {
+            if let Err(e) = s.eval(&mut state) {
+                return Some(Err(e));
+            }
+            skip()
+        }
+    };
+    Some(Ok(Config { stmt, state }))
+}
+
+// s; k where the break and continue statements of the loop around s are
+// replaced by skip and again, and everything which would run after them in s
+// is left out
+fn unjump(s: Stmt, k: &Stmt, again: &Stmt) -> Box<Stmt> {
+    if s.jumps(0).is_empty() {
+        return seq(Box::new(s), Box::new(k.clone()));
+    }
+    match s {
+        Stmt::Break => skip(),
+        Stmt::Continue => Box::new(again.clone()),
+        Stmt::Seq { first, second } => unjump(*first, &unjump(*second, k, again), again),
+        Stmt::IfThenElse {
+            cond,
+            then_stmt,
+            else_stmt,
+        } => ifthenelse(
+            cond,
+            unjump(*then_stmt, k, again),
+            unjump(*else_stmt, k, again),
+        ),
+        // only the statements above contain break and continue of the loop
+        s => seq(Box::new(s), Box::new(k.clone())),
+    }
+}
+
+pub fn show_config(c: &Config) -> String {
+    let mut xs: Vec<String> = c
+        .state
+        .iter()
+        .map(|(x, v)| format!("{} = {}", x, v))
+        .collect();
+    xs.sort();
+    format!("{} | {{{}}}", c.stmt.pretty(), xs.join(", "))
+}
+
+// Runs stmt from the state s to the end and hands every configuration on
+// the way to show
+pub fn trace(
+    stmt: &Stmt,
+    s: ValState,
+    show: &mut impl FnMut(usize, &Config),
+) -> Result<Config, RuntimeError> {
+    let mut c = Config::new(stmt, s);
+    let mut n = 0;
+    show(n, &c);
+    while !matches!(*c.stmt, Stmt::Skip) {
+        c = step(c).unwrap()?;
+        n += 1;
+        show(n, &c);
+    }
+    Ok(c)
+}
@@ -0,0 +1,620 @@
+use super::solver::{guess_types, type_of};
+use super::*;
+use std::collections::BTreeSet;
+
+// Translation into SMT-LIB2. Imp ints are mapped to the unbounded SMT Int
+// sort, so i32 overflow is not modelled.
+//
+// A program is encoded in SSA form: every Decl and Assign defines a new
+// version x_1, x_2, ... of its variable, inputs (variables read before they
+// are declared) are the constants x_0, and a Read declares its version as
+// another unconstrained constant. IfThenElse joins the versions of both
+// branches with ite. While is unrolled a bounded number of times; executions
+// needing more iterations are excluded by an unwinding assumption. Every
+// statement runs under a guard g_k, and for each Print the file contains a
+// check whether its guard is satisfiable. After a break or continue the
+// guard of the rest of the loop body excludes the paths which took it, their
+// versions are joined in at the end of the loop or the iteration.
+//
+// Arrays are sequences; indexing out of bounds is not modelled, seq.nth
+// gives an unspecified value there. Strings are the SMT String sort, + and
+// < are resolved to str.++ and str.< by the types of the operands.
+//
+// A record type Point is a datatype with the constructor mk.Point and a
+// selector Point.x for every field x; an update builds a new value from
+// the selections of the other fields.
+
+// Closures are arrays from their parameter to their result
+fn show_sort(t: &Type) -> String {
+    match t {
+        Type::TyBool => "Bool".to_string(),
+        Type::TyStr => "String".to_string(),
+        Type::TyFun(a, b) => format!("(Array {} {})", show_sort(a), show_sort(b)),
+        Type::TyArray(ty) => format!("(Seq {})", show_sort(ty)),
+        Type::TyRecord(name) => name.clone(),
+        _ => "Int".to_string(),
+    }
+}
+
+// SMT-LIB2 string literal; only printable ASCII is written as is
+fn show_str(x: &str) -> String {
+    let mut t = String::from("\"");
+    for c in x.chars() {
+        match c {
+            '"' => t.push_str("\"\""),
+            ' '..='~' if c != '\\' => t.push(c),
+            c => t.push_str(&format!("\\u{{{:x}}}", c as u32)),
+        }
+    }
+    t.push('"');
+    t
+}
+
+// SMT-LIB2 term of e; name gives the symbol of each variable, types the
+// type of the variables which are known
+fn term(e: &Exp, types: &TyState, name: &mut dyn FnMut(&str) -> String) -> String {
+    let mut bin = |op: &str, l: &Exp, r: &Exp| {
+        let l = term(l, types, name);
+        format!("({} {} {})", op, l, term(r, types, name))
+    };
+    let is_str = |e: &Exp| e.infer(&mut types.clone()) == Type::TyStr;
+    match e {
+        Exp::Var { name: x } => name(x),
+        Exp::Num { val } if *val < 0 => format!("(- {})", -(*val as i64)),
+        Exp::Num { val } => val.to_string(),
+        Exp::Bool { val } => val.to_string(),
+        Exp::Str { val } => show_str(val),
+        Exp::Plus { left, right } if is_str(left) => bin("str.++", left, right),
+        Exp::Plus { left, right } => bin("+", left, right),
+        Exp::Mult { left, right } => bin("*", left, right),
+        Exp::And { left, right } => bin("and", left, right),
+        Exp::Or { left, right } => bin("or", left, right),
+        Exp::Equ { left, right } => bin("=", left, right),
+        Exp::Less { left, right } if is_str(left) => bin("str.<", left, right),
+        Exp::Less { left, right } => bin("<", left, right),
+        Exp::Neg { exp } => format!("(not {})", term(exp, types, name)),
+        Exp::Grp { exp } => term(exp, types, name),
+        Exp::Call { name: f, args } if matches!(types.get(f), Some(Type::TyFun(..))) => {
+            let f = name(f);
+            let args: Vec<String> = args.iter().map(|a| term(a, types, name)).collect();
+            format!("(select {} {})", f, args.join(" "))
+        }
+        Exp::Call { name: f, args } if args.is_empty() => proc_symbol(f),
+        Exp::Call { name: f, args } => {
+            let args: Vec<String> = args.iter().map(|a| term(a, types, name)).collect();
+            format!("({} {})", proc_symbol(f), args.join(" "))
+        }
+        Exp::Lambda { param, ty, body } => {
+            let p = format!("{}_arg", param);
+            let mut inner = types.clone();
+            inner.insert(param.clone(), ty.clone());
+            let body = term(body, &inner, &mut |x| {
+                if x == param {
+                    p.clone()
+                } else {
+                    name(x)
+                }
+            });
+            format!("(lambda (({} {})) {})", p, show_sort(ty), body)
+        }
+        Exp::Array { elems } if elems.len() == 1 => {
+            format!("(seq.unit {})", term(&elems[0], types, name))
+        }
+        Exp::Array { elems } => {
+            let elems: Vec<String> = elems
+                .iter()
+                .map(|e| format!("(seq.unit {})", term(e, types, name)))
+                .collect();
+            format!("(seq.++ {})", elems.join(" "))
+        }
+        Exp::Index { array, index } => bin("seq.nth", array, index),
+        Exp::Update {
+            array,
+            index,
+            value,
+        } => {
+            let a = term(array, types, name);
+            let i = term(index, types, name);
+            format!(
+                "(seq.update {} {} (seq.unit {}))",
+                a,
+                i,
+                term(value, types, name)
+            )
+        }
+        Exp::Len { exp } if is_str(exp) => format!("(str.len {})", term(exp, types, name)),
+        Exp::Len { exp } => format!("(seq.len {})", term(exp, types, name)),
+        // the arguments of the constructor in the order of the declaration
+        Exp::Record { name: r, fields } => {
+            let order: Vec<String> = match types.fields(r) {
+                Some(decl) => decl.iter().map(|(x, _)| x.clone()).collect(),
+                None => fields.iter().map(|(x, _)| x.clone()).collect(),
+            };
+            let args: Vec<String> = order
+                .iter()
+                .filter_map(|x| fields.iter().find(|(y, _)| y == x))
+                .map(|(_, e)| term(e, types, name))
+                .collect();
+            format!("(mk.{} {})", r, args.join(" "))
+        }
+        Exp::Field { exp, field } => {
+            let r = record_name(exp, types);
+            format!("({}.{} {})", r, field, term(exp, types, name))
+        }
+        Exp::With { exp, field, value } => {
+            let r = record_name(exp, types);
+            let t = term(exp, types, name);
+            let args: Vec<String> = match types.fields(&r) {
+                Some(decl) => decl
+                    .clone()
+                    .iter()
+                    .map(|(x, _)| match x == field {
+                        true => term(value, types, name),
+                        false => format!("({}.{} {})", r, x, t),
+                    })
+                    .collect(),
+                None => vec![],
+            };
+            format!("(mk.{} {})", r, args.join(" "))
+        }
+    }
+}
+
+// Name of the record type of e, which has to be known
+fn record_name(e: &Exp, types: &TyState) -> String {
+    match type_of(e, types) {
+        Some(Type::TyRecord(r)) => r,
+        _ => "unknown".to_string(),
+    }
+}
+
+// The record types as one declaration, so they may refer to each other
+fn declare_records(records: &[Record]) -> String {
+    let sorts: Vec<String> = records.iter().map(|r| format!("({} 0)", r.name)).collect();
+    let ctors: Vec<String> = records
+        .iter()
+        .map(|r| {
+            let sels: Vec<String> = r
+                .fields
+                .iter()
+                .map(|(x, ty)| format!(" ({}.{} {})", r.name, x, show_sort(ty)))
+                .collect();
+            format!("((mk.{}{}))", r.name, sels.concat())
+        })
+        .collect();
+    format!(
+        "(declare-datatypes ({}) ({}))",
+        sorts.join(" "),
+        ctors.join(" ")
+    )
+}
+
+// Procedures are uninterpreted functions; they have no side effects on the
+// variables, so a call only depends on its arguments
+fn proc_symbol(f: &str) -> String {
+    format!("{}_proc", f)
+}
+
+fn called(e: &Exp, out: &mut BTreeSet<String>) {
+    match e {
+        Exp::Call { name, args } => {
+            out.insert(name.clone());
+            for a in args {
+                called(a, out);
+            }
+        }
+        Exp::Plus { left, right }
+        | Exp::Mult { left, right }
+        | Exp::And { left, right }
+        | Exp::Or { left, right }
+        | Exp::Equ { left, right }
+        | Exp::Less { left, right } => {
+            called(left, out);
+            called(right, out);
+        }
+        Exp::Neg { exp } | Exp::Grp { exp } | Exp::Len { exp } | Exp::Field { exp, .. } => {
+            called(exp, out)
+        }
+        Exp::Lambda { body, .. } => called(body, out),
+        Exp::Array { elems } => {
+            for e in elems {
+                called(e, out);
+            }
+        }
+        Exp::Index { array, index } => {
+            called(array, out);
+            called(index, out);
+        }
+        Exp::Update {
+            array,
+            index,
+            value,
+        } => {
+            called(array, out);
+            called(index, out);
+            called(value, out);
+        }
+        Exp::Record { fields, .. } => {
+            for (_, e) in fields {
+                called(e, out);
+            }
+        }
+        Exp::With { exp, value, .. } => {
+            called(exp, out);
+            called(value, out);
+        }
+        Exp::Var { .. } | Exp::Num { .. } | Exp::Bool { .. } | Exp::Str { .. } => {}
+    }
+}
+
+fn nonlinear(e: &Exp) -> bool {
+    match e {
+        Exp::Mult { left, right } => {
+            (!left.vars().is_empty() && !right.vars().is_empty())
+                || nonlinear(left)
+                || nonlinear(right)
+        }
+        Exp::Plus { left, right }
+        | Exp::And { left, right }
+        | Exp::Or { left, right }
+        | Exp::Equ { left, right }
+        | Exp::Less { left, right } => nonlinear(left) || nonlinear(right),
+        Exp::Neg { exp } | Exp::Grp { exp } | Exp::Len { exp } | Exp::Field { exp, .. } => {
+            nonlinear(exp)
+        }
+        Exp::Record { fields, .. } => fields.iter().any(|(_, e)| nonlinear(e)),
+        Exp::With { exp, value, .. } => nonlinear(exp) || nonlinear(value),
+        Exp::Array { elems } => elems.iter().any(nonlinear),
+        Exp::Index { array, index } => nonlinear(array) || nonlinear(index),
+        Exp::Update {
+            array,
+            index,
+            value,
+        } => nonlinear(array) || nonlinear(index) || nonlinear(value),
+        _ => false,
+    }
+}
+
+struct Encoder {
+    unroll: usize,
+    // type of each source variable
+    types: TyState,
+    // current SSA symbol of each source variable
+    current: HashMap<String, String>,
+    // number of versions defined so far for each source variable
+    versions: HashMap<String, usize>,
+    fresh: usize,
+    inputs: Vec<String>,
+    decls: Vec<String>,
+    defs: Vec<String>,
+    asserts: Vec<String>,
+    checks: Vec<String>,
+    nonlinear: bool,
+    // procedures called somewhere
+    called: BTreeSet<String>,
+    // guard and versions at every break and continue of the enclosing loops
+    loops: Vec<Jumps>,
+}
+
+#[derive(Default)]
+struct Jumps {
+    breaks: Vec<(String, HashMap<String, String>)>,
+    continues: Vec<(String, HashMap<String, String>)>,
+}
+
+impl Encoder {
+    fn symbol(&mut self, x: &str) -> String {
+        if let Some(s) = self.current.get(x) {
+            return s.clone();
+        }
+        // an input
+        let s = format!("{}_0", x);
+        if !self.inputs.contains(&s) {
+            let ty = self.types.entry(x.to_string()).or_insert(Type::TyInt);
+            let decl = format!("(declare-const {} {})", s, show_sort(ty));
+            self.decls.push(decl);
+            self.inputs.push(s.clone());
+        }
+        self.versions.entry(x.to_string()).or_insert(0);
+        self.current.insert(x.to_string(), s.clone());
+        s
+    }
+    fn term(&mut self, e: &Exp) -> String {
+        self.nonlinear |= nonlinear(e);
+        called(e, &mut self.called);
+        let types = self.types.clone();
+        term(e, &types, &mut |x| self.symbol(x))
+    }
+    fn define(&mut self, x: &str, ty: Type, t: String) -> String {
+        let n = self.versions.get(x).map_or(1, |n| n + 1);
+        self.versions.insert(x.to_string(), n);
+        let s = format!("{}_{}", x, n);
+        self.defs
+            .push(format!("(define-fun {} () {} {})", s, show_sort(&ty), t));
+        self.current.insert(x.to_string(), s.clone());
+        s
+    }
+    // A new version of x which, like an input, is left unconstrained
+    fn declare(&mut self, x: &str, ty: Type) -> String {
+        let n = self.versions.get(x).map_or(1, |n| n + 1);
+        self.versions.insert(x.to_string(), n);
+        let s = format!("{}_{}", x, n);
+        self.decls
+            .push(format!("(declare-const {} {})", s, show_sort(&ty)));
+        self.current.insert(x.to_string(), s.clone());
+        s
+    }
+    // Defines a fresh bool symbol with the given prefix
+    fn define_bool(&mut self, prefix: &str, t: String) -> String {
+        self.fresh += 1;
+        let s = format!("{}_{}", prefix, self.fresh);
+        self.defs.push(format!("(define-fun {} () Bool {})", s, t));
+        s
+    }
+    fn guard(&mut self, g: &str, c: String) -> String {
+        if g == "true" {
+            return self.define_bool("g", c);
+        }
+        self.define_bool("g", format!("(and {} {})", g, c))
+    }
+    // Merges the versions after a branch on c
+    fn join(&mut self, c: &str, then_vars: HashMap<String, String>) {
+        let else_vars = self.current.clone();
+        let mut xs: Vec<&String> = then_vars.keys().collect();
+        xs.sort();
+        for x in xs {
+            let t = &then_vars[x];
+            match else_vars.get(x) {
+                Some(e) if e != t => {
+                    let ty = self.types[x].clone();
+                    self.define(x, ty, format!("(ite {} {} {})", c, t, e));
+                }
+                Some(_) => {}
+                None => {
+                    self.current.insert(x.clone(), t.clone());
+                }
+            }
+        }
+    }
+    // Encodes s under the guard g and returns the guard of the paths which
+    // reach its end, g unless s has a break or continue
+    fn stmt(&mut self, s: &Stmt, g: &str) -> String {
+        match s {
+            Stmt::Skip => {}
+            Stmt::Seq { first, second } => {
+                let g = self.stmt(first, g);
+                return self.stmt(second, &g);
+            }
+            // the continues in body go on with step
+            Stmt::Step { body, step } => {
+                let outer = self
+                    .loops
+                    .last_mut()
+                    .map(|l| std::mem::take(&mut l.continues));
+                let g_next = self.stmt(body, g);
+                let continues = match self.loops.last_mut() {
+                    Some(l) => std::mem::replace(&mut l.continues, outer.unwrap_or_default()),
+                    None => Vec::new(),
+                };
+                let g_next = self.continues(g_next, continues);
+                return self.stmt(step, &g_next);
+            }
+            Stmt::Break | Stmt::Continue => {
+                let jump = (g.to_string(), self.current.clone());
+                if let Some(l) = self.loops.last_mut() {
+                    match s {
+                        Stmt::Break => l.breaks.push(jump),
+                        _ => l.continues.push(jump),
+                    }
+                }
+                return "false".to_string();
+            }
+            Stmt::Decl { lhs, rhs, .. } => {
+                let t = self.term(rhs);
+                let ty = rhs.infer(&mut self.types);
+                self.types.insert(lhs.clone(), ty.clone());
+                self.define(lhs, ty, t);
+            }
+            Stmt::Assign { lhs, rhs } => {
+                let t = self.term(rhs);
+                let ty = rhs.infer(&mut self.types);
+                self.define(lhs, ty, t);
+            }
+            Stmt::Read { lhs, ty } => {
+                self.types.insert(lhs.clone(), ty.clone());
+                self.declare(lhs, ty.clone());
+            }
+            Stmt::ArrayAssign { lhs, index, rhs } => {
+                let rhs = update(variable(lhs.clone()), index.clone(), rhs.clone());
+                let t = self.term(&rhs);
+                let ty = self.types.get(lhs).cloned().unwrap_or(Type::TyIllTyped);
+                self.define(lhs, ty, t);
+            }
+            Stmt::FieldAssign { lhs, field, rhs } => {
+                let rhs = with(variable(lhs.clone()), field.clone(), rhs.clone());
+                let t = self.term(&rhs);
+                let ty = self.types.get(lhs).cloned().unwrap_or(Type::TyIllTyped);
+                self.define(lhs, ty, t);
+            }
+            Stmt::Print { print_exp } => {
+                let mut check = vec![format!("; {}", s.pretty())];
+                check.push("(push 1)".to_string());
+                check.push(format!("(assert {})", g));
+                check.push("(check-sat)".to_string());
+                check.push("(get-model)".to_string());
+                check.push("(pop 1)".to_string());
+                self.checks.push(check.join("\n"));
+                self.term(print_exp);
+            }
+            // only well-typed in procedure bodies, which are not encoded
+            Stmt::Return { exp } => {
+                self.term(exp);
+            }
+            Stmt::Assume { cond } => {
+                let c = self.term(cond);
+                self.asserts.push(format!("(assert (=> {} {}))", g, c));
+            }
+            Stmt::Assert { cond } => {
+                // sat means the assertion can fail
+                let c = self.term(cond);
+                let mut check = vec![format!("; {}", s.pretty())];
+                check.push("(push 1)".to_string());
+                check.push(format!("(assert (and {} (not {})))", g, c));
+                check.push("(check-sat)".to_string());
+                check.push("(get-model)".to_string());
+                check.push("(pop 1)".to_string());
+                self.checks.push(check.join("\n"));
+            }
+            Stmt::IfThenElse {
+                cond,
+                then_stmt,
+                else_stmt,
+            } => {
+                let t = self.term(cond);
+                let c = self.define_bool("c", t);
+                let g_then = self.guard(g, c.clone());
+                let g_else = self.guard(g, format!("(not {})", c));
+                let before = self.current.clone();
+                let end_then = self.stmt(then_stmt, &g_then);
+                let then_vars = std::mem::replace(&mut self.current, before);
+                let end_else = self.stmt(else_stmt, &g_else);
+                self.join(&c, then_vars);
+                if end_then != g_then || end_else != g_else {
+                    return self.define_bool("g", format!("(or {} {})", end_then, end_else));
+                }
+            }
+            Stmt::While { cond, stmt, .. } => self.unroll(cond, stmt, g, self.unroll),
+        }
+        g.to_string()
+    }
+    // The guard of the paths which reach g_next or one of the continues,
+    // with their variables joined into the current ones
+    fn continues(
+        &mut self,
+        g_next: String,
+        continues: Vec<(String, HashMap<String, String>)>,
+    ) -> String {
+        if continues.is_empty() {
+            return g_next;
+        }
+        let mut gs = vec![g_next];
+        for (g_cont, vars) in continues {
+            self.join(&g_cont, vars);
+            gs.push(g_cont);
+        }
+        self.define_bool("g", format!("(or {})", gs.join(" ")))
+    }
+    fn unroll(&mut self, cond: &Exp, body: &Stmt, g: &str, n: usize) {
+        let t = self.term(cond);
+        let c = self.define_bool("c", t);
+        if n == 0 {
+            self.asserts.push(format!(
+                "; unwinding assumption\n(assert (not (and {} {})))",
+                g, c
+            ));
+            return;
+        }
+        let g_body = self.guard(g, c.clone());
+        let before = self.current.clone();
+        self.loops.push(Jumps::default());
+        let g_next = self.stmt(body, &g_body);
+        let mut jumps = self.loops.pop().unwrap();
+        let g_next = self.continues(g_next, std::mem::take(&mut jumps.continues));
+        self.unroll(cond, body, &g_next, n - 1);
+        let body_vars = std::mem::replace(&mut self.current, before);
+        self.join(&c, body_vars);
+        for (g_break, vars) in jumps.breaks {
+            self.join(&g_break, vars);
+        }
+    }
+}
+
+// Encodes stmt with every While unrolled at most unroll times, calls of
+// procs are uninterpreted functions
+pub fn program(stmt: &Stmt, procs: &[Proc], records: &[Record], unroll: usize) -> String {
+    let mut types = TyState::with_procs(procs).with_records(records);
+    stmt.visit(&mut 0, &mut |_, s| match s {
+        Stmt::While { cond, .. }
+        | Stmt::IfThenElse { cond, .. }
+        | Stmt::Assert { cond }
+        | Stmt::Assume { cond } => guess_types(cond, Some(Type::TyBool), &mut types),
+        Stmt::Decl { rhs, .. } | Stmt::Assign { rhs, .. } => guess_types(rhs, None, &mut types),
+        Stmt::Print { print_exp } => guess_types(print_exp, None, &mut types),
+        Stmt::Return { exp } => guess_types(exp, None, &mut types),
+        Stmt::ArrayAssign { lhs, index, rhs } => {
+            guess_types(index, Some(Type::TyInt), &mut types);
+            guess_types(rhs, None, &mut types);
+            if let (None, Some(ty)) = (types.get(lhs), type_of(rhs, &types)) {
+                types.insert(lhs.clone(), Type::TyArray(Box::new(ty)));
+            }
+        }
+        Stmt::FieldAssign { lhs, field, rhs } => {
+            if !types.contains_key(lhs) {
+                if let Some(ty) = types.owner(field) {
+                    types.insert(lhs.clone(), ty);
+                }
+            }
+            let ty = types.get(lhs).and_then(|ty| types.field(ty, field));
+            guess_types(rhs, ty, &mut types);
+        }
+        Stmt::Read { lhs, ty } => {
+            types.insert(lhs.clone(), ty.clone());
+        }
+        Stmt::Seq { .. } | Stmt::Step { .. } | Stmt::Skip | Stmt::Break | Stmt::Continue => {}
+    });
+    let mut enc = Encoder {
+        unroll,
+        types,
+        current: HashMap::new(),
+        versions: HashMap::new(),
+        fresh: 0,
+        inputs: Vec::new(),
+        decls: Vec::new(),
+        defs: Vec::new(),
+        asserts: Vec::new(),
+        checks: Vec::new(),
+        nonlinear: false,
+        called: BTreeSet::new(),
+        loops: Vec::new(),
+    };
+    enc.stmt(stmt, "true");
+    let mut logic = if enc.nonlinear { "NIA" } else { "LIA" }.to_string();
+    for p in procs.iter().filter(|p| enc.called.contains(&p.name)) {
+        let params: Vec<String> = p.params.iter().map(|(_, ty)| show_sort(ty)).collect();
+        enc.decls.push(format!(
+            "(declare-fun {} ({}) {})",
+            proc_symbol(&p.name),
+            params.join(" "),
+            show_sort(&p.ret)
+        ));
+        logic = format!("UF{}", logic.trim_start_matches("UF"));
+    }
+    let mut body = enc.decls;
+    if !records.is_empty() {
+        body.insert(0, declare_records(records));
+    }
+    body.extend(enc.defs);
+    body.extend(enc.asserts);
+    body.extend(enc.checks);
+    let body = body.join("\n") + "\n";
+    // closures need arrays and lambda, which no quantifier-free logic has,
+    // sequences are not part of any standard logic and strings are left to
+    // the solver as well, as are datatypes combined with arithmetic
+    let strings = body.contains(" String") || body.contains("(str.");
+    let datatypes = body.contains("(declare-datatypes ");
+    if body.contains("(Array ")
+        || body.contains("(lambda ")
+        || body.contains("(Seq ")
+        || strings
+        || datatypes
+    {
+        logic = "ALL".to_string();
+    } else {
+        logic = format!("QF_{}", logic);
+    }
+    let out = [
+        "(set-option :produce-models true)".to_string(),
+        format!("(set-logic {})", logic),
+    ];
+    out.join("\n") + "\n" + &body
+}
@@ -0,0 +1,559 @@
+use super::*;
+use std::collections::BTreeMap;
+
+// A small solver for conjunctions of bool Exp formulas over int and bool
+// variables. Int terms have to be linear: sums of variables multiplied by
+// constants. The boolean structure is explored by case splitting, integer
+// constraints are solved by bounds propagation and branching on the domain
+// of a variable. Variables range over i32.
+
+pub type Model = BTreeMap<String, Val>;
+
+pub enum Outcome {
+    Sat(Model),
+    Unsat,
+    // non-linear constraints or search budget exceeded
+    Unknown,
+}
+
+// sum of coeffs[x] * x + c
+#[derive(Clone, Debug, PartialEq)]
+struct Lin {
+    coeffs: BTreeMap<String, i128>,
+    c: i128,
+}
+
+impl Lin {
+    fn constant(c: i128) -> Lin {
+        Lin {
+            coeffs: BTreeMap::new(),
+            c,
+        }
+    }
+    fn scale(mut self, k: i128) -> Lin {
+        for a in self.coeffs.values_mut() {
+            *a *= k;
+        }
+        self.c *= k;
+        self.coeffs.retain(|_, a| *a != 0);
+        self
+    }
+    fn add(mut self, other: Lin) -> Lin {
+        for (x, a) in other.coeffs {
+            *self.coeffs.entry(x).or_insert(0) += a;
+        }
+        self.c += other.c;
+        self.coeffs.retain(|_, a| *a != 0);
+        self
+    }
+    fn sub(self, other: Lin) -> Lin {
+        self.add(other.scale(-1))
+    }
+}
+
+// Guesses the type of every variable from the context it is used in
+pub fn guess_types(e: &Exp, expected: Option<Type>, out: &mut TyState) {
+    match e {
+        Exp::Var { name } => {
+            if let Some(ty) = expected {
+                out.entry(name.clone()).or_insert(ty);
+            }
+        }
+        Exp::Num { .. } | Exp::Bool { .. } | Exp::Str { .. } => {}
+        Exp::Mult { left, right } => {
+            guess_types(left, Some(Type::TyInt), out);
+            guess_types(right, Some(Type::TyInt), out);
+        }
+        // + and < are on ints unless an operand is a string
+        Exp::Plus { left, right } | Exp::Less { left, right } => {
+            let ty = match type_of(left, out).or_else(|| type_of(right, out)) {
+                Some(Type::TyStr) => Type::TyStr,
+                _ => Type::TyInt,
+            };
+            guess_types(left, Some(ty.clone()), out);
+            guess_types(right, Some(ty), out);
+        }
+        Exp::And { left, right } | Exp::Or { left, right } => {
+            guess_types(left, Some(Type::TyBool), out);
+            guess_types(right, Some(Type::TyBool), out);
+        }
+        Exp::Equ { left, right } => {
+            let ty = type_of(left, out).or_else(|| type_of(right, out));
+            guess_types(left, ty.clone(), out);
+            guess_types(right, ty, out);
+        }
+        Exp::Neg { exp } => guess_types(exp, Some(Type::TyBool), out),
+        Exp::Grp { exp } => guess_types(exp, expected, out),
+        // the signature is unknown here
+        Exp::Call { args, .. } => {
+            for a in args {
+                guess_types(a, None, out);
+            }
+        }
+        // the parameter hides a variable of the same name
+        Exp::Lambda { param, ty, body } => {
+            let mut inner = out.clone();
+            inner.insert(param.clone(), ty.clone());
+            guess_types(body, None, &mut inner);
+            for (x, ty) in inner.iter() {
+                if x != param && !out.contains_key(x) {
+                    out.insert(x.clone(), ty.clone());
+                }
+            }
+        }
+        Exp::Array { elems } => {
+            let ty = match expected {
+                Some(Type::TyArray(ty)) => Some(*ty),
+                _ => elems.iter().find_map(|e| type_of(e, out)),
+            };
+            for e in elems {
+                guess_types(e, ty.clone(), out);
+            }
+        }
+        Exp::Index { array, index } => {
+            let ty = expected.map(|ty| Type::TyArray(Box::new(ty)));
+            guess_types(array, ty, out);
+            guess_types(index, Some(Type::TyInt), out);
+        }
+        Exp::Update {
+            array,
+            index,
+            value,
+        } => {
+            let ty = match (expected, type_of(value, out)) {
+                (Some(ty), _) => Some(ty),
+                (None, ty) => ty.map(|ty| Type::TyArray(Box::new(ty))),
+            };
+            let elem = match &ty {
+                Some(Type::TyArray(ty)) => Some((**ty).clone()),
+                _ => None,
+            };
+            guess_types(array, ty, out);
+            guess_types(index, Some(Type::TyInt), out);
+            guess_types(value, elem, out);
+        }
+        // the element type is unknown here
+        Exp::Len { exp } => guess_types(exp, None, out),
+        Exp::Record { name, fields } => {
+            for (x, e) in fields {
+                let ty = out.field(&Type::TyRecord(name.clone()), x);
+                guess_types(e, ty, out);
+            }
+        }
+        // a record of unknown type is one with the field
+        Exp::Field { exp, field } => {
+            let ty = type_of(exp, out).or_else(|| out.owner(field));
+            guess_types(exp, ty, out);
+        }
+        Exp::With { exp, field, value } => {
+            let ty = expected
+                .or_else(|| type_of(exp, out))
+                .or_else(|| out.owner(field));
+            let f = ty.as_ref().and_then(|ty| out.field(ty, field));
+            guess_types(exp, ty, out);
+            guess_types(value, f, out);
+        }
+    }
+}
+
+pub fn type_of(e: &Exp, vars: &TyState) -> Option<Type> {
+    match e {
+        Exp::Var { name } => vars.get(name).cloned(),
+        Exp::Num { .. } | Exp::Mult { .. } => Some(Type::TyInt),
+        Exp::Str { .. } => Some(Type::TyStr),
+        Exp::Plus { left, right } => type_of(left, vars)
+            .or_else(|| type_of(right, vars))
+            .or(Some(Type::TyInt)),
+        Exp::Grp { exp } => type_of(exp, vars),
+        Exp::Call { name, .. } => match vars.get(name) {
+            Some(Type::TyFun(_, ret)) => Some((**ret).clone()),
+            _ => None,
+        },
+        Exp::Lambda { param, ty, body } => {
+            let mut inner = vars.clone();
+            inner.insert(param.clone(), ty.clone());
+            Some(Type::TyFun(
+                Box::new(ty.clone()),
+                Box::new(type_of(body, &inner)?),
+            ))
+        }
+        Exp::Len { .. } => Some(Type::TyInt),
+        Exp::Array { elems } => Some(Type::TyArray(Box::new(type_of(elems.first()?, vars)?))),
+        Exp::Index { array, .. } => match type_of(array, vars)? {
+            Type::TyArray(ty) => Some(*ty),
+            _ => None,
+        },
+        Exp::Update { array, value, .. } => {
+            type_of(array, vars).or_else(|| Some(Type::TyArray(Box::new(type_of(value, vars)?))))
+        }
+        Exp::Record { name, .. } => Some(Type::TyRecord(name.clone())),
+        Exp::Field { exp, field } => vars.field(&type_of(exp, vars)?, field),
+        Exp::With { exp, .. } => type_of(exp, vars),
+        _ => Some(Type::TyBool),
+    }
+}
+
+fn linear(e: &Exp) -> Option<Lin> {
+    match e {
+        Exp::Var { name } => {
+            let mut l = Lin::constant(0);
+            l.coeffs.insert(name.clone(), 1);
+            Some(l)
+        }
+        Exp::Num { val } => Some(Lin::constant(*val as i128)),
+        Exp::Plus { left, right } => Some(linear(left)?.add(linear(right)?)),
+        Exp::Mult { left, right } => {
+            let l1 = linear(left)?;
+            let l2 = linear(right)?;
+            if l1.coeffs.is_empty() {
+                return Some(l2.scale(l1.c));
+            }
+            if l2.coeffs.is_empty() {
+                return Some(l1.scale(l2.c));
+            }
+            None
+        }
+        Exp::Grp { exp } => linear(exp),
+        _ => None,
+    }
+}
+
+fn div_floor(a: i128, b: i128) -> i128 {
+    let q = a / b;
+    if (a % b != 0) && ((a < 0) != (b < 0)) {
+        q - 1
+    } else {
+        q
+    }
+}
+
+fn div_ceil(a: i128, b: i128) -> i128 {
+    -div_floor(-a, b)
+}
+
+#[derive(Clone)]
+struct Search {
+    // constraints lin <= 0
+    le: Vec<Lin>,
+    bools: BTreeMap<String, bool>,
+}
+
+struct Solver {
+    types: TyState,
+    budget: usize,
+    unknown: bool,
+}
+
+type Domains = BTreeMap<String, (i128, i128)>;
+
+// deepest nesting of case splits on integer domains
+const MAX_DEPTH: usize = 256;
+
+impl Solver {
+    fn tick(&mut self) -> bool {
+        if self.budget == 0 {
+            self.unknown = true;
+            return false;
+        }
+        self.budget -= 1;
+        true
+    }
+    // Expands the formulas in goals; each goal is a formula and the truth
+    // value it has to take.
+    fn goals(&mut self, mut goals: Vec<(&Exp, bool)>, mut st: Search) -> Option<Model> {
+        if !self.tick() {
+            return None;
+        }
+        let (e, pos) = match goals.pop() {
+            Some(g) => g,
+            None => return self.ints(st),
+        };
+        match e {
+            Exp::Grp { exp } => {
+                goals.push((exp, pos));
+                self.goals(goals, st)
+            }
+            Exp::Neg { exp } => {
+                goals.push((exp, !pos));
+                self.goals(goals, st)
+            }
+            Exp::Bool { val } => {
+                if *val != pos {
+                    return None;
+                }
+                self.goals(goals, st)
+            }
+            // a variable of unknown type is only compared with others of
+            // unknown type, such comparisons are on bools
+            Exp::Var { name } if matches!(self.types.get(name), Some(Type::TyBool) | None) => {
+                match st.bools.get(name) {
+                    Some(b) if *b != pos => return None,
+                    _ => {
+                        st.bools.insert(name.clone(), pos);
+                    }
+                }
+                self.goals(goals, st)
+            }
+            Exp::And { left, right } | Exp::Or { left, right } => {
+                let conj = matches!(e, Exp::And { .. }) == pos;
+                if conj {
+                    goals.push((left, pos));
+                    goals.push((right, pos));
+                    return self.goals(goals, st);
+                }
+                let mut other = goals.clone();
+                goals.push((left, pos));
+                if let Some(m) = self.goals(goals, st.clone()) {
+                    return Some(m);
+                }
+                other.push((right, pos));
+                self.goals(other, st)
+            }
+            // strings are not supported
+            Exp::Less { left, .. } if type_of(left, &self.types) == Some(Type::TyStr) => {
+                self.unknown = true;
+                None
+            }
+            Exp::Less { left, right } => {
+                let (l, r) = match (linear(left), linear(right)) {
+                    (Some(l), Some(r)) => (l, r),
+                    _ => {
+                        self.unknown = true;
+                        return None;
+                    }
+                };
+                if pos {
+                    st.le.push(l.sub(r).add(Lin::constant(1)));
+                } else {
+                    st.le.push(r.sub(l));
+                }
+                self.goals(goals, st)
+            }
+            Exp::Equ { left, right } if type_of(left, &self.types) == Some(Type::TyInt) => {
+                let (l, r) = match (linear(left), linear(right)) {
+                    (Some(l), Some(r)) => (l, r),
+                    _ => {
+                        self.unknown = true;
+                        return None;
+                    }
+                };
+                let d = l.sub(r);
+                if pos {
+                    st.le.push(d.clone());
+                    st.le.push(d.scale(-1));
+                    return self.goals(goals, st);
+                }
+                // l != r is l < r || r < l
+                let mut other = st.clone();
+                other.le.push(d.clone().scale(-1).add(Lin::constant(1)));
+                st.le.push(d.add(Lin::constant(1)));
+                if let Some(m) = self.goals(goals.clone(), st) {
+                    return Some(m);
+                }
+                self.goals(goals, other)
+            }
+            Exp::Equ { left, right }
+                if matches!(type_of(left, &self.types), Some(Type::TyBool) | None) =>
+            {
+                // l == r is (l && r) || (!l && !r)
+                let mut other = goals.clone();
+                goals.push((left, true));
+                goals.push((right, pos));
+                if let Some(m) = self.goals(goals, st.clone()) {
+                    return Some(m);
+                }
+                other.push((left, false));
+                other.push((right, !pos));
+                self.goals(other, st)
+            }
+            _ => {
+                self.unknown = true;
+                None
+            }
+        }
+    }
+    // Solves the integer constraints once the boolean structure is fixed
+    fn ints(&mut self, st: Search) -> Option<Model> {
+        let mut dom = Domains::new();
+        for l in &st.le {
+            for x in l.coeffs.keys() {
+                dom.insert(x.clone(), (i32::MIN as i128, i32::MAX as i128));
+            }
+        }
+        if !eliminate(&st.le) {
+            return None;
+        }
+        let mut model = self.branch(&st.le, dom, 0)?;
+        for (x, b) in st.bools {
+            model.insert(x, Val::mk_bool(&b));
+        }
+        Some(model)
+    }
+    fn branch(&mut self, le: &[Lin], mut dom: Domains, depth: usize) -> Option<Model> {
+        if depth > MAX_DEPTH {
+            self.unknown = true;
+            return None;
+        }
+        if !self.tick() || !propagate(le, &mut dom) {
+            return None;
+        }
+        let open = dom
+            .iter()
+            .filter(|(_, (lo, hi))| lo < hi)
+            .min_by_key(|(_, (lo, hi))| hi - lo)
+            .map(|(x, d)| (x.clone(), *d));
+        let (x, (lo, hi)) = match open {
+            Some(o) => o,
+            None => {
+                return Some(
+                    dom.iter()
+                        .map(|(x, (v, _))| (x.clone(), Val::mk_int(&(*v as i32))))
+                        .collect(),
+                );
+            }
+        };
+        // values close to zero first
+        let v = 0.clamp(lo, hi);
+        let mut tries = vec![(v, v)];
+        if lo < v {
+            tries.push((lo, v - 1));
+        }
+        if v < hi {
+            tries.push((v + 1, hi));
+        }
+        for d in tries {
+            let mut dom = dom.clone();
+            dom.insert(x.clone(), d);
+            if let Some(m) = self.branch(le, dom, depth + 1) {
+                return Some(m);
+            }
+        }
+        None
+    }
+}
+
+fn gcd(a: i128, b: i128) -> i128 {
+    if b == 0 {
+        a.abs()
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+// Divides l <= 0 by the gcd of its coefficients, rounding the constant up;
+// this keeps all integer solutions
+fn normalize(l: Lin) -> Lin {
+    let g = l.coeffs.values().fold(0, |g, a| gcd(g, *a));
+    if g <= 1 {
+        return l;
+    }
+    Lin {
+        coeffs: l.coeffs.into_iter().map(|(x, a)| (x, a / g)).collect(),
+        c: div_ceil(l.c, g),
+    }
+}
+
+// Fourier-Motzkin elimination of all variables; false if the constraints
+// have no solution. Gives up (true) when the system grows too large.
+fn eliminate(le: &[Lin]) -> bool {
+    let mut cs: Vec<Lin> = le.iter().cloned().map(normalize).collect();
+    loop {
+        if cs.iter().any(|l| l.coeffs.is_empty() && l.c > 0) {
+            return false;
+        }
+        cs.retain(|l| !l.coeffs.is_empty());
+        let count = |x: &String, cs: &[Lin]| {
+            let pos = cs
+                .iter()
+                .filter(|l| l.coeffs.get(x).is_some_and(|a| *a > 0));
+            let neg = cs
+                .iter()
+                .filter(|l| l.coeffs.get(x).is_some_and(|a| *a < 0));
+            pos.count() * neg.count()
+        };
+        let x = match cs
+            .iter()
+            .flat_map(|l| l.coeffs.keys())
+            .min_by_key(|x| count(x, &cs))
+        {
+            Some(x) => x.clone(),
+            None => return true,
+        };
+        if count(&x, &cs) > 256 {
+            return true;
+        }
+        let (with, mut rest): (Vec<Lin>, Vec<Lin>) =
+            cs.into_iter().partition(|l| l.coeffs.contains_key(&x));
+        for p in with.iter().filter(|l| l.coeffs[&x] > 0) {
+            for n in with.iter().filter(|l| l.coeffs[&x] < 0) {
+                let (a, b) = (p.coeffs[&x], -n.coeffs[&x]);
+                let l = normalize(p.clone().scale(b).add(n.clone().scale(a)));
+                if l.c.abs() > 1 << 64 || l.coeffs.values().any(|a| a.abs() > 1 << 64) {
+                    return true;
+                }
+                rest.push(l);
+            }
+        }
+        cs = rest;
+    }
+}
+
+// Tightens the domains with the constraints; false if one becomes empty
+fn propagate(le: &[Lin], dom: &mut Domains) -> bool {
+    for _ in 0..64 {
+        let mut changed = false;
+        for l in le {
+            let min = |x: &String, a: i128, dom: &Domains| {
+                let (lo, hi) = dom[x];
+                (a * lo).min(a * hi)
+            };
+            let total: i128 = l.c + l.coeffs.iter().map(|(x, a)| min(x, *a, dom)).sum::<i128>();
+            if total > 0 {
+                return false;
+            }
+            for (x, a) in &l.coeffs {
+                // a * x <= -(total - min(a * x))
+                let bound = -(total - min(x, *a, dom));
+                let (lo, hi) = dom[x];
+                let (lo2, hi2) = if *a > 0 {
+                    (lo, hi.min(div_floor(bound, *a)))
+                } else {
+                    (lo.max(div_ceil(bound, *a)), hi)
+                };
+                if lo2 > hi2 {
+                    return false;
+                }
+                if (lo2, hi2) != (lo, hi) {
+                    dom.insert(x.clone(), (lo2, hi2));
+                    changed = true;
+                }
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+    true
+}
+
+pub fn solve(formulas: &[&Exp]) -> Outcome {
+    let mut vars = TyState::new();
+    for e in formulas {
+        guess_types(e, Some(Type::TyBool), &mut vars);
+    }
+    let mut solver = Solver {
+        types: vars,
+        budget: 100_000,
+        unknown: false,
+    };
+    let search = Search {
+        le: Vec::new(),
+        bools: BTreeMap::new(),
+    };
+    let goals = formulas.iter().map(|e| (*e, true)).collect();
+    match solver.goals(goals, search) {
+        Some(m) => Outcome::Sat(m),
+        None if solver.unknown => Outcome::Unknown,
+        None => Outcome::Unsat,
+    }
+}
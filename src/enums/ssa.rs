@@ -0,0 +1,234 @@
+use super::ir::*;
+use std::collections::{HashMap, HashSet};
+
+// Conversion of the IR into SSA form, in which every variable is assigned
+// once. Phis are placed in the iterated dominance frontiers of the blocks
+// assigning a variable and the uses are renamed along the dominator tree
+// (Cytron et al.). The dominators are computed with the iterative
+// algorithm of Cooper, Harvey and Kennedy.
+//
+// The versions of x are x.1, x.2, .., parameters start as version 1. A use
+// which no assignment reaches keeps the name x. Temporaries are assigned
+// once already and keep their names.
+
+pub fn module(m: &Module) -> Module {
+    Module {
+        funcs: m.funcs.iter().map(function).collect(),
+    }
+}
+
+pub fn function(f: &Function) -> Function {
+    let mut f = f.clone();
+    let preds = f.preds();
+    let idom = dominators(&f, &preds);
+    let df = frontiers(&preds, &idom);
+    let phis = place(&mut f, &preds, &df);
+    let mut children = vec![Vec::new(); f.blocks.len()];
+    for b in 1..f.blocks.len() {
+        children[idom[b]].push(b);
+    }
+    let mut r = Rename {
+        versions: HashMap::new(),
+        stacks: HashMap::new(),
+        children,
+        phis,
+    };
+    f.params = f.params.iter().map(|x| r.define(x)).collect();
+    r.block(&mut f, 0);
+    f
+}
+
+// The immediate dominator of every block, the entry is its own
+fn dominators(f: &Function, preds: &[Vec<usize>]) -> Vec<usize> {
+    let order = postorder(f);
+    let mut number = vec![0; f.blocks.len()];
+    for (k, b) in order.iter().enumerate() {
+        number[*b] = k;
+    }
+    let mut idom: Vec<Option<usize>> = vec![None; f.blocks.len()];
+    idom[0] = Some(0);
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for &b in order.iter().rev().skip(1) {
+            let mut new: Option<usize> = None;
+            for &p in &preds[b] {
+                if idom[p].is_none() {
+                    continue;
+                }
+                new = Some(match new {
+                    None => p,
+                    Some(mut a) => {
+                        let mut p = p;
+                        while a != p {
+                            while number[a] < number[p] {
+                                a = idom[a].unwrap();
+                            }
+                            while number[p] < number[a] {
+                                p = idom[p].unwrap();
+                            }
+                        }
+                        a
+                    }
+                });
+            }
+            if new.is_some() && idom[b] != new {
+                idom[b] = new;
+                changed = true;
+            }
+        }
+    }
+    idom.into_iter().map(|d| d.unwrap()).collect()
+}
+
+fn postorder(f: &Function) -> Vec<usize> {
+    let mut order = Vec::new();
+    let mut seen = vec![false; f.blocks.len()];
+    // blocks with the index of the next successor to visit
+    let mut stack = vec![(0, 0)];
+    seen[0] = true;
+    while let Some((b, k)) = stack.pop() {
+        let succs = f.blocks[b].term.succs();
+        if k < succs.len() {
+            stack.push((b, k + 1));
+            if !seen[succs[k]] {
+                seen[succs[k]] = true;
+                stack.push((succs[k], 0));
+            }
+        } else {
+            order.push(b);
+        }
+    }
+    order
+}
+
+// The dominance frontier of every block
+fn frontiers(preds: &[Vec<usize>], idom: &[usize]) -> Vec<HashSet<usize>> {
+    let mut df = vec![HashSet::new(); preds.len()];
+    for (b, ps) in preds.iter().enumerate() {
+        if ps.len() < 2 {
+            continue;
+        }
+        for &p in ps {
+            let mut runner = p;
+            while runner != idom[b] {
+                df[runner].insert(b);
+                runner = idom[runner];
+            }
+        }
+    }
+    df
+}
+
+// Inserts the phis at the start of the blocks and returns the variables
+// they are for, in the same order
+fn place(f: &mut Function, preds: &[Vec<usize>], df: &[HashSet<usize>]) -> Vec<Vec<String>> {
+    let mut defs: Vec<(String, Vec<usize>)> = Vec::new();
+    let mut define = |x: &String, b: usize| match defs.iter_mut().find(|(y, _)| y == x) {
+        Some((_, bs)) => bs.push(b),
+        None => defs.push((x.clone(), vec![b])),
+    };
+    for x in &f.params {
+        define(x, 0);
+    }
+    for (b, block) in f.blocks.iter().enumerate() {
+        for i in &block.instrs {
+            if let Some(x) = i.dst().filter(|x| !x.starts_with('%')) {
+                define(x, b);
+            }
+        }
+    }
+    let mut phis = vec![Vec::new(); f.blocks.len()];
+    for (x, mut todo) in defs {
+        let mut placed = HashSet::new();
+        while let Some(b) = todo.pop() {
+            let mut frontier: Vec<usize> = df[b].iter().copied().collect();
+            frontier.sort();
+            for d in frontier {
+                if placed.insert(d) {
+                    phis[d].push(x.clone());
+                    todo.push(d);
+                }
+            }
+        }
+    }
+    for (b, xs) in phis.iter_mut().enumerate() {
+        xs.sort();
+        let instrs = xs.iter().map(|x| Instr::Phi {
+            dst: x.clone(),
+            args: preds[b]
+                .iter()
+                .map(|p| (*p, Operand::Var(x.clone())))
+                .collect(),
+        });
+        f.blocks[b].instrs.splice(0..0, instrs);
+    }
+    phis
+}
+
+struct Rename {
+    // the last version of every variable
+    versions: HashMap<String, usize>,
+    // the versions visible in the current block
+    stacks: HashMap<String, Vec<String>>,
+    children: Vec<Vec<usize>>,
+    phis: Vec<Vec<String>>,
+}
+
+impl Rename {
+    fn define(&mut self, x: &str) -> String {
+        let n = self.versions.entry(x.to_string()).or_insert(0);
+        *n += 1;
+        let name = format!("{}.{}", x, n);
+        self.stacks
+            .entry(x.to_string())
+            .or_default()
+            .push(name.clone());
+        name
+    }
+    fn use_of(&self, x: &mut Operand) {
+        if let Operand::Var(y) = x {
+            if let Some(name) = self.stacks.get(y).and_then(|s| s.last()) {
+                *y = name.clone();
+            }
+        }
+    }
+    fn block(&mut self, f: &mut Function, b: usize) {
+        let mut defined = Vec::new();
+        let n = self.phis[b].len();
+        for (k, i) in f.blocks[b].instrs.iter_mut().enumerate() {
+            if k >= n {
+                for x in i.operands_mut() {
+                    self.use_of(x);
+                }
+            }
+            let x = match i.dst_mut() {
+                Some(x) if !x.starts_with('%') => x,
+                _ => continue,
+            };
+            defined.push(x.clone());
+            *x = self.define(x);
+        }
+        for x in f.blocks[b].term.operands_mut() {
+            self.use_of(x);
+        }
+        for s in f.blocks[b].term.succs() {
+            for k in 0..self.phis[s].len() {
+                let name = self.stacks.get(&self.phis[s][k]).and_then(|s| s.last());
+                if let Some(Instr::Phi { args, .. }) = f.blocks[s].instrs.get_mut(k) {
+                    for (p, x) in args.iter_mut() {
+                        if let (true, Some(name), Operand::Var(y)) = (*p == b, name, x) {
+                            *y = name.clone();
+                        }
+                    }
+                }
+            }
+        }
+        for c in self.children[b].clone() {
+            self.block(f, c);
+        }
+        for x in defined {
+            self.stacks.get_mut(&x).unwrap().pop();
+        }
+    }
+}
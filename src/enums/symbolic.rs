@@ -0,0 +1,385 @@
+use super::solver::{solve, Model, Outcome};
+use super::*;
+use std::collections::BTreeMap;
+
+// Symbolic execution of Stmt. Every variable holds an Exp over the inputs
+// of the program, the variables which are read before they are declared.
+// IfThenElse forks the current path, While is unrolled at most a given
+// number of times. Paths whose condition is unsatisfiable are dropped.
+// Paths taking a break or continue wait at their loop until the iteration
+// is over. Every Read gives its variable a new input x@k.
+
+pub type SymState = HashMap<String, Box<Exp>>;
+
+#[derive(Clone)]
+pub struct Path {
+    pub state: SymState,
+    // conditions taken on the way, all of them hold
+    pub cond: Vec<Box<Exp>>,
+    // indices of the Print statements executed on the path
+    pub prints: Vec<usize>,
+}
+
+pub struct Engine {
+    pub unroll: usize,
+    // paths that ran to the end of the program
+    pub paths: Vec<Path>,
+    // paths given up because a loop ran more than unroll times
+    pub cut: usize,
+    // for each Print statement the conditions of the paths reaching it
+    pub reached: BTreeMap<usize, Vec<Vec<Box<Exp>>>>,
+    // for each Assert statement the conditions of the paths violating it
+    pub failed: BTreeMap<usize, Vec<Vec<Box<Exp>>>>,
+    // paths at a break and at a continue of the enclosing loops
+    jumps: Vec<(Vec<Path>, Vec<Path>)>,
+    // number of inputs made up for Read statements
+    reads: usize,
+}
+
+// Replaces the variables of e by their symbolic values. Closed
+// subexpressions are evaluated, those which overflow stay as they are.
+fn subst(e: &Exp, s: &SymState) -> Box<Exp> {
+    let e = match e {
+        Exp::Var { name } => match s.get(name) {
+            Some(v) => return v.clone(),
+            None => return variable(name.clone()),
+        },
+        Exp::Num { val } => return number(*val),
+        Exp::Bool { val } => return boolean(*val),
+        Exp::Str { val } => return string(val.clone()),
+        Exp::Plus { left, right } => plus(subst(left, s), subst(right, s)),
+        Exp::Mult { left, right } => mult(subst(left, s), subst(right, s)),
+        Exp::And { left, right } => and(subst(left, s), subst(right, s)),
+        Exp::Or { left, right } => or(subst(left, s), subst(right, s)),
+        Exp::Equ { left, right } => equal(subst(left, s), subst(right, s)),
+        Exp::Less { left, right } => less(subst(left, s), subst(right, s)),
+        Exp::Neg { exp } => not(subst(exp, s)),
+        Exp::Grp { exp } => group(subst(exp, s)),
+        // a closure whose lambda is known is applied right away
+        Exp::Call { name, args } => match (s.get(name).map(|f| &**f), args.as_slice()) {
+            (Some(Exp::Lambda { param, body, .. }), [a]) => {
+                let arg = SymState::from([(param.clone(), subst(a, s))]);
+                return subst(body, &arg);
+            }
+            (Some(Exp::Var { name: f }), _) => {
+                call(f.clone(), args.iter().map(|a| *subst(a, s)).collect())
+            }
+            _ => call(name.clone(), args.iter().map(|a| *subst(a, s)).collect()),
+        },
+        // the parameter is renamed if it would capture a variable of a value
+        Exp::Lambda { param, ty, body } => {
+            let captures = body
+                .vars()
+                .iter()
+                .any(|x| x != param && s.get(x).is_some_and(|v| v.vars().contains(param)));
+            let p = if captures {
+                format!("{}'", param)
+            } else {
+                param.clone()
+            };
+            let mut inner = s.clone();
+            inner.insert(param.clone(), variable(p.clone()));
+            lambda(p, ty.clone(), subst(body, &inner))
+        }
+        Exp::Array { elems } => array(elems.iter().map(|e| *subst(e, s)).collect()),
+        // elements of a literal at a known position are picked right away
+        Exp::Index { array: a, index: i } => match (*subst(a, s), *subst(i, s)) {
+            (Exp::Array { elems }, Exp::Num { val })
+                if (val as usize) < elems.len() && val >= 0 =>
+            {
+                return Box::new(elems[val as usize].clone());
+            }
+            (a, i) => index(Box::new(a), Box::new(i)),
+        },
+        Exp::Update {
+            array: a,
+            index: i,
+            value,
+        } => update(subst(a, s), subst(i, s), subst(value, s)),
+        Exp::Len { exp } => match *subst(exp, s) {
+            Exp::Array { elems } => return number(elems.len() as i32),
+            exp => len(Box::new(exp)),
+        },
+        Exp::Record { name, fields } => record(
+            name.clone(),
+            fields
+                .iter()
+                .map(|(x, e)| (x.clone(), *subst(e, s)))
+                .collect(),
+        ),
+        // fields of a literal or of an update are picked right away
+        Exp::Field { exp, field: f } => return project(*subst(exp, s), f),
+        Exp::With {
+            exp,
+            field: f,
+            value,
+        } => with(subst(exp, s), f.clone(), subst(value, s)),
+    };
+    if !e.vars().is_empty() {
+        return e;
+    }
+    e.fold().and_then(|v| quote(&v)).unwrap_or(e)
+}
+
+// The field f of the record e, simplified if e is a literal or an update
+pub fn project(e: Exp, f: &str) -> Box<Exp> {
+    match e {
+        Exp::Grp { exp } => project(*exp, f),
+        Exp::Record { fields, .. } if fields.iter().any(|(x, _)| x == f) => {
+            let (_, v) = fields.into_iter().find(|(x, _)| x == f).unwrap();
+            Box::new(v)
+        }
+        Exp::With { field, value, .. } if field == f => value,
+        Exp::With { exp, .. } => project(*exp, f),
+        e => field(Box::new(e), f.to_string()),
+    }
+}
+
+// The expression of a value, None for closures and undefined values
+fn quote(v: &Val) -> Option<Box<Exp>> {
+    match v.flag {
+        Kind::ValueInt => Some(number(v.val_i.unwrap())),
+        Kind::ValueBool => Some(boolean(v.val_b.unwrap())),
+        Kind::ValueStr => Some(string(v.val_s.clone().unwrap())),
+        Kind::Array => {
+            let elems = v.val_a.as_ref().unwrap().iter().map(quote);
+            Some(array(elems.map(|e| e.map(|e| *e)).collect::<Option<_>>()?))
+        }
+        Kind::Record => {
+            let r = v.val_r.as_ref().unwrap();
+            let mut fields = Vec::new();
+            for (x, v) in &r.fields {
+                fields.push((x.clone(), *quote(v)?));
+            }
+            Some(record(r.name.clone(), fields))
+        }
+        Kind::Closure | Kind::Undefined => None,
+    }
+}
+
+pub fn feasible(cond: &[Box<Exp>]) -> Outcome {
+    let formulas: Vec<&Exp> = cond.iter().map(|e| e.as_ref()).collect();
+    solve(&formulas)
+}
+
+impl Engine {
+    // Extends path by cond, unless that makes the path infeasible
+    fn assume(&self, path: &Path, cond: Box<Exp>) -> Option<Path> {
+        if let Exp::Bool { val } = *cond {
+            return if val { Some(path.clone()) } else { None };
+        }
+        let mut p = path.clone();
+        p.cond.push(cond);
+        match feasible(&p.cond) {
+            Outcome::Unsat => None,
+            _ => Some(p),
+        }
+    }
+    // Splits the paths by cond; non-bool conditions take neither branch
+    fn fork(&self, cond: &Exp, paths: Vec<Path>) -> (Vec<Path>, Vec<Path>, Vec<Path>) {
+        let mut yes = Vec::new();
+        let mut no = Vec::new();
+        let mut stuck = Vec::new();
+        for p in paths {
+            let c = subst(cond, &p.state);
+            if let Exp::Num { .. } = *c {
+                stuck.push(p);
+                continue;
+            }
+            let neg = match *c {
+                Exp::Bool { val } => boolean(!val),
+                _ => not(c.clone()),
+            };
+            yes.extend(self.assume(&p, c));
+            no.extend(self.assume(&p, neg));
+        }
+        (yes, no, stuck)
+    }
+    fn exec(&mut self, stmt: &Stmt, idx: usize, paths: Vec<Path>) -> Vec<Path> {
+        match stmt {
+            Stmt::Skip => paths,
+            // only well-typed in procedure bodies, which are not executed
+            Stmt::Return { .. } => Vec::new(),
+            Stmt::Break | Stmt::Continue => {
+                if let Some(j) = self.jumps.last_mut() {
+                    match stmt {
+                        Stmt::Break => j.0.extend(paths),
+                        _ => j.1.extend(paths),
+                    }
+                }
+                Vec::new()
+            }
+            Stmt::Seq { first, second } => {
+                let paths = self.exec(first, idx, paths);
+                self.exec(second, idx + first.size(), paths)
+            }
+            // the paths which continue in body go on with step
+            Stmt::Step { body, step } => {
+                let outer = self.jumps.last_mut().map(|j| std::mem::take(&mut j.1));
+                let mut paths = self.exec(body, idx, paths);
+                if let Some(j) = self.jumps.last_mut() {
+                    paths.extend(std::mem::replace(&mut j.1, outer.unwrap_or_default()));
+                }
+                self.exec(step, idx + body.size(), paths)
+            }
+            Stmt::Decl { lhs, rhs, .. } | Stmt::Assign { lhs, rhs } => paths
+                .into_iter()
+                .map(|mut p| {
+                    let v = subst(rhs, &p.state);
+                    p.state.insert(lhs.clone(), v);
+                    p
+                })
+                .collect(),
+            Stmt::Read { lhs, .. } => {
+                self.reads += 1;
+                let v = variable(format!("{}@{}", lhs, self.reads));
+                paths
+                    .into_iter()
+                    .map(|mut p| {
+                        p.state.insert(lhs.clone(), v.clone());
+                        p
+                    })
+                    .collect()
+            }
+            // the updated array is a new value of lhs
+            Stmt::ArrayAssign { lhs, index: i, rhs } => paths
+                .into_iter()
+                .map(|mut p| {
+                    let e = update(variable(lhs.clone()), i.clone(), rhs.clone());
+                    let v = subst(&e, &p.state);
+                    p.state.insert(lhs.clone(), v);
+                    p
+                })
+                .collect(),
+            Stmt::FieldAssign { lhs, field: f, rhs } => paths
+                .into_iter()
+                .map(|mut p| {
+                    let e = with(variable(lhs.clone()), f.clone(), rhs.clone());
+                    let v = subst(&e, &p.state);
+                    p.state.insert(lhs.clone(), v);
+                    p
+                })
+                .collect(),
+            Stmt::Print { .. } => paths
+                .into_iter()
+                .map(|mut p| {
+                    p.prints.push(idx);
+                    self.reached.entry(idx).or_default().push(p.cond.clone());
+                    p
+                })
+                .collect(),
+            Stmt::Assume { cond } => {
+                let (yes, _, stuck) = self.fork(cond, paths);
+                yes.into_iter().chain(stuck).collect()
+            }
+            Stmt::Assert { cond } => {
+                // only the paths on which the assertion holds go on
+                let (yes, no, stuck) = self.fork(cond, paths);
+                for p in no {
+                    self.failed.entry(idx).or_default().push(p.cond);
+                }
+                yes.into_iter().chain(stuck).collect()
+            }
+            Stmt::IfThenElse {
+                cond,
+                then_stmt,
+                else_stmt,
+            } => {
+                let (yes, no, mut stuck) = self.fork(cond, paths);
+                stuck.extend(self.exec(then_stmt, idx + 1, yes));
+                stuck.extend(self.exec(else_stmt, idx + 1 + then_stmt.size(), no));
+                stuck
+            }
+            Stmt::While {
+                cond, stmt: body, ..
+            } => {
+                let mut done = Vec::new();
+                let mut paths = paths;
+                for round in 0..=self.unroll {
+                    if paths.is_empty() {
+                        break;
+                    }
+                    let (yes, no, stuck) = self.fork(cond, paths);
+                    done.extend(no);
+                    done.extend(stuck);
+                    if round == self.unroll {
+                        self.cut += yes.len();
+                        break;
+                    }
+                    self.jumps.push((Vec::new(), Vec::new()));
+                    paths = self.exec(body, idx + 1, yes);
+                    let (breaks, continues) = self.jumps.pop().unwrap();
+                    done.extend(breaks);
+                    paths.extend(continues);
+                }
+                done
+            }
+        }
+    }
+}
+
+pub fn run(stmt: &Stmt, unroll: usize) -> Engine {
+    let mut e = Engine {
+        unroll,
+        paths: Vec::new(),
+        cut: 0,
+        reached: BTreeMap::new(),
+        failed: BTreeMap::new(),
+        jumps: Vec::new(),
+        reads: 0,
+    };
+    let start = Path {
+        state: SymState::new(),
+        cond: Vec::new(),
+        prints: Vec::new(),
+    };
+    e.paths = e.exec(stmt, 0, vec![start]);
+    e
+}
+
+// Indices of all Print statements
+pub fn prints(stmt: &Stmt) -> Vec<usize> {
+    let mut xs = Vec::new();
+    stmt.visit(&mut 0, &mut |i, s| {
+        if let Stmt::Print { .. } = s {
+            xs.push(i);
+        }
+    });
+    xs
+}
+
+// Concrete inputs leading to the Print statement idx, if one is found
+pub fn inputs_for(e: &Engine, idx: usize) -> Option<Model> {
+    for cond in e.reached.get(&idx)? {
+        if let Outcome::Sat(m) = feasible(cond) {
+            return Some(m);
+        }
+    }
+    None
+}
+
+// Concrete inputs violating the Assert statement idx, if one is found
+pub fn counterexample(e: &Engine, idx: usize) -> Option<Model> {
+    for cond in e.failed.get(&idx)? {
+        if let Outcome::Sat(m) = feasible(cond) {
+            return Some(m);
+        }
+    }
+    None
+}
+
+pub fn show_cond(cond: &[Box<Exp>]) -> String {
+    if cond.is_empty() {
+        return "true".to_string();
+    }
+    let cs: Vec<String> = cond.iter().map(|c| c.pretty()).collect();
+    cs.join(" && ")
+}
+
+pub fn show_model(m: &Model) -> String {
+    if m.is_empty() {
+        return "any input".to_string();
+    }
+    let xs: Vec<String> = m.iter().map(|(x, v)| format!("{} = {}", x, v)).collect();
+    xs.join(", ")
+}
@@ -0,0 +1,883 @@
+use super::scalar::Vars;
+use super::*;
+
+// Translation of a well typed program into a WebAssembly module in the
+// text format. Ints and bools are i32, the variables of a procedure are
+// locals of its function and the main program is the exported function
+// main. A While is a loop in a block which br_if leaves when the condition
+// is false, an IfThenElse is an if. Operands are evaluated from left to
+// right as in exec, && and || evaluate both.
+//
+// The host provides the functions of the module "imp": print_i32 and
+// print_bool, read_i32 and read_bool, and the runtime errors, which do not
+// return: assert_failed with the number of the assertion, no_return with
+// the number of the procedure, stack_overflow, and overflow with the
+// operands of + or * and the operator as a character between them. The
+// module checks + and * itself in $add and $mul. Only ints and bools are
+// translated.
+
+const IMPORTS: &str = r#"  (import "imp" "print_i32" (func $print_i32 (param i32)))
+  (import "imp" "print_bool" (func $print_bool (param i32)))
+  (import "imp" "read_i32" (func $read_i32 (result i32)))
+  (import "imp" "read_bool" (func $read_bool (result i32)))
+  (import "imp" "assert_failed" (func $assert_failed (param i32)))
+  (import "imp" "no_return" (func $no_return (param i32)))
+  (import "imp" "stack_overflow" (func $stack_overflow))
+  (import "imp" "overflow" (func $overflow (param i32 i32 i32)))
+  (global $depth (mut i32) (i32.const 0))
+  (func $add (param $a i32) (param $b i32) (result i32)
+    (local $r i32)
+    local.get $a
+    local.get $b
+    i32.add
+    local.set $r
+    ;; both operands differ from the result in the sign
+    local.get $a
+    local.get $r
+    i32.xor
+    local.get $b
+    local.get $r
+    i32.xor
+    i32.and
+    i32.const 0
+    i32.lt_s
+    if
+      local.get $a
+      i32.const 43
+      local.get $b
+      call $overflow
+      unreachable
+    end
+    local.get $r
+  )
+  (func $mul (param $a i32) (param $b i32) (result i32)
+    (local $r i32)
+    local.get $a
+    local.get $b
+    i32.mul
+    local.set $r
+    ;; r / a is b unless the product overflows, -1 * -2147483648 traps
+    local.get $a
+    if
+      local.get $a
+      i32.const -1
+      i32.eq
+      if
+        local.get $b
+        i32.const -2147483648
+        i32.eq
+        if
+          local.get $a
+          i32.const 42
+          local.get $b
+          call $overflow
+          unreachable
+        end
+      else
+        local.get $r
+        local.get $a
+        i32.div_s
+        local.get $b
+        i32.ne
+        if
+          local.get $a
+          i32.const 42
+          local.get $b
+          call $overflow
+          unreachable
+        end
+      end
+    end
+    local.get $r
+  )
+"#;
+
+pub fn compile(stmt: &Stmt, procs: &[Proc]) -> Result<String, String> {
+    let mut out = String::from("(module\n");
+    out.push_str(IMPORTS);
+    let mut asserts = 0;
+    for (k, p) in procs.iter().enumerate() {
+        let mut g = Gen::new(procs, &p.params, asserts);
+        g.lines(&[
+            "global.get $depth",
+            "i32.const 1000",
+            "i32.eq",
+            "if",
+            "  call $stack_overflow",
+            "  unreachable",
+            "end",
+            "global.get $depth",
+            "i32.const 1",
+            "i32.add",
+            "global.set $depth",
+        ]);
+        g.stmt(&p.body)?;
+        g.line(&format!("i32.const {}", k));
+        g.lines(&["call $no_return", "unreachable"]);
+        g.vars.check(&p.ret)?;
+        let mut header = format!("  (func $f_{}", p.name);
+        for (x, ty) in &p.params {
+            g.vars.check(ty)?;
+            header.push_str(&format!(" (param $v_{} i32)", x));
+        }
+        header.push_str(" (result i32)");
+        out.push_str(&g.func(header));
+        asserts = g.asserts;
+    }
+    let mut g = Gen::new(procs, &[], asserts);
+    g.stmt(stmt)?;
+    out.push_str(&g.func("  (func $main (export \"main\")".to_string()));
+    out.push_str(")\n");
+    Ok(out)
+}
+
+struct Gen<'a> {
+    vars: Vars<'a>,
+    out: String,
+    indent: usize,
+    // the number of the next assertion of the module
+    asserts: usize,
+    // the number of the enclosing loops and whether continue leaves the
+    // block of a step
+    loops: Vec<(usize, bool)>,
+    labels: usize,
+}
+
+impl<'a> Gen<'a> {
+    fn new(procs: &'a [Proc], params: &[(String, Type)], asserts: usize) -> Gen<'a> {
+        Gen {
+            vars: Vars::new(procs, params, "WAT"),
+            out: String::new(),
+            indent: 2,
+            asserts,
+            loops: Vec::new(),
+            labels: 0,
+        }
+    }
+    // The function with the header, the locals and the body
+    fn func(&self, header: String) -> String {
+        let mut f = header + "\n";
+        for (x, _) in self.vars.locals() {
+            f.push_str(&format!("    (local $v_{} i32)\n", x));
+        }
+        f.push_str(&self.out);
+        f.push_str("  )\n");
+        f
+    }
+    fn line(&mut self, x: &str) {
+        self.out.push_str(&"  ".repeat(self.indent));
+        self.out.push_str(x);
+        self.out.push('\n');
+    }
+    fn lines(&mut self, xs: &[&str]) {
+        for x in xs {
+            self.line(x);
+        }
+    }
+    fn stmt(&mut self, stmt: &Stmt) -> Result<(), String> {
+        match stmt {
+            Stmt::Skip | Stmt::Assume { .. } => {}
+            Stmt::Seq { first, second } => {
+                self.stmt(first)?;
+                self.stmt(second)?;
+            }
+            Stmt::Decl { lhs, ty, rhs } => {
+                let ty = match ty {
+                    Some(ty) => ty.clone(),
+                    None => self.vars.ty(rhs)?,
+                };
+                self.exp(rhs)?;
+                self.vars.declare(lhs, ty)?;
+                self.line(&format!("local.set $v_{}", lhs));
+            }
+            Stmt::Assign { lhs, rhs } => {
+                self.exp(rhs)?;
+                self.line(&format!("local.set $v_{}", lhs));
+            }
+            Stmt::Read { lhs, ty } => {
+                self.vars.declare(lhs, ty.clone())?;
+                let f = if *ty == Type::TyBool { "bool" } else { "i32" };
+                self.line(&format!("call $read_{}", f));
+                self.line(&format!("local.set $v_{}", lhs));
+            }
+            Stmt::Print { print_exp } => {
+                let f = if self.vars.ty(print_exp)? == Type::TyBool {
+                    "bool"
+                } else {
+                    "i32"
+                };
+                self.exp(print_exp)?;
+                self.line(&format!("call $print_{}", f));
+            }
+            Stmt::Assert { cond } => {
+                self.line(&format!(";; assertion {}: {}", self.asserts, cond.pretty()));
+                self.exp(cond)?;
+                self.lines(&["i32.eqz", "if"]);
+                self.line(&format!("  i32.const {}", self.asserts));
+                self.lines(&["  call $assert_failed", "  unreachable", "end"]);
+                self.asserts += 1;
+            }
+            Stmt::Return { exp } => {
+                self.exp(exp)?;
+                self.lines(&[
+                    "global.get $depth",
+                    "i32.const 1",
+                    "i32.sub",
+                    "global.set $depth",
+                    "return",
+                ]);
+            }
+            Stmt::Break => {
+                let (n, _) = *self.loops.last().ok_or("break outside a loop")?;
+                self.line(&format!("br $exit{}", n));
+            }
+            Stmt::Continue => match *self.loops.last().ok_or("continue outside a loop")? {
+                (n, true) => self.line(&format!("br $body{}", n)),
+                (n, false) => self.line(&format!("br $head{}", n)),
+            },
+            Stmt::IfThenElse {
+                cond,
+                then_stmt,
+                else_stmt,
+            } => {
+                self.exp(cond)?;
+                self.line("if");
+                self.block(then_stmt)?;
+                if !matches!(**else_stmt, Stmt::Skip) {
+                    self.line("else");
+                    self.block(else_stmt)?;
+                }
+                self.line("end");
+            }
+            Stmt::While { cond, stmt, .. } => {
+                self.labels += 1;
+                let n = self.labels;
+                self.line(&format!("block $exit{}", n));
+                self.indent += 1;
+                self.line(&format!("loop $head{}", n));
+                self.indent += 1;
+                self.exp(cond)?;
+                self.line("i32.eqz");
+                self.line(&format!("br_if $exit{}", n));
+                self.loops.push((n, false));
+                self.stmt(stmt)?;
+                self.loops.pop();
+                self.line(&format!("br $head{}", n));
+                self.indent -= 1;
+                self.line("end");
+                self.indent -= 1;
+                self.line("end");
+            }
+            // continue leaves the block of the body and runs the step
+            Stmt::Step { body, step } => {
+                let l = self.loops.last_mut().ok_or("step outside a loop")?;
+                l.1 = true;
+                let n = l.0;
+                self.line(&format!("block $body{}", n));
+                self.block(body)?;
+                self.line("end");
+                self.loops.last_mut().unwrap().1 = false;
+                self.stmt(step)?;
+            }
+            Stmt::ArrayAssign { .. } | Stmt::FieldAssign { .. } => {
+                return Err(self.vars.unsupported(&stmt.pretty()))
+            }
+        }
+        Ok(())
+    }
+    fn block(&mut self, stmt: &Stmt) -> Result<(), String> {
+        self.indent += 1;
+        self.stmt(stmt)?;
+        self.indent -= 1;
+        Ok(())
+    }
+    // Pushes the value of e
+    fn exp(&mut self, e: &Exp) -> Result<(), String> {
+        if let Some((op, left, right)) = e.binary() {
+            self.exp(left)?;
+            self.exp(right)?;
+            self.line(match op {
+                BinOp::Plus => "call $add",
+                BinOp::Mult => "call $mul",
+                BinOp::And => "i32.and",
+                BinOp::Or => "i32.or",
+                BinOp::Equ => "i32.eq",
+                BinOp::Less => "i32.lt_s",
+            });
+            return Ok(());
+        }
+        match e {
+            Exp::Num { val } => self.line(&format!("i32.const {}", val)),
+            Exp::Bool { val } => self.line(&format!("i32.const {}", *val as i32)),
+            Exp::Var { name } => {
+                self.vars.ty(e)?;
+                self.line(&format!("local.get $v_{}", name));
+            }
+            Exp::Grp { exp } => self.exp(exp)?,
+            Exp::Neg { exp } => {
+                self.exp(exp)?;
+                self.line("i32.eqz");
+            }
+            Exp::Call { name, args } => {
+                self.vars.ty(e)?;
+                for a in args {
+                    self.exp(a)?;
+                }
+                self.line(&format!("call $f_{}", name));
+            }
+            _ => return Err(self.vars.unsupported(&e.pretty())),
+        }
+        Ok(())
+    }
+}
+
+// A validator for the modules compile emits, which checks the structure of
+// a module without a WebAssembly toolchain: the fields of the module, the
+// references to functions, globals, locals and labels, the nesting of
+// blocks and the types of the operand stack. The only value type is i32
+// and instructions are written one after the other, not folded.
+pub fn validate(text: &str) -> Result<(), String> {
+    let mut toks = tokens(text)?.into_iter();
+    let module = match (toks.next(), sexp_list(&mut toks)?) {
+        (Some(Tok::Open), m) => m,
+        _ => return Err("expected (module ..)".to_string()),
+    };
+    if toks.next().is_some() {
+        return Err("text after the module".to_string());
+    }
+    let mut fields = module.iter();
+    if !matches!(fields.next(), Some(Sexp::Atom(x)) if x == "module") {
+        return Err("expected (module ..)".to_string());
+    }
+    let mut m = Module::default();
+    let mut bodies = Vec::new();
+    for field in fields {
+        let xs = match field {
+            Sexp::List(xs) => xs,
+            _ => return Err(format!("unexpected {} in the module", field)),
+        };
+        match xs.first() {
+            Some(Sexp::Atom(x)) if x == "import" => m.import(xs)?,
+            Some(Sexp::Atom(x)) if x == "global" => m.global(xs)?,
+            Some(Sexp::Atom(x)) if x == "func" => bodies.push(m.func(xs)?),
+            _ => return Err(format!("unsupported module field {}", field)),
+        }
+    }
+    for body in bodies {
+        m.body(body)?;
+    }
+    Ok(())
+}
+
+#[derive(PartialEq, Debug)]
+enum Tok {
+    Open,
+    Close,
+    Atom(String),
+}
+
+fn tokens(text: &str) -> Result<Vec<Tok>, String> {
+    let mut toks = Vec::new();
+    let mut cs = text.chars().peekable();
+    while let Some(c) = cs.next() {
+        match c {
+            ';' if cs.peek() == Some(&';') => {
+                for c in cs.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            '(' if cs.peek() == Some(&';') => {
+                let mut prev = ' ';
+                loop {
+                    match cs.next() {
+                        Some(')') if prev == ';' => break,
+                        Some(c) => prev = c,
+                        None => return Err("unterminated block comment".to_string()),
+                    }
+                }
+            }
+            '(' => toks.push(Tok::Open),
+            ')' => toks.push(Tok::Close),
+            '"' => {
+                let mut x = String::from('"');
+                loop {
+                    match cs.next() {
+                        Some('"') => break,
+                        Some('\\') => {
+                            x.push('\\');
+                            x.extend(cs.next());
+                        }
+                        Some(c) => x.push(c),
+                        None => return Err("unterminated string".to_string()),
+                    }
+                }
+                x.push('"');
+                toks.push(Tok::Atom(x));
+            }
+            c if c.is_whitespace() => {}
+            c => {
+                let mut x = String::from(c);
+                while let Some(&c) = cs.peek() {
+                    if c.is_whitespace() || c == '(' || c == ')' || c == ';' || c == '"' {
+                        break;
+                    }
+                    x.push(c);
+                    cs.next();
+                }
+                toks.push(Tok::Atom(x));
+            }
+        }
+    }
+    Ok(toks)
+}
+
+enum Sexp {
+    Atom(String),
+    List(Vec<Sexp>),
+}
+
+impl Display for Sexp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Sexp::Atom(x) => write!(f, "{}", x),
+            Sexp::List(xs) => match xs.first() {
+                Some(x) => write!(f, "({} ..)", x),
+                None => write!(f, "()"),
+            },
+        }
+    }
+}
+
+// The elements of a list whose opening parenthesis was read
+fn sexp_list(toks: &mut impl Iterator<Item = Tok>) -> Result<Vec<Sexp>, String> {
+    let mut xs = Vec::new();
+    loop {
+        match toks.next() {
+            Some(Tok::Open) => xs.push(Sexp::List(sexp_list(toks)?)),
+            Some(Tok::Close) => return Ok(xs),
+            Some(Tok::Atom(x)) => xs.push(Sexp::Atom(x)),
+            None => return Err("missing )".to_string()),
+        }
+    }
+}
+
+// The numbers of parameters and results of a function
+#[derive(Clone, Copy)]
+struct FuncType {
+    params: usize,
+    results: usize,
+}
+
+type Signature<'a> = (FuncType, Vec<Option<String>>, &'a [Sexp]);
+
+#[derive(Default)]
+struct Module {
+    funcs: Vec<(Option<String>, FuncType)>,
+    // whether each global is mutable
+    globals: Vec<(Option<String>, bool)>,
+    exports: Vec<String>,
+}
+
+// The parts of a function, with the names of its parameters and locals
+struct Body<'a> {
+    name: String,
+    ty: FuncType,
+    locals: Vec<Option<String>>,
+    instrs: &'a [Sexp],
+}
+
+fn atom(x: &Sexp) -> Option<&str> {
+    match x {
+        Sexp::Atom(x) => Some(x),
+        Sexp::List(_) => None,
+    }
+}
+
+fn id(x: Option<&Sexp>) -> Option<String> {
+    x.and_then(atom)
+        .filter(|x| x.starts_with('$'))
+        .map(|x| x.to_string())
+}
+
+fn string(x: Option<&Sexp>) -> Result<String, String> {
+    match x.and_then(atom) {
+        Some(x) if x.starts_with('"') => Ok(x.to_string()),
+        _ => Err("expected a string".to_string()),
+    }
+}
+
+fn value_type(x: Option<&Sexp>) -> Result<(), String> {
+    match x.and_then(atom) {
+        Some("i32") => Ok(()),
+        Some(x) => Err(format!("unsupported value type {}", x)),
+        None => Err("expected a value type".to_string()),
+    }
+}
+
+fn i32_const(x: Option<&Sexp>) -> Result<(), String> {
+    let x = x.and_then(atom).ok_or("expected a number")?;
+    let n = x.replace('_', "");
+    match (n.parse::<i32>(), n.parse::<u32>()) {
+        (Err(_), Err(_)) => Err(format!("{} is no i32", x)),
+        _ => Ok(()),
+    }
+}
+
+impl Module {
+    fn declare<T>(xs: &[(Option<String>, T)], x: &Option<String>) -> Result<(), String> {
+        match x {
+            Some(x) if xs.iter().any(|(y, _)| y.as_ref() == Some(x)) => {
+                Err(format!("{} is defined twice", x))
+            }
+            _ => Ok(()),
+        }
+    }
+    fn import(&mut self, xs: &[Sexp]) -> Result<(), String> {
+        string(xs.get(1))?;
+        string(xs.get(2))?;
+        let desc = match xs.get(3) {
+            Some(Sexp::List(desc)) if xs.len() == 4 => desc,
+            _ => return Err("expected (import \"module\" \"name\" (func ..))".to_string()),
+        };
+        if desc.first().and_then(atom) != Some("func") {
+            return Err("only functions can be imported".to_string());
+        }
+        let name = id(desc.get(1));
+        let rest = &desc[1 + name.is_some() as usize..];
+        let (ty, _, rest) = self.signature(rest)?;
+        if !rest.is_empty() {
+            return Err(format!("unexpected {} in an import", rest[0]));
+        }
+        Module::declare(&self.funcs, &name)?;
+        self.funcs.push((name, ty));
+        Ok(())
+    }
+    fn global(&mut self, xs: &[Sexp]) -> Result<(), String> {
+        let name = id(xs.get(1));
+        let rest = &xs[1 + name.is_some() as usize..];
+        let mutable = match rest.first() {
+            Some(Sexp::List(ty)) if ty.first().and_then(atom) == Some("mut") => {
+                value_type(ty.get(1))?;
+                true
+            }
+            ty => {
+                value_type(ty)?;
+                false
+            }
+        };
+        match rest.get(1) {
+            Some(Sexp::List(init))
+                if rest.len() == 2 && init.len() == 2 && atom(&init[0]) == Some("i32.const") =>
+            {
+                i32_const(init.get(1))?
+            }
+            _ => return Err("a global needs an (i32.const ..) initializer".to_string()),
+        }
+        Module::declare(&self.globals, &name)?;
+        self.globals.push((name, mutable));
+        Ok(())
+    }
+    // The type of the params and results at the start of xs, with the names
+    // of the params and the rest of xs
+    fn signature<'a>(&self, xs: &'a [Sexp]) -> Result<Signature<'a>, String> {
+        let mut names = Vec::new();
+        let mut results = 0;
+        let mut k = 0;
+        while let Some(Sexp::List(x)) = xs.get(k) {
+            match x.first().and_then(atom) {
+                Some("param") if results == 0 => {
+                    let name = id(x.get(1));
+                    let types = &x[1 + name.is_some() as usize..];
+                    if name.is_some() && types.len() != 1 {
+                        return Err("a named param has one type".to_string());
+                    }
+                    for ty in types {
+                        value_type(Some(ty))?;
+                        names.push(name.clone());
+                    }
+                }
+                Some("result") => {
+                    for ty in &x[1..] {
+                        value_type(Some(ty))?;
+                        results += 1;
+                    }
+                }
+                _ => break,
+            }
+            k += 1;
+        }
+        if results > 1 {
+            return Err("functions have at most one result".to_string());
+        }
+        let ty = FuncType {
+            params: names.len(),
+            results,
+        };
+        Ok((ty, names, &xs[k..]))
+    }
+    fn func<'a>(&mut self, xs: &'a [Sexp]) -> Result<Body<'a>, String> {
+        let name = id(xs.get(1));
+        let mut rest = &xs[1 + name.is_some() as usize..];
+        while let Some(Sexp::List(x)) = rest.first() {
+            if x.first().and_then(atom) != Some("export") {
+                break;
+            }
+            let e = string(x.get(1))?;
+            if self.exports.contains(&e) {
+                return Err(format!("export {} is defined twice", e));
+            }
+            self.exports.push(e);
+            rest = &rest[1..];
+        }
+        let (ty, mut locals, mut rest) = self.signature(rest)?;
+        while let Some(Sexp::List(x)) = rest.first() {
+            if x.first().and_then(atom) != Some("local") {
+                break;
+            }
+            let local = id(x.get(1));
+            let types = &x[1 + local.is_some() as usize..];
+            for ty in types {
+                value_type(Some(ty))?;
+                locals.push(local.clone());
+            }
+            rest = &rest[1..];
+        }
+        for (k, x) in locals.iter().enumerate() {
+            if x.is_some() && locals[..k].contains(x) {
+                return Err(format!("{} is defined twice", x.as_ref().unwrap()));
+            }
+        }
+        Module::declare(&self.funcs, &name)?;
+        self.funcs.push((name.clone(), ty));
+        let body = Body {
+            name: name.unwrap_or_else(|| format!("{}", self.funcs.len() - 1)),
+            ty,
+            locals,
+            instrs: rest,
+        };
+        Ok(body)
+    }
+    // Checks the instructions of a function with the operand stack, of
+    // which only the height matters since all values are i32
+    fn body(&self, f: Body) -> Result<(), String> {
+        let mut c = Checker {
+            frames: vec![Frame {
+                kind: "func",
+                label: None,
+                height: 0,
+                unreachable: false,
+                has_else: false,
+            }],
+            height: 0,
+            results: f.ty.results,
+        };
+        let err = |msg: String| format!("func {}: {}", f.name, msg);
+        let mut xs = f.instrs.iter().peekable();
+        while let Some(x) = xs.next() {
+            let op = match x {
+                Sexp::Atom(op) => op.as_str(),
+                Sexp::List(_) => return Err(err(format!("folded instruction {}", x))),
+            };
+            match op {
+                "i32.const" => {
+                    i32_const(xs.next()).map_err(err)?;
+                    c.push(1);
+                }
+                "local.get" | "local.set" | "local.tee" => {
+                    let x = xs.next().and_then(atom).unwrap_or("");
+                    let found = match x.parse::<usize>() {
+                        Ok(k) => k < f.locals.len(),
+                        Err(_) => f.locals.iter().any(|y| y.as_deref() == Some(x)),
+                    };
+                    if !found {
+                        return Err(err(format!("unknown local {}", x)));
+                    }
+                    if op != "local.get" {
+                        c.pop(op, 1).map_err(err)?;
+                    }
+                    if op != "local.set" {
+                        c.push(1);
+                    }
+                }
+                "global.get" | "global.set" => {
+                    let x = xs.next().and_then(atom).unwrap_or("");
+                    let g = match x.parse::<usize>() {
+                        Ok(k) => self.globals.get(k),
+                        Err(_) => self.globals.iter().find(|(y, _)| y.as_deref() == Some(x)),
+                    };
+                    match (g, op) {
+                        (None, _) => return Err(err(format!("unknown global {}", x))),
+                        (Some(_), "global.get") => c.push(1),
+                        (Some((_, false)), _) => {
+                            return Err(err(format!("global {} is immutable", x)))
+                        }
+                        (Some(_), _) => c.pop(op, 1).map_err(err)?,
+                    }
+                }
+                "i32.add" | "i32.sub" | "i32.mul" | "i32.div_s" | "i32.and" | "i32.or"
+                | "i32.xor" | "i32.eq" | "i32.ne" | "i32.lt_s" | "i32.lt_u" | "i32.gt_s"
+                | "i32.gt_u" | "i32.le_s" | "i32.le_u" | "i32.ge_s" | "i32.ge_u" => {
+                    c.pop(op, 2).map_err(err)?;
+                    c.push(1);
+                }
+                "i32.eqz" => {
+                    c.pop(op, 1).map_err(err)?;
+                    c.push(1);
+                }
+                "drop" => c.pop(op, 1).map_err(err)?,
+                "nop" => {}
+                "call" => {
+                    let x = xs.next().and_then(atom).unwrap_or("");
+                    let g = match x.parse::<usize>() {
+                        Ok(k) => self.funcs.get(k),
+                        Err(_) => self.funcs.iter().find(|(y, _)| y.as_deref() == Some(x)),
+                    };
+                    let ty = g.ok_or_else(|| err(format!("unknown function {}", x)))?.1;
+                    c.pop(op, ty.params).map_err(err)?;
+                    c.push(ty.results);
+                }
+                "unreachable" => c.unreachable(),
+                "return" => {
+                    c.pop(op, c.results).map_err(err)?;
+                    c.unreachable();
+                }
+                "br" => {
+                    let arity = c.label(xs.next()).map_err(err)?;
+                    c.pop(op, arity).map_err(err)?;
+                    c.unreachable();
+                }
+                "br_if" => {
+                    let arity = c.label(xs.next()).map_err(err)?;
+                    c.pop(op, 1 + arity).map_err(err)?;
+                    c.push(arity);
+                }
+                "block" | "loop" | "if" => {
+                    if op == "if" {
+                        c.pop(op, 1).map_err(err)?;
+                    }
+                    let label = match xs.peek() {
+                        Some(Sexp::Atom(x)) if x.starts_with('$') => Some(x.clone()),
+                        _ => None,
+                    };
+                    if label.is_some() {
+                        xs.next();
+                    }
+                    if let Some(Sexp::List(t)) = xs.peek() {
+                        if matches!(t.first().and_then(atom), Some("result" | "param")) {
+                            return Err(err("blocks with types are not supported".to_string()));
+                        }
+                    }
+                    c.frames.push(Frame {
+                        kind: match op {
+                            "block" => "block",
+                            "loop" => "loop",
+                            _ => "if",
+                        },
+                        label,
+                        height: c.height,
+                        unreachable: false,
+                        has_else: false,
+                    });
+                }
+                "else" | "end" => c.end(op).map_err(err)?,
+                _ => return Err(err(format!("unknown instruction {}", op))),
+            }
+        }
+        if c.frames.len() > 1 {
+            let kind = c.frames.last().unwrap().kind;
+            return Err(err(format!("{} without end", kind)));
+        }
+        if c.height != c.results && !(c.frames[0].unreachable && c.height <= c.results) {
+            return Err(err(format!(
+                "the function ends with {} values on the stack instead of {}",
+                c.height, c.results
+            )));
+        }
+        Ok(())
+    }
+}
+
+// The state of the check of a function body
+struct Checker {
+    // the enclosing blocks, the function first and the innermost last
+    frames: Vec<Frame>,
+    height: usize,
+    results: usize,
+}
+
+struct Frame {
+    kind: &'static str,
+    label: Option<String>,
+    // the height of the operand stack at the start of the block
+    height: usize,
+    // whether the rest of the block cannot be reached, where any operands
+    // can be popped
+    unreachable: bool,
+    has_else: bool,
+}
+
+impl Checker {
+    fn push(&mut self, n: usize) {
+        self.height += n;
+    }
+    // Pops n operands, which are there in unreachable code
+    fn pop(&mut self, op: &str, n: usize) -> Result<(), String> {
+        let frame = self.frames.last().unwrap();
+        if self.height >= frame.height + n {
+            self.height -= n;
+        } else if frame.unreachable {
+            self.height = frame.height;
+        } else {
+            return Err(format!("{} needs {} operands", op, n));
+        }
+        Ok(())
+    }
+    // The rest of the block cannot be reached
+    fn unreachable(&mut self) {
+        let frame = self.frames.last_mut().unwrap();
+        self.height = frame.height;
+        frame.unreachable = true;
+    }
+    fn end(&mut self, op: &str) -> Result<(), String> {
+        if self.frames.len() == 1 {
+            return Err(format!("{} without block", op));
+        }
+        let frame = self.frames.last_mut().unwrap();
+        if self.height != frame.height && !frame.unreachable {
+            return Err(format!(
+                "{} values left on the stack at {}",
+                self.height - frame.height,
+                op
+            ));
+        }
+        self.height = frame.height;
+        if op == "end" {
+            self.frames.pop();
+        } else if frame.kind != "if" || frame.has_else {
+            return Err("else without if".to_string());
+        } else {
+            frame.has_else = true;
+            frame.unreachable = false;
+        }
+        Ok(())
+    }
+    // The number of operands a branch to the label x takes: none for blocks
+    // and loops, which have no types, the results for the function
+    fn label(&self, x: Option<&Sexp>) -> Result<usize, String> {
+        let x = x.and_then(atom).unwrap_or("");
+        let k = match x.parse::<usize>() {
+            Ok(k) => self.frames.len().checked_sub(k + 1),
+            Err(_) => self
+                .frames
+                .iter()
+                .rposition(|f| f.label.as_deref() == Some(x)),
+        };
+        match k {
+            Some(0) => Ok(self.results),
+            Some(_) => Ok(0),
+            None => Err(format!("unknown label {}", x)),
+        }
+    }
+}
@@ -0,0 +1,407 @@
+use super::solver::{solve, Model, Outcome};
+use super::symbolic::project;
+use super::*;
+
+// Weakest preconditions of Stmt in the style of Hoare logic.
+//
+// wp(x := e, Q)            = Q[e/x]
+// wp(s1; s2, Q)            = wp(s1, wp(s2, Q))
+// wp(if c s1 else s2, Q)   = (c => wp(s1, Q)) && (!c => wp(s2, Q))
+// wp(assert e, Q)          = e && Q
+// wp(assume e, Q)          = e => Q
+// wp(while c inv i s, Q)   = i
+// wp(break, Q)             = Q of the loop
+// wp(continue, Q)          = i of the loop
+// wp(read x, Q)            = Q[x@k/x] for a fresh input x@k
+//
+// plus the side conditions (i && c) => wp(s, i) and (i && !c) => Q for every
+// loop. The step t of a for loop runs after s and on continue, so wp(t, i)
+// takes the place of i in s. A loop without invariant has the invariant
+// true. Preconditions of a program are assume statements at its beginning.
+// Implication a => b is expressed as !a || b.
+
+pub struct Vc {
+    // index of the loop the condition belongs to, None for the program
+    pub stmt: Option<usize>,
+    pub what: String,
+    pub formula: Box<Exp>,
+}
+
+pub enum Status {
+    // simplified to true
+    Trivial,
+    // the solver found no counterexample
+    Proved,
+    Failed(Model),
+    Unknown,
+}
+
+fn implies(a: Box<Exp>, b: Box<Exp>) -> Box<Exp> {
+    or(not(a), b)
+}
+
+// Replaces the variable x by v in e
+fn subst(e: &Exp, x: &str, v: &Exp) -> Box<Exp> {
+    match e {
+        Exp::Var { name } if name == x => group(Box::new(v.clone())),
+        Exp::Var { .. } | Exp::Num { .. } | Exp::Bool { .. } | Exp::Str { .. } => {
+            Box::new(e.clone())
+        }
+        Exp::Plus { left, right } => plus(subst(left, x, v), subst(right, x, v)),
+        Exp::Mult { left, right } => mult(subst(left, x, v), subst(right, x, v)),
+        Exp::And { left, right } => and(subst(left, x, v), subst(right, x, v)),
+        Exp::Or { left, right } => or(subst(left, x, v), subst(right, x, v)),
+        Exp::Equ { left, right } => equal(subst(left, x, v), subst(right, x, v)),
+        Exp::Less { left, right } => less(subst(left, x, v), subst(right, x, v)),
+        Exp::Neg { exp } => not(subst(exp, x, v)),
+        Exp::Grp { exp } => group(subst(exp, x, v)),
+        // x is a closure, its lambda is applied right away
+        Exp::Call { name, args } if name == x => {
+            let args: Vec<Exp> = args.iter().map(|a| *subst(a, x, v)).collect();
+            let mut f = v;
+            while let Exp::Grp { exp } = f {
+                f = exp;
+            }
+            match (f, args.as_slice()) {
+                (Exp::Lambda { param, body, .. }, [a]) => subst(body, param, a),
+                (Exp::Var { name }, _) => call(name.clone(), args),
+                // other closures have no name to call them by
+                _ => call(name.clone(), args),
+            }
+        }
+        Exp::Call { name, args } => {
+            call(name.clone(), args.iter().map(|a| *subst(a, x, v)).collect())
+        }
+        Exp::Lambda { param, .. } if param == x => Box::new(e.clone()),
+        // the parameter is renamed if it would capture a variable of v
+        Exp::Lambda { param, ty, body } if v.vars().contains(param) => {
+            let p = format!("{}'", param);
+            let body = subst(body, param, &Exp::Var { name: p.clone() });
+            lambda(p, ty.clone(), subst(&body, x, v))
+        }
+        Exp::Lambda { param, ty, body } => lambda(param.clone(), ty.clone(), subst(body, x, v)),
+        Exp::Array { elems } => array(elems.iter().map(|e| *subst(e, x, v)).collect()),
+        Exp::Index { array, index: i } => index(subst(array, x, v), subst(i, x, v)),
+        Exp::Update {
+            array,
+            index,
+            value,
+        } => update(subst(array, x, v), subst(index, x, v), subst(value, x, v)),
+        Exp::Len { exp } => len(subst(exp, x, v)),
+        Exp::Record { name, fields } => record(
+            name.clone(),
+            fields
+                .iter()
+                .map(|(f, e)| (f.clone(), *subst(e, x, v)))
+                .collect(),
+        ),
+        Exp::Field { exp, field: f } => field(subst(exp, x, v), f.clone()),
+        Exp::With {
+            exp,
+            field: f,
+            value,
+        } => with(subst(exp, x, v), f.clone(), subst(value, x, v)),
+    }
+}
+
+struct Gen {
+    vcs: Vec<Vc>,
+    // what break and continue have to establish in the enclosing loops
+    loops: Vec<(Box<Exp>, Box<Exp>)>,
+    // number of inputs made up for Read statements
+    reads: usize,
+}
+
+impl Gen {
+    fn wp(&mut self, s: &Stmt, idx: usize, q: Box<Exp>) -> Box<Exp> {
+        match s {
+            Stmt::Seq { first, second } => {
+                let q = self.wp(second, idx + first.size(), q);
+                self.wp(first, idx, q)
+            }
+            // continue in body goes on with step
+            Stmt::Step { body, step } => {
+                let q = self.wp(step, idx + body.size(), q);
+                let outer = self.loops.pop();
+                let exit = outer.clone().map_or(boolean(true), |l| l.0);
+                self.loops.push((exit, q.clone()));
+                let q = self.wp(body, idx, q);
+                self.loops.pop();
+                self.loops.extend(outer);
+                q
+            }
+            Stmt::Decl { lhs, rhs, .. } | Stmt::Assign { lhs, rhs } => subst(&q, lhs, rhs),
+            // Q has to hold for any value read
+            Stmt::Read { lhs, .. } => {
+                self.reads += 1;
+                subst(&q, lhs, &variable(format!("{}@{}", lhs, self.reads)))
+            }
+            // out-of-bounds updates are not checked
+            Stmt::ArrayAssign { lhs, index, rhs } => subst(
+                &q,
+                lhs,
+                &update(variable(lhs.clone()), index.clone(), rhs.clone()),
+            ),
+            Stmt::FieldAssign { lhs, field: f, rhs } => subst(
+                &q,
+                lhs,
+                &with(variable(lhs.clone()), f.clone(), rhs.clone()),
+            ),
+            Stmt::Skip | Stmt::Print { .. } => q,
+            Stmt::Assert { cond } => and(cond.clone(), q),
+            Stmt::Assume { cond } => implies(cond.clone(), q),
+            // the program ends, its postcondition is true
+            Stmt::Return { .. } => boolean(true),
+            Stmt::Break => self.loops.last().map_or(boolean(true), |l| l.0.clone()),
+            Stmt::Continue => self.loops.last().map_or(boolean(true), |l| l.1.clone()),
+            Stmt::IfThenElse {
+                cond,
+                then_stmt,
+                else_stmt,
+            } => {
+                let q1 = self.wp(then_stmt, idx + 1, q.clone());
+                let q2 = self.wp(else_stmt, idx + 1 + then_stmt.size(), q);
+                and(implies(cond.clone(), q1), implies(not(cond.clone()), q2))
+            }
+            Stmt::While { cond, inv, stmt } => {
+                let inv = inv.clone().unwrap_or_else(|| boolean(true));
+                self.loops.push((q.clone(), inv.clone()));
+                let body = self.wp(stmt, idx + 1, inv.clone());
+                self.loops.pop();
+                self.vcs.push(Vc {
+                    stmt: Some(idx),
+                    what: "invariant is preserved by the loop body".to_string(),
+                    formula: implies(and(inv.clone(), cond.clone()), body),
+                });
+                self.vcs.push(Vc {
+                    stmt: Some(idx),
+                    what: "invariant and exit condition establish what follows the loop"
+                        .to_string(),
+                    formula: implies(and(inv.clone(), not(cond.clone())), q),
+                });
+                inv
+            }
+        }
+    }
+}
+
+// Conjuncts of e, also below the conclusion of an implication
+fn conjuncts(e: &Exp) -> Vec<Exp> {
+    match e {
+        Exp::And { left, right } => {
+            let mut xs = conjuncts(left);
+            xs.extend(conjuncts(right));
+            xs
+        }
+        Exp::Or { left, right } => match left.as_ref() {
+            Exp::Neg { exp } => conjuncts(right)
+                .into_iter()
+                .map(|c| *implies(exp.clone(), Box::new(c)))
+                .collect(),
+            _ => vec![e.clone()],
+        },
+        Exp::Grp { exp } => conjuncts(exp),
+        _ => vec![e.clone()],
+    }
+}
+
+// Conjuncts of e without looking into implications
+fn hypotheses(e: &Exp) -> Vec<String> {
+    match e {
+        Exp::And { left, right } => {
+            let mut xs = hypotheses(left);
+            xs.extend(hypotheses(right));
+            xs
+        }
+        Exp::Grp { exp } => hypotheses(exp),
+        _ => vec![e.pretty()],
+    }
+}
+
+fn is_bool(e: &Exp, b: bool) -> bool {
+    matches!(e, Exp::Bool { val } if *val == b)
+}
+
+fn is_num(e: &Exp, n: i32) -> bool {
+    matches!(e, Exp::Num { val } if *val == n)
+}
+
+// Rewrites e with constant folding and simple boolean identities
+pub fn simplify(e: &Exp) -> Box<Exp> {
+    let e = match e {
+        Exp::Grp { exp } => return simplify(exp),
+        Exp::Var { .. } | Exp::Num { .. } | Exp::Bool { .. } | Exp::Str { .. } => {
+            return Box::new(e.clone())
+        }
+        Exp::Plus { left, right } => {
+            let (l, r) = (simplify(left), simplify(right));
+            if is_num(&l, 0) {
+                return r;
+            }
+            if is_num(&r, 0) {
+                return l;
+            }
+            plus(l, r)
+        }
+        Exp::Mult { left, right } => {
+            let (l, r) = (simplify(left), simplify(right));
+            if is_num(&l, 1) {
+                return r;
+            }
+            if is_num(&r, 1) {
+                return l;
+            }
+            mult(l, r)
+        }
+        Exp::And { left, right } => {
+            let (l, r) = (simplify(left), simplify(right));
+            if is_bool(&l, false) || is_bool(&r, false) {
+                return boolean(false);
+            }
+            if is_bool(&l, true) || l.pretty() == r.pretty() {
+                return r;
+            }
+            if is_bool(&r, true) {
+                return l;
+            }
+            and(l, r)
+        }
+        Exp::Or { left, right } => {
+            let (l, r) = (simplify(left), simplify(right));
+            if is_bool(&l, true) || is_bool(&r, true) {
+                return boolean(true);
+            }
+            if is_bool(&l, false) || l.pretty() == r.pretty() {
+                return r;
+            }
+            if is_bool(&r, false) {
+                return l;
+            }
+            // a => b holds if b is one of the conjuncts of a
+            if let Exp::Neg { exp } = l.as_ref() {
+                let r_str = r.pretty();
+                if hypotheses(exp).contains(&r_str) {
+                    return boolean(true);
+                }
+                if let Exp::Neg { exp: r_neg } = r.as_ref() {
+                    // a => !b with b one of the conjuncts of a is !a
+                    if hypotheses(exp).contains(&r_neg.pretty()) {
+                        return l;
+                    }
+                }
+            }
+            or(l, r)
+        }
+        Exp::Equ { left, right } => {
+            let (l, r) = (simplify(left), simplify(right));
+            if l.pretty() == r.pretty() {
+                return boolean(true);
+            }
+            equal(l, r)
+        }
+        Exp::Less { left, right } => {
+            let (l, r) = (simplify(left), simplify(right));
+            if l.pretty() == r.pretty() {
+                return boolean(false);
+            }
+            less(l, r)
+        }
+        Exp::Neg { exp } => {
+            let x = simplify(exp);
+            match *x {
+                Exp::Bool { val } => return boolean(!val),
+                Exp::Neg { exp } => return exp,
+                _ => not(x),
+            }
+        }
+        // calls are not evaluated, their value is unknown here
+        Exp::Call { name, args } => {
+            return call(name.clone(), args.iter().map(|a| *simplify(a)).collect())
+        }
+        Exp::Lambda { param, ty, body } => {
+            return lambda(param.clone(), ty.clone(), simplify(body))
+        }
+        Exp::Array { elems } => array(elems.iter().map(|e| *simplify(e)).collect()),
+        Exp::Index { array, index: i } => index(simplify(array), simplify(i)),
+        Exp::Update {
+            array,
+            index,
+            value,
+        } => update(simplify(array), simplify(index), simplify(value)),
+        Exp::Len { exp } => len(simplify(exp)),
+        Exp::Record { name, fields } => record(
+            name.clone(),
+            fields
+                .iter()
+                .map(|(f, e)| (f.clone(), *simplify(e)))
+                .collect(),
+        ),
+        Exp::Field { exp, field: f } => match *project(*simplify(exp), f) {
+            e @ Exp::Field { .. } => Box::new(e),
+            e => return simplify(&e),
+        },
+        Exp::With {
+            exp,
+            field: f,
+            value,
+        } => with(simplify(exp), f.clone(), simplify(value)),
+    };
+    if !e.vars().is_empty() {
+        return e;
+    }
+    // a sum or product which overflows stays as it is
+    let v = match e.fold() {
+        Some(v) => v,
+        None => return e,
+    };
+    match v.flag {
+        Kind::ValueInt => number(v.val_i.unwrap()),
+        Kind::ValueBool => boolean(v.val_b.unwrap()),
+        Kind::ValueStr => string(v.val_s.unwrap()),
+        Kind::Closure | Kind::Array | Kind::Record | Kind::Undefined => e,
+    }
+}
+
+// Verification conditions of stmt, split into conjuncts and simplified
+pub fn vcs(stmt: &Stmt) -> Vec<Vc> {
+    let mut g = Gen {
+        vcs: Vec::new(),
+        loops: Vec::new(),
+        reads: 0,
+    };
+    let pre = g.wp(stmt, 0, boolean(true));
+    let mut all = vec![Vc {
+        stmt: None,
+        what: "precondition of the program holds".to_string(),
+        formula: pre,
+    }];
+    all.extend(g.vcs);
+    let mut out = Vec::new();
+    for vc in all {
+        for c in conjuncts(&simplify(&vc.formula)) {
+            out.push(Vc {
+                stmt: vc.stmt,
+                what: vc.what.clone(),
+                formula: simplify(&c),
+            });
+        }
+    }
+    out
+}
+
+// A condition is valid if it holds for all values of its variables
+pub fn discharge(vc: &Vc) -> Status {
+    if is_bool(&vc.formula, true) {
+        return Status::Trivial;
+    }
+    let neg = not(vc.formula.clone());
+    match solve(&[neg.as_ref()]) {
+        Outcome::Unsat => Status::Proved,
+        Outcome::Sat(m) => Status::Failed(m),
+        Outcome::Unknown => Status::Unknown,
+    }
+}
+
+pub fn show_vc(vc: &Vc) -> String {
+    vc.formula.pretty()
+}
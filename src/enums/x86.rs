@@ -0,0 +1,776 @@
+use super::scalar::Vars;
+use super::*;
+
+// Translation of a well typed program into x86-64 assembly for the GNU
+// assembler (AT&T syntax), which runs on Linux without libc:
+//
+//   as -o prog.o prog.s && ld -o prog prog.o
+//
+// Every variable has a stack slot in the frame of its procedure, ints and
+// bools are 32 bit. The temporaries of an expression live in registers
+// which are taken from a pool and given back once their value is used; if
+// the pool runs out, the left operand of a binary operator waits on the
+// stack. A call saves the registers in use, pushes the arguments from left
+// to right and returns its result in %eax, which is also the scratch
+// register.
+//
+// The runtime at the end of the output prints values, reads input from
+// the command-line arguments or else the lines of stdin, and reports
+// runtime errors with the messages of exec and exit code 1. Only ints and
+// bools are translated.
+
+// The registers for temporaries with their 32 and 64 bit names. None of
+// them is in use when the runtime is called, which may change all of them.
+const REGS: [(&str, &str); 8] = [
+    ("%ecx", "%rcx"),
+    ("%esi", "%rsi"),
+    ("%edi", "%rdi"),
+    ("%r8d", "%r8"),
+    ("%r9d", "%r9"),
+    ("%r10d", "%r10"),
+    ("%r11d", "%r11"),
+    ("%edx", "%rdx"),
+];
+
+pub fn compile(stmt: &Stmt, procs: &[Proc]) -> Result<String, String> {
+    let mut g = Gen {
+        procs,
+        vars: Vars::new(procs, &[], "x86-64"),
+        params: 0,
+        out: String::new(),
+        free: (0..REGS.len()).rev().collect(),
+        used: Vec::new(),
+        loops: Vec::new(),
+        ret: String::new(),
+        labels: 0,
+        strings: Vec::new(),
+        overflows: Vec::new(),
+    };
+    let mut out = String::from(START);
+    out.push_str(&g.func("imp_main", &[], stmt, None)?);
+    for p in procs {
+        out.push_str(&g.func(&format!("f_{}", p.name), &p.params, &p.body, Some(p))?);
+    }
+    out.push_str(RUNTIME);
+    for (k, x) in g.strings.iter().enumerate() {
+        out.push_str(&format!(".Lstr{}:\n    .ascii {}\n", k, quote(x)));
+    }
+    Ok(out)
+}
+
+const START: &str = "    .text
+    .globl _start
+_start:
+    movq (%rsp), %rax
+    cmpq $1, %rax
+    jle 1f
+    leaq 16(%rsp), %rax
+    movq %rax, imp_args(%rip)
+1:  call imp_main
+    xorl %edi, %edi
+    jmp imp_exit
+
+";
+
+struct Gen<'a> {
+    procs: &'a [Proc],
+    vars: Vars<'a>,
+    // the number of parameters of the procedure
+    params: usize,
+    out: String,
+    // the registers which are not in use, the next one last
+    free: Vec<usize>,
+    // the registers in use in the order they were taken
+    used: Vec<usize>,
+    // the labels of continue and break of the enclosing loops
+    loops: Vec<(String, String)>,
+    // the label of the code which returns from the procedure
+    ret: String,
+    labels: usize,
+    // the messages of the program, .Lstr0, .Lstr1, ..
+    strings: Vec<String>,
+    // the code reporting the overflows of the procedure
+    overflows: Vec<String>,
+}
+
+impl Gen<'_> {
+    // The code of a procedure, of the main program if p is None
+    fn func(
+        &mut self,
+        name: &str,
+        params: &[(String, Type)],
+        body: &Stmt,
+        p: Option<&Proc>,
+    ) -> Result<String, String> {
+        self.vars = Vars::new(self.procs, params, "x86-64");
+        self.params = params.len();
+        for (_, ty) in params {
+            self.vars.check(ty)?;
+        }
+        self.ret = self.label();
+        if let Some(p) = p {
+            self.vars.check(&p.ret)?;
+            self.ins("cmpl $1000, imp_depth(%rip)");
+            self.ins("je imp_stack_overflow");
+            self.ins("incl imp_depth(%rip)");
+        }
+        self.stmt(body)?;
+        if let Some(p) = p {
+            let msg = format!("procedure {} ended without return\n", p.name);
+            self.message(&msg);
+            self.ins("jmp imp_error");
+        }
+        let body = std::mem::take(&mut self.out);
+        self.out.push_str(&format!("{}:\n", name));
+        self.ins("pushq %rbp");
+        self.ins("movq %rsp, %rbp");
+        let locals = self.vars.locals().len();
+        if locals > 0 {
+            self.ins(&format!("subq ${}, %rsp", 8 * locals));
+        }
+        for k in 0..locals {
+            self.ins(&format!("movq $0, {}(%rbp)", -8 * (k as i64 + 1)));
+        }
+        self.out.push_str(&body);
+        self.out.push_str(&format!("{}:\n", self.ret));
+        if p.is_some() {
+            self.ins("decl imp_depth(%rip)");
+        }
+        self.ins("leave");
+        self.ins("ret");
+        for x in std::mem::take(&mut self.overflows) {
+            self.out.push_str(&x);
+        }
+        self.out.push('\n');
+        Ok(std::mem::take(&mut self.out))
+    }
+    fn ins(&mut self, x: &str) {
+        self.out.push_str("    ");
+        self.out.push_str(x);
+        self.out.push('\n');
+    }
+    fn label(&mut self) -> String {
+        self.labels += 1;
+        format!(".L{}", self.labels)
+    }
+    fn place(&mut self, label: &str) {
+        self.out.push_str(&format!("{}:\n", label));
+    }
+    // Loads the address and the length of the message x into %rsi and %edx
+    fn message(&mut self, x: &str) {
+        self.strings.push(x.to_string());
+        self.ins(&format!("leaq .Lstr{}(%rip), %rsi", self.strings.len() - 1));
+        self.ins(&format!("movl ${}, %edx", x.len()));
+    }
+    // The stack slot of x: parameters are above the return address, the
+    // last one first, the other variables below the saved %rbp
+    fn slot(&self, x: &str) -> Result<String, String> {
+        let k = self
+            .vars
+            .position(x)
+            .ok_or_else(|| format!("{} is not declared", x))?;
+        if k < self.params {
+            Ok(format!("{}(%rbp)", 16 + 8 * (self.params - 1 - k)))
+        } else {
+            Ok(format!("{}(%rbp)", -8 * (k - self.params + 1) as i64))
+        }
+    }
+    fn take(&mut self) -> usize {
+        let r = self.free.pop().expect("a free register");
+        self.used.push(r);
+        r
+    }
+    fn give(&mut self, r: usize) {
+        self.used.retain(|x| *x != r);
+        self.free.push(r);
+    }
+    fn stmt(&mut self, stmt: &Stmt) -> Result<(), String> {
+        match stmt {
+            Stmt::Skip | Stmt::Assume { .. } => {}
+            Stmt::Seq { first, second } => {
+                self.stmt(first)?;
+                self.stmt(second)?;
+            }
+            Stmt::Decl { lhs, ty, rhs } => {
+                let ty = match ty {
+                    Some(ty) => ty.clone(),
+                    None => self.vars.ty(rhs)?,
+                };
+                let r = self.exp(rhs)?;
+                self.vars.declare(lhs, ty)?;
+                self.store(r, lhs)?;
+            }
+            Stmt::Assign { lhs, rhs } => {
+                let r = self.exp(rhs)?;
+                self.store(r, lhs)?;
+            }
+            Stmt::Read { lhs, ty } => {
+                self.vars.declare(lhs, ty.clone())?;
+                let f = if *ty == Type::TyBool { "bool" } else { "int" };
+                self.ins(&format!("call imp_read_{}", f));
+                let slot = self.slot(lhs)?;
+                self.ins(&format!("movl %eax, {}", slot));
+            }
+            Stmt::Print { print_exp } => {
+                let f = if self.vars.ty(print_exp)? == Type::TyBool {
+                    "bool"
+                } else {
+                    "int"
+                };
+                let r = self.exp(print_exp)?;
+                self.ins(&format!("movl {}, %edi", REGS[r].0));
+                self.give(r);
+                self.ins(&format!("call imp_print_{}", f));
+            }
+            Stmt::Assert { cond } => {
+                let r = self.exp(cond)?;
+                self.ins(&format!("testl {0}, {0}", REGS[r].0));
+                self.give(r);
+                let ok = self.label();
+                self.ins(&format!("jnz {}", ok));
+                self.assert_failed(cond)?;
+                self.place(&ok);
+            }
+            Stmt::Return { exp } => {
+                let r = self.exp(exp)?;
+                self.ins(&format!("movl {}, %eax", REGS[r].0));
+                self.give(r);
+                let ret = self.ret.clone();
+                self.ins(&format!("jmp {}", ret));
+            }
+            Stmt::Break | Stmt::Continue => {
+                let (cont, exit) = self.loops.last().ok_or("break outside a loop")?;
+                let target = if let Stmt::Break = stmt { exit } else { cont };
+                let x = format!("jmp {}", target);
+                self.ins(&x);
+            }
+            Stmt::IfThenElse {
+                cond,
+                then_stmt,
+                else_stmt,
+            } => {
+                let other = self.label();
+                let end = self.label();
+                self.branch(cond, &other)?;
+                self.stmt(then_stmt)?;
+                self.ins(&format!("jmp {}", end));
+                self.place(&other);
+                self.stmt(else_stmt)?;
+                self.place(&end);
+            }
+            Stmt::While { cond, stmt, .. } => {
+                let head = self.label();
+                let cont = self.label();
+                let exit = self.label();
+                self.place(&head);
+                self.branch(cond, &exit)?;
+                self.loops.push((cont.clone(), exit.clone()));
+                self.stmt(stmt)?;
+                self.loops.pop();
+                // the body of a for loop places cont before its step
+                if !matches!(**stmt, Stmt::Step { .. }) {
+                    self.place(&cont);
+                }
+                self.ins(&format!("jmp {}", head));
+                self.place(&exit);
+            }
+            Stmt::Step { body, step } => {
+                self.stmt(body)?;
+                let (cont, _) = self.loops.last().ok_or("step outside a loop")?;
+                let cont = cont.clone();
+                self.place(&cont);
+                self.stmt(step)?;
+            }
+            Stmt::ArrayAssign { .. } | Stmt::FieldAssign { .. } => {
+                return Err(self.vars.unsupported(&stmt.pretty()))
+            }
+        }
+        Ok(())
+    }
+    fn store(&mut self, r: usize, x: &str) -> Result<(), String> {
+        let slot = self.slot(x)?;
+        self.ins(&format!("movl {}, {}", REGS[r].0, slot));
+        self.give(r);
+        Ok(())
+    }
+    // Jumps to target if cond is false
+    fn branch(&mut self, cond: &Exp, target: &str) -> Result<(), String> {
+        let r = self.exp(cond)?;
+        self.ins(&format!("testl {0}, {0}", REGS[r].0));
+        self.give(r);
+        self.ins(&format!("jz {}", target));
+        Ok(())
+    }
+    // Writes the message of exec to stderr and exits with 1
+    fn assert_failed(&mut self, cond: &Exp) -> Result<(), String> {
+        let mut text = format!("assertion failed: {}", cond.pretty());
+        let vars: Vec<String> = cond
+            .vars()
+            .into_iter()
+            .filter(|x| self.vars.contains(x))
+            .collect();
+        for (k, x) in vars.iter().enumerate() {
+            text.push_str(if k == 0 { " with " } else { ", " });
+            text.push_str(&format!("{} = ", x));
+            self.message(&text);
+            self.ins("movl $2, %edi");
+            self.ins("call imp_write_str");
+            let f = if self.vars.ty(&Exp::Var { name: x.clone() })? == Type::TyBool {
+                "bool"
+            } else {
+                "int"
+            };
+            let slot = self.slot(x)?;
+            self.ins("movl $2, %edi");
+            self.ins(&format!("movl {}, %esi", slot));
+            self.ins(&format!("call imp_write_{}", f));
+            text.clear();
+        }
+        text.push('\n');
+        self.message(&text);
+        self.ins("jmp imp_error");
+        Ok(())
+    }
+    // The register with the value of e
+    fn exp(&mut self, e: &Exp) -> Result<usize, String> {
+        if let Some((op, left, right)) = e.binary() {
+            let l = self.exp(left)?;
+            let spilled = self.free.is_empty();
+            if spilled {
+                self.ins(&format!("pushq {}", REGS[l].1));
+                self.give(l);
+            }
+            let r = self.exp(right)?;
+            if !spilled {
+                self.op(op, REGS[l].0, REGS[r].0);
+                self.ins(&format!("movl %eax, {}", REGS[l].0));
+                self.give(r);
+                return Ok(l);
+            }
+            self.op(op, "(%rsp)", REGS[r].0);
+            self.ins("addq $8, %rsp");
+            self.ins(&format!("movl %eax, {}", REGS[r].0));
+            return Ok(r);
+        }
+        match e {
+            Exp::Num { val } => {
+                let r = self.take();
+                self.ins(&format!("movl ${}, {}", val, REGS[r].0));
+                Ok(r)
+            }
+            Exp::Bool { val } => {
+                let r = self.take();
+                self.ins(&format!("movl ${}, {}", *val as i32, REGS[r].0));
+                Ok(r)
+            }
+            Exp::Var { name } => {
+                let slot = self.slot(name)?;
+                let r = self.take();
+                self.ins(&format!("movl {}, {}", slot, REGS[r].0));
+                Ok(r)
+            }
+            Exp::Grp { exp } => self.exp(exp),
+            Exp::Neg { exp } => {
+                let r = self.exp(exp)?;
+                self.ins(&format!("xorl $1, {}", REGS[r].0));
+                Ok(r)
+            }
+            Exp::Call { name, args } => {
+                self.vars.ty(e)?;
+                let saved = self.used.clone();
+                for r in &saved {
+                    self.ins(&format!("pushq {}", REGS[*r].1));
+                }
+                for a in args {
+                    let r = self.exp(a)?;
+                    self.ins(&format!("pushq {}", REGS[r].1));
+                    self.give(r);
+                }
+                self.ins(&format!("call f_{}", name));
+                if !args.is_empty() {
+                    self.ins(&format!("addq ${}, %rsp", 8 * args.len()));
+                }
+                for r in saved.iter().rev() {
+                    self.ins(&format!("popq {}", REGS[*r].1));
+                }
+                let r = self.take();
+                self.ins(&format!("movl %eax, {}", REGS[r].0));
+                Ok(r)
+            }
+            _ => Err(self.vars.unsupported(&e.pretty())),
+        }
+    }
+    // %eax = left op right, left is a register or on the stack. An
+    // overflow jumps to code after the procedure which reports the operands
+    fn op(&mut self, op: BinOp, left: &str, right: &str) {
+        self.ins(&format!("movl {}, %eax", left));
+        let ins = match op {
+            BinOp::Plus => "addl",
+            BinOp::Mult => "imull",
+            BinOp::And => "andl",
+            BinOp::Or => "orl",
+            BinOp::Equ | BinOp::Less => {
+                self.ins(&format!("cmpl {}, %eax", right));
+                let set = if op == BinOp::Equ { "sete" } else { "setl" };
+                self.ins(&format!("{} %al", set));
+                self.ins("movzbl %al, %eax");
+                return;
+            }
+        };
+        self.ins(&format!("{} {}, %eax", ins, right));
+        if let BinOp::Plus | BinOp::Mult = op {
+            let label = self.label();
+            self.ins(&format!("jo {}", label));
+            // the operands may be in %edi and %edx
+            self.overflows.push(format!(
+                "{}:\n    movl {}, %eax\n    movl {}, %edi\n    movl %eax, %edx\n    movl ${}, %esi\n    jmp imp_arith_overflow\n",
+                label,
+                right,
+                left,
+                op.symbol().as_bytes()[0]
+            ));
+        }
+    }
+}
+
+const RUNTIME: &str = r#"# write(%edi, %rsi, %edx)
+imp_write_str:
+    movl %edx, %edx
+    movl $1, %eax
+    syscall
+    ret
+
+# writes the int %esi in decimal to the file %edi
+imp_write_int:
+    pushq %rbp
+    movq %rsp, %rbp
+    subq $32, %rsp
+    movl %edi, %r8d
+    movslq %esi, %rax
+    xorl %r9d, %r9d
+    testq %rax, %rax
+    jns 1f
+    negq %rax
+    movl $1, %r9d
+1:  movq %rbp, %rsi
+    movl $10, %ecx
+2:  xorl %edx, %edx
+    divq %rcx
+    addb $48, %dl
+    decq %rsi
+    movb %dl, (%rsi)
+    testq %rax, %rax
+    jnz 2b
+    testl %r9d, %r9d
+    jz 3f
+    decq %rsi
+    movb $45, (%rsi)
+3:  movq %rbp, %rdx
+    subq %rsi, %rdx
+    movl %r8d, %edi
+    call imp_write_str
+    leave
+    ret
+
+# writes the bool %esi to the file %edi
+imp_write_bool:
+    testl %esi, %esi
+    jz 1f
+    leaq imp_true(%rip), %rsi
+    movl $4, %edx
+    jmp imp_write_str
+1:  leaq imp_false(%rip), %rsi
+    movl $5, %edx
+    jmp imp_write_str
+
+imp_print_int:
+    pushq %rdi
+    movl $1, %edi
+    leaq imp_ouput(%rip), %rsi
+    movl $6, %edx
+    call imp_write_str
+    popq %rsi
+    movl $1, %edi
+    call imp_write_int
+    jmp imp_print_end
+
+imp_print_bool:
+    pushq %rdi
+    movl $1, %edi
+    leaq imp_output(%rip), %rsi
+    movl $7, %edx
+    call imp_write_str
+    popq %rsi
+    movl $1, %edi
+    call imp_write_bool
+imp_print_end:
+    movl $1, %edi
+    leaq imp_newline(%rip), %rsi
+    movl $1, %edx
+    jmp imp_write_str
+
+imp_exit:
+    movl $60, %eax
+    syscall
+
+# writes the message %rsi of length %edx to stderr and exits with 1
+imp_error:
+    movl $2, %edi
+    call imp_write_str
+    movl $1, %edi
+    jmp imp_exit
+
+# integer overflow in %edi op %edx, the operator is the byte %esi
+imp_arith_overflow:
+    pushq %rdx
+    pushq %rsi
+    pushq %rdi
+    movl $2, %edi
+    leaq imp_arith(%rip), %rsi
+    movl $(imp_arith_end - imp_arith), %edx
+    call imp_write_str
+    movl $2, %edi
+    movl (%rsp), %esi
+    call imp_write_int
+    movl $2, %edi
+    leaq imp_space(%rip), %rsi
+    movl $1, %edx
+    call imp_write_str
+    movl $2, %edi
+    leaq 8(%rsp), %rsi
+    movl $1, %edx
+    call imp_write_str
+    movl $2, %edi
+    leaq imp_space(%rip), %rsi
+    movl $1, %edx
+    call imp_write_str
+    movl $2, %edi
+    movl 16(%rsp), %esi
+    call imp_write_int
+    leaq imp_newline(%rip), %rsi
+    movl $1, %edx
+    jmp imp_error
+
+imp_stack_overflow:
+    leaq imp_overflow(%rip), %rsi
+    movl $(imp_overflow_end - imp_overflow), %edx
+    jmp imp_error
+
+imp_no_input:
+    leaq imp_exhausted(%rip), %rsi
+    movl $(imp_exhausted_end - imp_exhausted), %edx
+    jmp imp_error
+
+# the next argument or line of stdin without white space around it in %rax
+imp_item:
+    movq imp_args(%rip), %rcx
+    testq %rcx, %rcx
+    jz 1f
+    movq (%rcx), %rax
+    testq %rax, %rax
+    jz imp_no_input
+    addq $8, %rcx
+    movq %rcx, imp_args(%rip)
+    jmp 5f
+1:  xorl %r8d, %r8d
+    xorl %r9d, %r9d
+2:  leaq imp_line(%rip), %rsi
+    addq %r8, %rsi
+    xorl %edi, %edi
+    movl $1, %edx
+    xorl %eax, %eax
+    syscall
+    testq %rax, %rax
+    jle 3f
+    movl $1, %r9d
+    leaq imp_line(%rip), %rsi
+    cmpb $10, (%rsi,%r8)
+    je 4f
+    incq %r8
+    cmpq $4095, %r8
+    jb 2b
+    jmp 4f
+3:  testl %r9d, %r9d
+    jz imp_no_input
+4:  leaq imp_line(%rip), %rax
+    movb $0, (%rax,%r8)
+5:  movzbl (%rax), %ecx
+    cmpl $32, %ecx
+    je 6f
+    subl $9, %ecx
+    cmpl $4, %ecx
+    ja 7f
+6:  incq %rax
+    jmp 5b
+7:  movq %rax, %rdx
+8:  cmpb $0, (%rdx)
+    je 9f
+    incq %rdx
+    jmp 8b
+9:  cmpq %rax, %rdx
+    je 11f
+    movzbl -1(%rdx), %ecx
+    cmpl $32, %ecx
+    je 10f
+    subl $9, %ecx
+    cmpl $4, %ecx
+    ja 11f
+10: decq %rdx
+    movb $0, (%rdx)
+    jmp 9b
+11: ret
+
+imp_read_int:
+    call imp_item
+    movq %rax, %rsi
+    movq %rax, %rdi
+    xorl %r8d, %r8d
+    movzbl (%rdi), %ecx
+    cmpl $43, %ecx
+    je 1f
+    cmpl $45, %ecx
+    jne 2f
+    movl $1, %r8d
+1:  incq %rdi
+2:  movzbl (%rdi), %ecx
+    subl $48, %ecx
+    cmpl $9, %ecx
+    ja imp_bad_int
+    xorl %eax, %eax
+    movabsq $2147483648, %r9
+3:  imulq $10, %rax
+    addq %rcx, %rax
+    cmpq %r9, %rax
+    ja imp_bad_int
+    incq %rdi
+    movzbl (%rdi), %ecx
+    subl $48, %ecx
+    cmpl $9, %ecx
+    jbe 3b
+    cmpb $0, (%rdi)
+    jne imp_bad_int
+    testl %r8d, %r8d
+    jz 4f
+    negq %rax
+    ret
+4:  cmpq %r9, %rax
+    je imp_bad_int
+    ret
+
+imp_read_bool:
+    call imp_item
+    movq %rax, %rsi
+    leaq imp_true(%rip), %rdi
+    movl $4, %ecx
+    call imp_is
+    testl %eax, %eax
+    jnz 1f
+    leaq imp_false(%rip), %rdi
+    movl $5, %ecx
+    call imp_is
+    testl %eax, %eax
+    jz imp_bad_bool
+    ret
+1:  movl $1, %eax
+    ret
+
+# whether the string %rsi is the %ecx bytes at %rdi
+imp_is:
+    xorl %edx, %edx
+1:  movb (%rdi,%rdx), %al
+    cmpb %al, (%rsi,%rdx)
+    jne 2f
+    incl %edx
+    cmpl %ecx, %edx
+    jb 1b
+    cmpb $0, (%rsi,%rdx)
+    jne 2f
+    movl $1, %eax
+    ret
+2:  xorl %eax, %eax
+    ret
+
+imp_bad_int:
+    leaq imp_int(%rip), %rdx
+    movl $3, %ecx
+    jmp imp_bad_input
+
+imp_bad_bool:
+    leaq imp_bool(%rip), %rdx
+    movl $4, %ecx
+
+# the item %rsi is no value of the type named by the %ecx bytes at %rdx
+imp_bad_input:
+    pushq %rcx
+    pushq %rdx
+    pushq %rsi
+    movl $2, %edi
+    leaq imp_cannot(%rip), %rsi
+    movl $(imp_cannot_end - imp_cannot), %edx
+    call imp_write_str
+    movq (%rsp), %rsi
+    movq %rsi, %rdx
+1:  cmpb $0, (%rdx)
+    je 2f
+    incq %rdx
+    jmp 1b
+2:  subq %rsi, %rdx
+    movl $2, %edi
+    call imp_write_str
+    movl $2, %edi
+    leaq imp_as(%rip), %rsi
+    movl $(imp_as_end - imp_as), %edx
+    call imp_write_str
+    movl $2, %edi
+    movq 8(%rsp), %rsi
+    movq 16(%rsp), %rdx
+    call imp_write_str
+    movl $2, %edi
+    leaq imp_newline(%rip), %rsi
+    movl $1, %edx
+    call imp_write_str
+    movl $1, %edi
+    jmp imp_exit
+
+    .bss
+    .p2align 3
+imp_args:
+    .zero 8
+imp_depth:
+    .zero 4
+imp_line:
+    .zero 4096
+
+    .section .rodata
+imp_ouput:
+    .ascii "Ouput "
+imp_output:
+    .ascii "Output "
+imp_true:
+    .ascii "true"
+imp_false:
+    .ascii "false"
+imp_newline:
+    .ascii "\n"
+imp_space:
+    .ascii " "
+imp_int:
+    .ascii "int"
+imp_bool:
+    .ascii "bool"
+imp_arith:
+    .ascii "integer overflow in "
+imp_arith_end:
+imp_overflow:
+    .ascii "stack overflow after 1000 nested calls\n"
+imp_overflow_end:
+imp_exhausted:
+    .ascii "no more input to read\n"
+imp_exhausted_end:
+imp_cannot:
+    .ascii "cannot read \""
+imp_cannot_end:
+imp_as:
+    .ascii "\" as "
+imp_as_end:
+"#;
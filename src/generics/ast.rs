@@ -7,6 +7,12 @@ pub fn boolean(x: bool) -> Box<Bool> {
     let y: Bool = x;
     return Box::new(y);
 }
+pub fn string(x: &str) -> Box<Str> {
+    Box::new(Str { val: x.to_string() })
+}
+pub fn len<T: Exp>(exp: Box<T>) -> Box<Len<T>> {
+    Box::new(Len { exp })
+}
 pub fn mult<T1: Exp, T2: Exp>(left: Box<T1>, right: Box<T2>) -> Box<Mult<T1, T2>> {
     Box::new(Mult { left, right })
 }
@@ -58,6 +64,26 @@ pub fn ifthenelse<T1: Exp, T2: Stmt, T3: Stmt>(
 pub fn _while<T1: Exp, T2: Stmt>(cond: Box<T1>, stmt: Box<T2>) -> Box<While<T1, T2>> {
     Box::new(While { cond, stmt })
 }
+// for init; cond; step { stmt } is init; while cond { stmt; step }, where
+// step also runs after continue
+pub type For<T1, T2, T3, T4> = Seq<T1, While<T2, Step<T4, T3>>>;
+pub fn _for<T1: Stmt, T2: Exp, T3: Stmt, T4: Stmt>(
+    init: Box<T1>,
+    cond: Box<T2>,
+    step: Box<T3>,
+    stmt: Box<T4>,
+) -> Box<For<T1, T2, T3, T4>> {
+    seq(init, _while(cond, Box::new(Step { body: stmt, step })))
+}
+pub fn _break() -> Box<Break> {
+    Box::new(Break)
+}
+pub fn _continue() -> Box<Continue> {
+    Box::new(Continue)
+}
 pub fn print<T: Exp>(print_exp: Box<T>) -> Box<Print<T>> {
     Box::new(Print { print_exp })
 }
+pub fn assert<T: Exp>(cond: Box<T>) -> Box<Assert<T>> {
+    Box::new(Assert { cond })
+}
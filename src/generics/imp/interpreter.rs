@@ -4,15 +4,17 @@ impl<T1: Stmt, T2: Stmt> Stmt for Seq<T1, T2> {
         let x = self.first.pretty() + "; " + &self.second.pretty();
         x
     }
-    fn eval(&self, s: &mut ValState) {
-        self.first.eval(s);
-        self.second.eval(s);
+    fn exec(&self, s: &mut ValState, fuel: &mut Fuel) -> Result<Flow, RuntimeError> {
+        match self.first.exec(s, fuel)? {
+            Flow::Normal => self.second.exec(s, fuel),
+            flow => Ok(flow),
+        }
     }
-    fn check(&self, t: &mut TyState) -> bool {
-        if !self.first.check(t) {
+    fn check_in(&self, t: &mut TyState, in_loop: bool) -> bool {
+        if !self.first.check_in(t, in_loop) {
             return false;
         }
-        self.second.check(t)
+        self.second.check_in(t, in_loop)
     }
 }
 
@@ -24,11 +26,13 @@ impl<T: Exp> Stmt for Decl<T> {
         x.push_str(&self.rhs.pretty());
         x
     }
-    fn eval(&self, s: &mut ValState) {
+    fn exec(&self, s: &mut ValState, fuel: &mut Fuel) -> Result<Flow, RuntimeError> {
+        fuel.tick()?;
         let x = self.rhs.eval(s);
         s.insert(self.lhs.clone(), x);
+        Ok(Flow::Normal)
     }
-    fn check(&self, t: &mut TyState) -> bool {
+    fn check_in(&self, t: &mut TyState, _: bool) -> bool {
         let ty = self.rhs.infer(t);
         if let Type::TyIllTyped = ty {
             return false;
@@ -48,24 +52,26 @@ impl<T1: Exp, T2: Stmt, T3: Stmt> Stmt for IfThenElse<T1, T2, T3> {
         x.push_str(&mut self.else_stmt.pretty());
         x
     }
-    fn eval(&self, s: &mut ValState) {
+    fn exec(&self, s: &mut ValState, fuel: &mut Fuel) -> Result<Flow, RuntimeError> {
+        fuel.tick()?;
         let v = self.cond.eval(s);
         if let Kind::ValueBool = v.flag {
             if v.val_b.unwrap() {
-                self.then_stmt.eval(s);
+                return self.then_stmt.exec(s, fuel);
             } else {
-                self.else_stmt.eval(s);
+                return self.else_stmt.exec(s, fuel);
             }
         } else {
             println!("Error Parsing IfThenElse");
         }
+        Ok(Flow::Normal)
     }
-    fn check(&self, t: &mut TyState) -> bool {
+    fn check_in(&self, t: &mut TyState, in_loop: bool) -> bool {
         let ty = self.cond.infer(t);
         if let Type::TyIllTyped = ty {
             return false;
         }
-        self.then_stmt.check(t) && self.else_stmt.check(t)
+        self.then_stmt.check_in(t, in_loop) && self.else_stmt.check_in(t, in_loop)
     }
 }
 impl<T: Exp> Stmt for Assign<T> {
@@ -76,24 +82,26 @@ impl<T: Exp> Stmt for Assign<T> {
         x.push_str(&self.rhs.pretty());
         x
     }
-    fn eval(&self, s: &mut ValState) {
+    fn exec(&self, s: &mut ValState, fuel: &mut Fuel) -> Result<Flow, RuntimeError> {
+        fuel.tick()?;
         let v = self.lhs.eval(s);
         let val = self.rhs.eval(s);
         if let Kind::Undefined = val.flag {
             println!("value is undefined");
-            return;
+            return Ok(Flow::Normal);
         }
         if let Kind::Undefined = v.flag {
             println!("var is undefined");
-            return;
+            return Ok(Flow::Normal);
         }
-        if v.flag == val.flag {
+        if v.flag != val.flag {
             println!("var and value not the same Kind");
-            return;
+            return Ok(Flow::Normal);
         }
         *s.get_mut(&self.lhs).unwrap() = val;
+        Ok(Flow::Normal)
     }
-    fn check(&self, t: &mut TyState) -> bool {
+    fn check_in(&self, t: &mut TyState, _: bool) -> bool {
         let x = self.lhs.pretty();
         let y = self.rhs.infer(t);
         let w = t.get(&x).unwrap();
@@ -109,23 +117,28 @@ impl<T1: Exp, T2: Stmt> Stmt for While<T1, T2> {
         x.push_str(&self.stmt.pretty());
         x
     }
-    fn eval(&self, s: &mut ValState) {
+    fn exec(&self, s: &mut ValState, fuel: &mut Fuel) -> Result<Flow, RuntimeError> {
+        fuel.tick()?;
         let mut cond = self.cond.eval(s);
         if let Kind::ValueBool = cond.flag {
             while cond.val_b.unwrap() {
-                self.stmt.eval(s);
+                if self.stmt.exec(s, fuel)? == Flow::Break {
+                    break;
+                }
+                fuel.tick()?;
                 cond = self.cond.eval(s);
             }
         } else {
             println!("cond is no bool");
         }
+        Ok(Flow::Normal)
     }
-    fn check(&self, t: &mut TyState) -> bool {
+    fn check_in(&self, t: &mut TyState, _: bool) -> bool {
         let ty = self.cond.infer(t);
         if let Type::TyIllTyped = ty {
             return false;
         }
-        self.stmt.check(t)
+        self.stmt.check_in(t, true)
     }
 }
 impl<T: Exp> Stmt for Print<T> {
@@ -135,15 +148,18 @@ impl<T: Exp> Stmt for Print<T> {
         x.push_str(&self.print_exp.pretty());
         x
     }
-    fn eval(&self, s: &mut ValState) {
+    fn exec(&self, s: &mut ValState, fuel: &mut Fuel) -> Result<Flow, RuntimeError> {
+        fuel.tick()?;
         let v = self.print_exp.eval(s);
         match v.flag {
             Kind::ValueBool => println!("Output {}", v.val_b.unwrap()),
             Kind::ValueInt => println!("Ouput {}", v.val_i.unwrap()),
+            Kind::ValueStr => println!("Output {}", v.val_s.unwrap()),
             _ => println!("Output Undefined"),
         }
+        Ok(Flow::Normal)
     }
-    fn check(&self, t: &mut TyState) -> bool {
+    fn check_in(&self, t: &mut TyState, _: bool) -> bool {
         let ty = self.print_exp.infer(t);
         if let Type::TyIllTyped = ty {
             return false;
@@ -151,6 +167,78 @@ impl<T: Exp> Stmt for Print<T> {
         true
     }
 }
+impl<T: Exp> Stmt for Assert<T> {
+    fn pretty(&self) -> String {
+        let mut x = String::new();
+        x.push_str("assert ");
+        x.push_str(&self.cond.pretty());
+        x
+    }
+    fn exec(&self, s: &mut ValState, fuel: &mut Fuel) -> Result<Flow, RuntimeError> {
+        fuel.tick()?;
+        let v = self.cond.eval(s);
+        if v.val_b == Some(true) {
+            return Ok(Flow::Normal);
+        }
+        let mut xs = Vec::new();
+        self.cond.vars(&mut xs);
+        let bindings = xs
+            .into_iter()
+            .map(|x| {
+                let v = s
+                    .get(&x)
+                    .map_or("undeclared".to_string(), |v| v.to_string());
+                (x, v)
+            })
+            .collect();
+        Err(RuntimeError::AssertionFailed {
+            cond: self.cond.pretty(),
+            bindings,
+        })
+    }
+    fn check_in(&self, t: &mut TyState, _: bool) -> bool {
+        self.cond.infer(t) == Type::TyBool
+    }
+}
+
+impl<T1: Stmt, T2: Stmt> Stmt for Step<T1, T2> {
+    fn pretty(&self) -> String {
+        self.body.pretty() + "; " + &self.step.pretty()
+    }
+    fn exec(&self, s: &mut ValState, fuel: &mut Fuel) -> Result<Flow, RuntimeError> {
+        if self.body.exec(s, fuel)? == Flow::Break {
+            return Ok(Flow::Break);
+        }
+        self.step.exec(s, fuel)
+    }
+    fn check_in(&self, t: &mut TyState, in_loop: bool) -> bool {
+        self.body.check_in(t, in_loop) && self.step.check(t)
+    }
+}
+impl Stmt for Break {
+    fn pretty(&self) -> String {
+        "break".to_string()
+    }
+    fn exec(&self, _s: &mut ValState, fuel: &mut Fuel) -> Result<Flow, RuntimeError> {
+        fuel.tick()?;
+        Ok(Flow::Break)
+    }
+    fn check_in(&self, _t: &mut TyState, in_loop: bool) -> bool {
+        in_loop
+    }
+}
+impl Stmt for Continue {
+    fn pretty(&self) -> String {
+        "continue".to_string()
+    }
+    fn exec(&self, _s: &mut ValState, fuel: &mut Fuel) -> Result<Flow, RuntimeError> {
+        fuel.tick()?;
+        Ok(Flow::Continue)
+    }
+    fn check_in(&self, _t: &mut TyState, in_loop: bool) -> bool {
+        in_loop
+    }
+}
 
 // Exp
 impl Exp for Var {
@@ -162,6 +250,7 @@ impl Exp for Var {
         match x.flag {
             Kind::ValueInt => return Val::mk_int(&x.val_i.unwrap()),
             Kind::ValueBool => return Val::mk_bool(&x.val_b.unwrap()),
+            Kind::ValueStr => return Val::mk_str(x.val_s.as_ref().unwrap()),
             Kind::Undefined => return Val::mk_undefined(),
         }
     }
@@ -172,6 +261,11 @@ impl Exp for Var {
             return Type::TyIllTyped;
         }
     }
+    fn vars(&self, xs: &mut Vec<Var>) {
+        if !xs.contains(self) {
+            xs.push(self.clone());
+        }
+    }
 }
 
 impl Exp for Bool {
@@ -184,6 +278,19 @@ impl Exp for Bool {
     fn infer(&self, t: &mut TyState) -> Type {
         Type::TyBool
     }
+    fn vars(&self, _xs: &mut Vec<Var>) {}
+}
+impl Exp for Str {
+    fn pretty(&self) -> String {
+        quote(&self.val)
+    }
+    fn eval(&self, _s: &mut ValState) -> Val {
+        Val::mk_str(&self.val)
+    }
+    fn infer(&self, _t: &mut TyState) -> Type {
+        Type::TyStr
+    }
+    fn vars(&self, _xs: &mut Vec<Var>) {}
 }
 impl Exp for Num {
     fn pretty(&self) -> String {
@@ -195,6 +302,7 @@ impl Exp for Num {
     fn infer(&self, t: &mut TyState) -> Type {
         Type::TyInt
     }
+    fn vars(&self, _xs: &mut Vec<Var>) {}
 }
 impl<T1: Exp, T2: Exp> Exp for Mult<T1, T2> {
     fn pretty(&self) -> String {
@@ -222,6 +330,10 @@ impl<T1: Exp, T2: Exp> Exp for Mult<T1, T2> {
         }
         Type::TyIllTyped
     }
+    fn vars(&self, xs: &mut Vec<Var>) {
+        self.left.vars(xs);
+        self.right.vars(xs);
+    }
 }
 impl<T1: Exp, T2: Exp> Exp for Plus<T1, T2> {
     fn pretty(&self) -> String {
@@ -236,18 +348,29 @@ impl<T1: Exp, T2: Exp> Exp for Plus<T1, T2> {
     fn eval(&self, s: &mut ValState) -> Val {
         let n1 = self.left.eval(s);
         let n2 = self.right.eval(s);
-        if let (Kind::ValueInt, Kind::ValueInt) = (n1.flag, n2.flag) {
-            return Val::mk_int(&(n1.val_i.unwrap() + n2.val_i.unwrap()));
+        // + adds ints and concatenates strings
+        match (n1.flag, n2.flag) {
+            (Kind::ValueInt, Kind::ValueInt) => {
+                Val::mk_int(&(n1.val_i.unwrap() + n2.val_i.unwrap()))
+            }
+            (Kind::ValueStr, Kind::ValueStr) => {
+                Val::mk_str(&(n1.val_s.unwrap() + &n2.val_s.unwrap()))
+            }
+            _ => Val::mk_undefined(),
         }
-        Val::mk_undefined()
     }
     fn infer(&self, t: &mut TyState) -> Type {
         let t1 = self.left.infer(t);
         let t2 = self.right.infer(t);
-        if let (Type::TyInt, Type::TyInt) = (t1, t2) {
-            return Type::TyInt;
+        match (t1, t2) {
+            (Type::TyInt, Type::TyInt) => Type::TyInt,
+            (Type::TyStr, Type::TyStr) => Type::TyStr,
+            _ => Type::TyIllTyped,
         }
-        Type::TyIllTyped
+    }
+    fn vars(&self, xs: &mut Vec<Var>) {
+        self.left.vars(xs);
+        self.right.vars(xs);
     }
 }
 impl<T1: Exp, T2: Exp> Exp for And<T1, T2> {
@@ -276,6 +399,10 @@ impl<T1: Exp, T2: Exp> Exp for And<T1, T2> {
         }
         Type::TyIllTyped
     }
+    fn vars(&self, xs: &mut Vec<Var>) {
+        self.left.vars(xs);
+        self.right.vars(xs);
+    }
 }
 impl<T1: Exp, T2: Exp> Exp for Or<T1, T2> {
     fn pretty(&self) -> String {
@@ -303,6 +430,10 @@ impl<T1: Exp, T2: Exp> Exp for Or<T1, T2> {
         }
         Type::TyIllTyped
     }
+    fn vars(&self, xs: &mut Vec<Var>) {
+        self.left.vars(xs);
+        self.right.vars(xs);
+    }
 }
 impl<T1: Exp, T2: Exp> Exp for Equ<T1, T2> {
     fn pretty(&self) -> String {
@@ -318,24 +449,26 @@ impl<T1: Exp, T2: Exp> Exp for Equ<T1, T2> {
         let v1 = self.left.eval(s);
         let v2 = self.right.eval(s);
         match (v1.flag, v2.flag) {
-            (Kind::ValueInt, Kind::ValueInt) => {
-                return Val::mk_bool(&(v1.val_i.unwrap() == v2.val_i.unwrap()))
-            }
-            (Kind::ValueBool, Kind::ValueBool) => {
-                return Val::mk_bool(&(v1.val_b.unwrap() == v2.val_b.unwrap()))
-            }
-            _ => return Val::mk_undefined(),
+            (Kind::ValueInt, Kind::ValueInt) => Val::mk_bool(&(v1.val_i == v2.val_i)),
+            (Kind::ValueBool, Kind::ValueBool) => Val::mk_bool(&(v1.val_b == v2.val_b)),
+            (Kind::ValueStr, Kind::ValueStr) => Val::mk_bool(&(v1.val_s == v2.val_s)),
+            _ => Val::mk_undefined(),
         }
     }
     fn infer(&self, t: &mut TyState) -> Type {
         let t1 = self.left.infer(t);
         let t2 = self.right.infer(t);
         match (t1, t2) {
-            (Type::TyBool, Type::TyBool) => return Type::TyBool,
-            (Type::TyInt, Type::TyInt) => return Type::TyInt,
+            (Type::TyBool, Type::TyBool)
+            | (Type::TyInt, Type::TyInt)
+            | (Type::TyStr, Type::TyStr) => Type::TyBool,
             _ => Type::TyIllTyped,
         }
     }
+    fn vars(&self, xs: &mut Vec<Var>) {
+        self.left.vars(xs);
+        self.right.vars(xs);
+    }
 }
 
 impl<T1: Exp, T2: Exp> Exp for Less<T1, T2> {
@@ -351,18 +484,26 @@ impl<T1: Exp, T2: Exp> Exp for Less<T1, T2> {
     fn eval(&self, s: &mut ValState) -> Val {
         let n1 = self.left.eval(s);
         let n2 = self.right.eval(s);
-        if let (Kind::ValueInt, Kind::ValueInt) = (n1.flag, n2.flag) {
-            return Val::mk_bool(&(n1.val_i.unwrap() < n2.val_i.unwrap()));
+        match (n1.flag, n2.flag) {
+            (Kind::ValueInt, Kind::ValueInt) => {
+                Val::mk_bool(&(n1.val_i.unwrap() < n2.val_i.unwrap()))
+            }
+            // lexicographic order
+            (Kind::ValueStr, Kind::ValueStr) => Val::mk_bool(&(n1.val_s < n2.val_s)),
+            _ => Val::mk_undefined(),
         }
-        Val::mk_undefined()
     }
     fn infer(&self, t: &mut TyState) -> Type {
         let t1 = self.left.infer(t);
         let t2 = self.right.infer(t);
-        if let (Type::TyInt, Type::TyInt) = (t1, t2) {
-            return Type::TyBool;
+        match (t1, t2) {
+            (Type::TyInt, Type::TyInt) | (Type::TyStr, Type::TyStr) => Type::TyBool,
+            _ => Type::TyIllTyped,
         }
-        Type::TyIllTyped
+    }
+    fn vars(&self, xs: &mut Vec<Var>) {
+        self.left.vars(xs);
+        self.right.vars(xs);
     }
 }
 
@@ -389,6 +530,9 @@ impl<T: Exp> Exp for Neg<T> {
         }
         Type::TyIllTyped
     }
+    fn vars(&self, xs: &mut Vec<Var>) {
+        self.exp.vars(xs);
+    }
 }
 
 impl<T: Exp> Exp for Grp<T> {
@@ -404,6 +548,7 @@ impl<T: Exp> Exp for Grp<T> {
         match v.flag {
             Kind::ValueBool => Val::mk_bool(&(v.val_b.unwrap())),
             Kind::ValueInt => Val::mk_int(&(v.val_i.unwrap())),
+            Kind::ValueStr => Val::mk_str(v.val_s.as_ref().unwrap()),
             _ => Val::mk_undefined(),
         }
     }
@@ -412,7 +557,33 @@ impl<T: Exp> Exp for Grp<T> {
         match t {
             Type::TyBool => Type::TyBool,
             Type::TyInt => Type::TyInt,
+            Type::TyStr => Type::TyStr,
             _ => Type::TyIllTyped,
         }
     }
+    fn vars(&self, xs: &mut Vec<Var>) {
+        self.exp.vars(xs);
+    }
+}
+
+impl<T: Exp> Exp for Len<T> {
+    fn pretty(&self) -> String {
+        format!("len({})", self.exp.pretty())
+    }
+    fn eval(&self, s: &mut ValState) -> Val {
+        let v = self.exp.eval(s);
+        if let Kind::ValueStr = v.flag {
+            return Val::mk_int(&(v.val_s.unwrap().chars().count() as i32));
+        }
+        Val::mk_undefined()
+    }
+    fn infer(&self, t: &mut TyState) -> Type {
+        if let Type::TyStr = self.exp.infer(t) {
+            return Type::TyInt;
+        }
+        Type::TyIllTyped
+    }
+    fn vars(&self, xs: &mut Vec<Var>) {
+        self.exp.vars(xs);
+    }
 }
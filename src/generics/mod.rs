@@ -11,12 +11,14 @@ fn run_exp(e: Box<dyn Exp>) {
     println!("{}", e.eval(&mut s));
     println!("{}", e.infer(&mut t));
 }
-fn run_stmt(stmt: Box<dyn Stmt>) {
+fn run_stmt(stmt: Box<dyn Stmt>, mut fuel: Fuel) {
     let mut s = HashMap::<String, Val>::new();
     let mut t = HashMap::<String, Type>::new();
     println!("*******");
     println!("{}", stmt.pretty());
-    stmt.eval(&mut s);
+    if let Err(e) = stmt.exec(&mut s, &mut fuel) {
+        println!("error: {}", e);
+    }
     println!("state: {:?}", s);
     println!("type checker: {:?}", stmt.check(&mut t))
 }
@@ -34,8 +36,56 @@ pub fn run() {
     let ast = less(number(0), number(1));
     run_exp(ast);
     let ast_stmt = seq(
-        decl("x".to_string(), number(1)),
-        decl("y".to_string(), plus(number(6), variable("x".to_string()))),
+        seq(
+            decl("x".to_string(), number(1)),
+            decl("y".to_string(), plus(number(6), variable("x".to_string()))),
+        ),
+        assert(equal(variable("y".to_string()), number(7))),
     );
-    run_stmt(ast_stmt);
+    run_stmt(ast_stmt, Fuel::unlimited());
+    let ast_stmt = seq(
+        decl("x".to_string(), number(0)),
+        _while(
+            boolean(true),
+            assign("x".to_string(), plus(variable("x".to_string()), number(1))),
+        ),
+    );
+    run_stmt(ast_stmt, Fuel::limit(100));
+    let ast_stmt = seq(
+        seq(
+            decl("s".to_string(), plus(string("Hello, "), string("world"))),
+            print(variable("s".to_string())),
+        ),
+        assert(and(
+            equal(len(variable("s".to_string())), number(12)),
+            less(string("Hello"), variable("s".to_string())),
+        )),
+    );
+    run_stmt(ast_stmt, Fuel::unlimited());
+    // 1 + 3 + 5: even numbers are skipped with continue, 6 ends the loop
+    let i = || variable("i".to_string());
+    let ast_stmt = seq(
+        seq(
+            decl("s".to_string(), number(0)),
+            _for(
+                decl("i".to_string(), number(0)),
+                less(i(), number(10)),
+                assign("i".to_string(), plus(i(), number(1))),
+                ifthenelse(
+                    equal(i(), number(6)),
+                    _break(),
+                    ifthenelse(
+                        or(
+                            or(equal(i(), number(0)), equal(i(), number(2))),
+                            equal(i(), number(4)),
+                        ),
+                        _continue(),
+                        assign("s".to_string(), plus(variable("s".to_string()), i())),
+                    ),
+                ),
+            ),
+        ),
+        assert(equal(variable("s".to_string()), number(9))),
+    );
+    run_stmt(ast_stmt, Fuel::unlimited());
 }
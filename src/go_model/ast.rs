@@ -7,6 +7,12 @@ pub fn boolean(x: bool) -> Box<dyn Exp> {
     let y: Bool = x;
     return Box::new(y);
 }
+pub fn string(x: &str) -> Box<dyn Exp> {
+    Box::new(Str { val: x.to_string() })
+}
+pub fn len(exp: Box<dyn Exp>) -> Box<dyn Exp> {
+    Box::new(Len { exp })
+}
 pub fn mult(x: Box<dyn Exp>, y: Box<dyn Exp>) -> Box<dyn Exp> {
     Box::new(Mult { exp: [x, y] })
 }
@@ -59,6 +65,25 @@ pub fn ifthenelse(
 pub fn _while(cond: Box<dyn Exp>, stmt: Box<dyn Stmt>) -> Box<dyn Stmt> {
     Box::new(While { cond, stmt })
 }
+// for init; cond; step { stmt } is init; while cond { stmt; step }, where
+// step also runs after continue
+pub fn _for(
+    init: Box<dyn Stmt>,
+    cond: Box<dyn Exp>,
+    step: Box<dyn Stmt>,
+    stmt: Box<dyn Stmt>,
+) -> Box<dyn Stmt> {
+    seq(init, _while(cond, Box::new(Step { body: stmt, step })))
+}
+pub fn _break() -> Box<dyn Stmt> {
+    Box::new(Break)
+}
+pub fn _continue() -> Box<dyn Stmt> {
+    Box::new(Continue)
+}
 pub fn print(print_exp: Box<dyn Exp>) -> Box<dyn Stmt> {
     Box::new(Print { print_exp })
 }
+pub fn assert(cond: Box<dyn Exp>) -> Box<dyn Stmt> {
+    Box::new(Assert { cond })
+}
@@ -10,6 +10,7 @@ pub mod interpreter;
 pub enum Kind {
     ValueInt,
     ValueBool,
+    ValueStr,
     Undefined,
 }
 #[derive(Debug)]
@@ -17,6 +18,7 @@ pub struct Val {
     pub flag: Kind,
     pub val_i: Option<i32>,
     pub val_b: Option<bool>,
+    pub val_s: Option<String>,
 }
 impl Val {
     fn mk_int(x: &i32) -> Val {
@@ -24,6 +26,7 @@ impl Val {
             flag: Kind::ValueInt,
             val_i: Some(*x), // does it need to be cloned?
             val_b: None,
+            val_s: None,
         }
     }
     fn mk_bool(x: &bool) -> Val {
@@ -31,6 +34,15 @@ impl Val {
             flag: Kind::ValueBool,
             val_i: None,
             val_b: Some(*x),
+            val_s: None,
+        }
+    }
+    fn mk_str(x: &str) -> Val {
+        Val {
+            flag: Kind::ValueStr,
+            val_i: None,
+            val_b: None,
+            val_s: Some(x.to_string()),
         }
     }
     fn mk_undefined() -> Val {
@@ -38,6 +50,7 @@ impl Val {
             flag: Kind::Undefined,
             val_i: None,
             val_b: None,
+            val_s: None,
         }
     }
 }
@@ -51,6 +64,12 @@ fn show_val(v: Val) -> String {
             let x: Bool = v.val_b.unwrap();
             return x.pretty();
         }
+        Kind::ValueStr => {
+            let x = Str {
+                val: v.val_s.unwrap(),
+            };
+            return x.pretty();
+        }
         Kind::Undefined => return "undefined".to_string(),
     }
 }
@@ -59,6 +78,7 @@ impl Display for Val {
         match self.flag {
             Kind::ValueInt => write!(f, "{}", self.val_i.unwrap()),
             Kind::ValueBool => write!(f, "{}", self.val_b.unwrap()),
+            Kind::ValueStr => write!(f, "{}", quote(self.val_s.as_ref().unwrap())),
             Kind::Undefined => write!(f, "{}", "undefined"),
         }
     }
@@ -68,12 +88,14 @@ pub enum Type {
     TyIllTyped,
     TyInt,
     TyBool,
+    TyStr,
 }
 
 fn show_type(t: Type) -> String {
     match t {
         Type::TyInt => return "int".to_string(),
         Type::TyBool => return "bool".to_string(),
+        Type::TyStr => return "string".to_string(),
         Type::TyIllTyped => return "Illtyped".to_string(),
     }
 }
@@ -83,22 +105,112 @@ impl Display for Type {
         match self {
             Type::TyInt => write!(f, "{}", "int"),
             Type::TyBool => write!(f, "{}", "bool"),
+            Type::TyStr => write!(f, "string"),
             Type::TyIllTyped => write!(f, "{}", "illtyped"),
         }
     }
 }
+// A string literal with the escape sequences of the enums parser
+fn quote(x: &str) -> String {
+    let mut q = String::from("\"");
+    for c in x.chars() {
+        match c {
+            '"' => q.push_str("\\\""),
+            '\\' => q.push_str("\\\\"),
+            '\n' => q.push_str("\\n"),
+            '\t' => q.push_str("\\t"),
+            c => q.push(c),
+        }
+    }
+    q.push('"');
+    q
+}
 type ValState = HashMap<String, Val>;
 type TyState = HashMap<String, Type>;
+
+#[derive(Debug)]
+pub enum RuntimeError {
+    // the pretty printed condition and the values of its variables
+    AssertionFailed {
+        cond: String,
+        bindings: Vec<(String, String)>,
+    },
+    // the step limit of Fuel was reached
+    OutOfFuel {
+        steps: usize,
+    },
+}
+impl Display for RuntimeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RuntimeError::AssertionFailed { cond, bindings } => {
+                write!(f, "assertion failed: {}", cond)?;
+                let xs: Vec<String> = bindings
+                    .iter()
+                    .map(|(x, v)| format!("{} = {}", x, v))
+                    .collect();
+                if !xs.is_empty() {
+                    write!(f, " with {}", xs.join(", "))?;
+                }
+                Ok(())
+            }
+            RuntimeError::OutOfFuel { steps } => write!(f, "out of fuel after {} steps", steps),
+        }
+    }
+}
+
+// Limits the number of steps of an execution. A step is the execution of a
+// statement other than Seq or a further evaluation of a While condition.
+pub struct Fuel {
+    pub steps: usize,
+    pub max: Option<usize>,
+}
+impl Fuel {
+    pub fn unlimited() -> Fuel {
+        Fuel {
+            steps: 0,
+            max: None,
+        }
+    }
+    pub fn limit(max: usize) -> Fuel {
+        Fuel {
+            steps: 0,
+            max: Some(max),
+        }
+    }
+    fn tick(&mut self) -> Result<(), RuntimeError> {
+        if self.max.is_some_and(|max| self.steps >= max) {
+            return Err(RuntimeError::OutOfFuel { steps: self.steps });
+        }
+        self.steps += 1;
+        Ok(())
+    }
+}
 pub trait Exp {
     fn pretty(&self) -> String;
     fn eval(&self, s: &mut ValState) -> Val;
     fn infer(&self, t: &mut TyState) -> Type;
+    // adds the variables of the expression to xs
+    fn vars(&self, xs: &mut Vec<Var>);
 }
 
+// How the execution of a statement ends; Break and Continue skip the rest
+// of the enclosing While body
+#[derive(Debug, PartialEq)]
+pub enum Flow {
+    Normal,
+    Break,
+    Continue,
+}
 pub trait Stmt {
     fn pretty(&self) -> String;
-    fn eval(&self, s: &mut ValState);
-    fn check(&self, t: &mut TyState) -> bool;
+    // Runs the statement, taking a step of fuel for every statement
+    fn exec(&self, s: &mut ValState, fuel: &mut Fuel) -> Result<Flow, RuntimeError>;
+    fn check(&self, t: &mut TyState) -> bool {
+        self.check_in(t, false)
+    }
+    // check where in_loop tells whether break and continue are allowed
+    fn check_in(&self, t: &mut TyState, in_loop: bool) -> bool;
 }
 
 pub struct Seq {
@@ -121,12 +233,26 @@ pub struct While {
     pub cond: Box<dyn Exp>,
     pub stmt: Box<dyn Stmt>,
 }
+// The body of a for loop; step runs after it unless it ends with break
+pub struct Step {
+    pub body: Box<dyn Stmt>,
+    pub step: Box<dyn Stmt>,
+}
+pub struct Break;
+pub struct Continue;
 pub struct Print {
     pub print_exp: Box<dyn Exp>,
 }
+pub struct Assert {
+    pub cond: Box<dyn Exp>,
+}
 pub type Var = String;
 pub type Bool = bool;
 pub type Num = i32;
+// a String would be a Var
+pub struct Str {
+    pub val: String,
+}
 pub struct Plus {
     pub exp: [Box<dyn Exp>; 2],
 }
@@ -145,6 +271,10 @@ pub struct Or {
 pub struct Less {
     pub exp: [Box<dyn Exp>; 2],
 }
+// length of a string
+pub struct Len {
+    pub exp: Box<dyn Exp>,
+}
 pub struct Neg {
     pub exp: Box<dyn Exp>,
 }
@@ -1,7 +1,14 @@
 pub mod enums;
 pub mod generics;
 pub mod go_model;
-fn main() {
+use std::env;
+use std::fs;
+use std::process;
+
+const USAGE: &str =
+    "usage: imp [run [--fuel N] FILE [-- INPUT...] | trace FILE [-- INPUT...] | derive FILE | debug FILE [-- INPUT...] | coverage [--lcov OUT] FILE [-- INPUT...] | lint [--allow CODE]... FILE | lsp | types FILE | interval FILE | symbolic [--unroll N] FILE | smt [--unroll N] FILE | verify FILE | ir [--tac | --run] FILE [-- INPUT...] | compile --target (c | wat | x86-64) FILE | validate FILE.wat]";
+
+fn demo() {
     println!("*****GO_MODEL*****");
     go_model::run();
     println!("**********");
@@ -11,3 +18,451 @@ fn main() {
     enums::run();
     println!("**********");
 }
+
+fn fail(msg: &str) -> ! {
+    eprintln!("{}", msg);
+    process::exit(2);
+}
+
+fn read_program(path: &str) -> enums::parser::Program {
+    let src = match fs::read_to_string(path) {
+        Ok(src) => src,
+        Err(e) => fail(&format!("{}: {}", path, e)),
+    };
+    match enums::parser::parse(&src) {
+        Ok(p) => p,
+        Err(e) => fail(&format!("{}:{}", path, e)),
+    }
+}
+
+// Prints the type errors of the program as the language server reports
+// them and exits with 1 if there are any
+fn type_check(file: &str, p: &enums::parser::Program) {
+    let diags = enums::lsp::diagnostics(p);
+    for d in &diags {
+        let pos = d.span.start;
+        eprintln!("{}:{}:{}: {}", file, pos.line, pos.col, d.msg);
+    }
+    if !diags.is_empty() {
+        process::exit(1);
+    }
+}
+
+fn lint(args: &[String]) {
+    let mut allow = Vec::new();
+    let mut file = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--allow" => match args.next() {
+                Some(code) => allow.push(code.clone()),
+                None => fail(USAGE),
+            },
+            _ if file.is_none() => file = Some(arg.clone()),
+            _ => fail(USAGE),
+        }
+    }
+    let file = file.unwrap_or_else(|| fail(USAGE));
+    let p = read_program(&file);
+    let warnings = enums::lint::lint_program(&p, &allow);
+    for w in &warnings {
+        let pos = p.pos[w.stmt];
+        println!("{}:{}:{}: {}", file, pos.line, pos.col, w);
+    }
+    if !warnings.is_empty() {
+        process::exit(1);
+    }
+}
+
+fn types(args: &[String]) {
+    let file = match args {
+        [file] => file,
+        _ => fail(USAGE),
+    };
+    let p = read_program(file);
+    let inf = enums::hm::infer(&p);
+    for (i, x, s) in &inf.decls {
+        let pos = p.pos[*i];
+        println!("{}:{}: {}: {}", pos.line, pos.col, x, inf.show(s));
+    }
+    for e in &inf.errors {
+        let pos = p.pos[e.stmt];
+        match &e.exp {
+            Some(exp) => println!("{}:{}:{}: {} in {}", file, pos.line, pos.col, e.msg, exp),
+            None => println!("{}:{}:{}: {}", file, pos.line, pos.col, e.msg),
+        }
+    }
+    if !inf.errors.is_empty() {
+        process::exit(1);
+    }
+}
+
+fn interval(args: &[String]) {
+    let file = match args {
+        [file] => file,
+        _ => fail(USAGE),
+    };
+    let p = read_program(file);
+    let (a, end) = enums::interval::analyze(&p.stmt);
+    for (i, s) in a.points.iter().enumerate() {
+        let pos = p.pos[i];
+        println!(
+            "{}:{}: {}",
+            pos.line,
+            pos.col,
+            enums::interval::show_state(s)
+        );
+    }
+    println!("end: {}", enums::interval::show_state(&end));
+    for o in &a.overflows {
+        let pos = p.pos[o.stmt];
+        println!(
+            "{}:{}:{}: possible i32 overflow in {}",
+            file, pos.line, pos.col, o.exp
+        );
+    }
+}
+
+// Arguments of the form [--unroll N] FILE
+fn unroll_args(args: &[String]) -> (usize, &String) {
+    match args {
+        [file] => (8, file),
+        [flag, n, file] if flag == "--unroll" => match n.parse() {
+            Ok(n) => (n, file),
+            Err(_) => fail(USAGE),
+        },
+        _ => fail(USAGE),
+    }
+}
+
+fn symbolic(args: &[String]) {
+    let (unroll, file) = unroll_args(args);
+    let p = read_program(file);
+    let e = enums::symbolic::run(&p.stmt, unroll);
+    for (i, path) in e.paths.iter().enumerate() {
+        println!("path {}: {}", i + 1, enums::symbolic::show_cond(&path.cond));
+        match enums::symbolic::feasible(&path.cond) {
+            enums::solver::Outcome::Sat(m) => {
+                println!("  inputs: {}", enums::symbolic::show_model(&m))
+            }
+            _ => println!("  inputs: unknown"),
+        }
+        let prints: Vec<String> = path
+            .prints
+            .iter()
+            .map(|i| format!("{}:{}", p.pos[*i].line, p.pos[*i].col))
+            .collect();
+        println!("  prints: {}", prints.join(", "));
+    }
+    if e.cut > 0 {
+        println!("{} paths cut after {} loop unrollings", e.cut, unroll);
+    }
+    for i in enums::symbolic::prints(&p.stmt) {
+        let pos = p.pos[i];
+        match enums::symbolic::inputs_for(&e, i) {
+            Some(m) => println!(
+                "{}:{}:{}: print reached with {}",
+                file,
+                pos.line,
+                pos.col,
+                enums::symbolic::show_model(&m)
+            ),
+            None => println!("{}:{}:{}: print not reached", file, pos.line, pos.col),
+        }
+    }
+    for i in e.failed.keys() {
+        if let Some(m) = enums::symbolic::counterexample(&e, *i) {
+            let pos = p.pos[*i];
+            println!(
+                "{}:{}:{}: assertion fails with {}",
+                file,
+                pos.line,
+                pos.col,
+                enums::symbolic::show_model(&m)
+            );
+        }
+    }
+}
+
+fn smt(args: &[String]) {
+    let (unroll, file) = unroll_args(args);
+    let p = read_program(file);
+    print!(
+        "{}",
+        enums::smt::program(&p.stmt, &p.procs, &p.records, unroll)
+    );
+}
+
+fn verify(args: &[String]) {
+    let file = match args {
+        [file] => file,
+        _ => fail(USAGE),
+    };
+    let p = read_program(file);
+    let mut ok = true;
+    for vc in enums::wp::vcs(&p.stmt) {
+        let loc = match vc.stmt {
+            Some(i) => format!("{}:{}:{}", file, p.pos[i].line, p.pos[i].col),
+            None => file.to_string(),
+        };
+        let status = match enums::wp::discharge(&vc) {
+            enums::wp::Status::Trivial => "trivial".to_string(),
+            enums::wp::Status::Proved => "proved".to_string(),
+            enums::wp::Status::Failed(m) => {
+                ok = false;
+                format!("fails with {}", enums::symbolic::show_model(&m))
+            }
+            enums::wp::Status::Unknown => {
+                ok = false;
+                "unknown".to_string()
+            }
+        };
+        println!("{}: {}: {}", loc, vc.what, status);
+        println!("  {}", enums::wp::show_vc(&vc));
+    }
+    if !ok {
+        process::exit(1);
+    }
+}
+
+// Splits off the values after "--", which the program reads one after the
+// other. Without them it reads the lines of stdin.
+fn input_args(args: &[String]) -> (&[String], Option<Vec<String>>) {
+    match args.iter().position(|a| a == "--") {
+        Some(i) => (&args[..i], Some(args[i + 1..].to_vec())),
+        None => (args, None),
+    }
+}
+
+fn start_state(p: &enums::parser::Program, input: Option<Vec<String>>) -> enums::ValState {
+    let s = enums::ValState::with_procs(p.procs.clone());
+    match input {
+        Some(xs) => s.with_input(enums::input::Text::new(xs.into_iter())),
+        None => s.with_input(enums::input::stdin()),
+    }
+}
+
+fn run(args: &[String]) {
+    let (args, input) = input_args(args);
+    let (mut fuel, file) = match args {
+        [file] => (enums::Fuel::unlimited(), file),
+        [flag, n, file] if flag == "--fuel" => match n.parse() {
+            Ok(n) => (enums::Fuel::limit(n), file),
+            Err(_) => fail(USAGE),
+        },
+        _ => fail(USAGE),
+    };
+    let p = read_program(file);
+    type_check(file, &p);
+    let state = start_state(&p, input);
+    let (state, r) = enums::exec_fuel(&p.stmt, state, &mut fuel);
+    if let Err(e) = r {
+        eprintln!("{}: {}", file, e);
+        if let enums::RuntimeError::OutOfFuel { .. } = e {
+            let mut xs: Vec<String> = state
+                .iter()
+                .map(|(x, v)| format!("{} = {}", x, v))
+                .collect();
+            xs.sort();
+            eprintln!("state: {}", xs.join(", "));
+        }
+        process::exit(1);
+    }
+}
+
+// Prints the IR of a program in SSA form, with --tac before the conversion,
+// or runs it with --run
+fn ir(args: &[String]) {
+    let (args, input) = input_args(args);
+    let (flag, file) = match args {
+        [file] => ("", file),
+        [flag, file] if flag == "--tac" || flag == "--run" => (flag.as_str(), file),
+        _ => fail(USAGE),
+    };
+    let p = read_program(file);
+    type_check(file, &p);
+    let m = match enums::ir::lower(&p.stmt, &p.procs) {
+        Ok(m) => m,
+        Err(e) => fail(&format!("{}: {}", file, e)),
+    };
+    match flag {
+        "--tac" => print!("{}", m),
+        "--run" => {
+            let m = enums::ssa::module(&m);
+            let r = match input {
+                Some(xs) => enums::ir::run(&m, &mut enums::input::Text::new(xs.into_iter())),
+                None => enums::ir::run(&m, &mut enums::input::stdin()),
+            };
+            if let Err(e) = r {
+                eprintln!("{}: {}", file, e);
+                process::exit(1);
+            }
+        }
+        _ => print!("{}", enums::ssa::module(&m)),
+    }
+}
+
+// Prints the program translated for the target
+fn compile(args: &[String]) {
+    let (target, file) = match args {
+        [flag, target, file] if flag == "--target" => (target.as_str(), file),
+        _ => fail(USAGE),
+    };
+    let compile = match target {
+        "c" => enums::c::compile,
+        "wat" => enums::wat::compile,
+        "x86-64" => enums::x86::compile,
+        _ => fail(USAGE),
+    };
+    let p = read_program(file);
+    type_check(file, &p);
+    match compile(&p.stmt, &p.procs) {
+        Ok(out) => print!("{}", out),
+        Err(e) => fail(&format!("{}: {}", file, e)),
+    }
+}
+
+// Checks a WebAssembly module in the text format as emitted by compile
+fn validate(args: &[String]) {
+    let file = match args {
+        [file] => file,
+        _ => fail(USAGE),
+    };
+    let text = match fs::read_to_string(file) {
+        Ok(text) => text,
+        Err(e) => fail(&format!("{}: {}", file, e)),
+    };
+    if let Err(e) = enums::wat::validate(&text) {
+        eprintln!("{}: {}", file, e);
+        process::exit(1);
+    }
+}
+
+fn trace(args: &[String]) {
+    let (args, input) = input_args(args);
+    let file = match args {
+        [file] => file,
+        _ => fail(USAGE),
+    };
+    let p = read_program(file);
+    type_check(file, &p);
+    let state = start_state(&p, input);
+    let r = enums::smallstep::trace(&p.stmt, state, &mut |n, c| {
+        println!("{}: {}", n, enums::smallstep::show_config(c))
+    });
+    if let Err(e) = r {
+        eprintln!("{}: {}", file, e);
+        process::exit(1);
+    }
+}
+
+fn derive(args: &[String]) {
+    let file = match args {
+        [file] => file,
+        _ => fail(USAGE),
+    };
+    let p = read_program(file);
+    type_check(file, &p);
+    let (_, typing) = enums::derivation::type_tree(&p.stmt, &p.procs, &p.records);
+    let (_, eval) = match enums::derivation::eval_tree(&p.stmt, &p.procs) {
+        Ok(t) => t,
+        Err(e) => {
+            eprintln!("{}: {}", file, e);
+            process::exit(1);
+        }
+    };
+    print!("{}", enums::derivation::document(&[typing, eval]));
+}
+
+fn debug(args: &[String]) {
+    let (args, input) = input_args(args);
+    let file = match args {
+        [file] => file,
+        _ => fail(USAGE),
+    };
+    let p = read_program(file);
+    type_check(file, &p);
+    // stdin has the commands of the debugger, so the program only reads
+    // the values after "--"
+    let state = start_state(&p, Some(input.unwrap_or_default()));
+    let stdin = std::io::stdin();
+    match enums::debug::debug(&p.stmt, &p.pos, state, stdin.lock(), std::io::stdout()) {
+        Ok(()) | Err(enums::RuntimeError::Stopped) => {}
+        Err(e) => {
+            eprintln!("{}: {}", file, e);
+            process::exit(1);
+        }
+    }
+}
+
+fn coverage(args: &[String]) {
+    let (args, input) = input_args(args);
+    let (lcov, file) = match args {
+        [file] => (None, file),
+        [flag, out, file] if flag == "--lcov" => (Some(out), file),
+        _ => fail(USAGE),
+    };
+    let p = read_program(file);
+    type_check(file, &p);
+    let state = start_state(&p, input);
+    let (c, r) = enums::coverage::run(&p.stmt, &p.procs, state);
+    if let Err(e) = &r {
+        eprintln!("{}: {}", file, e);
+    }
+    let counts = c.counts(&p.stmt, &p.procs);
+    print!("{}", enums::coverage::report(&p.stmt, &p.procs, &counts));
+    if let Some(out) = lcov {
+        let info = enums::coverage::lcov(&p.stmt, &p.procs, &p.pos, file, &counts);
+        if let Err(e) = fs::write(out, info) {
+            fail(&format!("{}: {}", out, e));
+        }
+    }
+    if r.is_err() {
+        process::exit(1);
+    }
+}
+
+fn lsp(args: &[String]) {
+    if !args.is_empty() {
+        fail(USAGE);
+    }
+    let stdin = std::io::stdin();
+    process::exit(enums::lsp::serve(stdin.lock(), std::io::stdout()));
+}
+
+// Nested procedure calls recurse in the interpreter, the main thread's
+// stack is too small for the 1000 nested calls the interpreter allows
+const STACK_SIZE: usize = 64 << 20;
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let t = std::thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(move || command(&args))
+        .unwrap();
+    if t.join().is_err() {
+        process::exit(101);
+    }
+}
+
+fn command(args: &[String]) {
+    match args.first().map(|s| s.as_str()) {
+        None => demo(),
+        Some("run") => run(&args[1..]),
+        Some("trace") => trace(&args[1..]),
+        Some("derive") => derive(&args[1..]),
+        Some("debug") => debug(&args[1..]),
+        Some("coverage") => coverage(&args[1..]),
+        Some("lint") => lint(&args[1..]),
+        Some("lsp") => lsp(&args[1..]),
+        Some("types") => types(&args[1..]),
+        Some("interval") => interval(&args[1..]),
+        Some("symbolic") => symbolic(&args[1..]),
+        Some("smt") => smt(&args[1..]),
+        Some("verify") => verify(&args[1..]),
+        Some("ir") => ir(&args[1..]),
+        Some("compile") => compile(&args[1..]),
+        Some("validate") => validate(&args[1..]),
+        Some(_) => fail(USAGE),
+    }
+}
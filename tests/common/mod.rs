@@ -0,0 +1,43 @@
+// Helpers shared by the integration tests, which run the imp binary
+#![allow(dead_code)]
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+// The file or directory tests/name
+pub fn path(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join(name)
+}
+
+// The programs in dir, sorted by name
+pub fn imp_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<_> = fs::read_dir(dir)
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .filter(|p| p.extension().is_some_and(|e| e == "imp"))
+        .collect();
+    files.sort();
+    assert!(!files.is_empty(), "{}", dir.display());
+    files
+}
+
+// imp with the arguments args
+pub fn imp(args: &[&str]) -> Command {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_imp"));
+    cmd.args(args);
+    cmd
+}
+
+// Compares actual with the golden file, UPDATE_GOLDEN=1 rewrites it instead
+pub fn golden(golden: &Path, actual: &str) {
+    if env::var("UPDATE_GOLDEN").is_ok() {
+        fs::write(golden, actual).unwrap();
+        return;
+    }
+    let expected = fs::read_to_string(golden).unwrap();
+    assert_eq!(actual, expected, "{}", golden.display());
+}
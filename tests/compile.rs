@@ -0,0 +1,209 @@
+mod common;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+// The programs of tests/run and tests/compile, without those which do not
+// terminate
+fn programs() -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    for dir in [
+        "run",
+        "run/bounds",
+        "run/fail",
+        "run/input",
+        "run/overflow",
+        "run/stack",
+        "compile",
+    ] {
+        files.extend(common::imp_files(&common::path(dir)));
+    }
+    files.sort();
+    files
+}
+
+const INPUT: [&str; 5] = ["3", "4", "9", "5", "true"];
+
+fn run(file: &Path) -> Output {
+    common::imp(&["run"])
+        .arg(file)
+        .arg("--")
+        .args(INPUT)
+        .output()
+        .unwrap()
+}
+
+// The translation of file by imp compile, None if the target does not
+// support the program
+fn compile(target: &str, file: &Path) -> Option<String> {
+    let out = common::imp(&["compile", "--target", target])
+        .arg(file)
+        .output()
+        .unwrap();
+    let err = String::from_utf8(out.stderr).unwrap();
+    if err.contains("not supported by the") {
+        return None;
+    }
+    assert!(out.status.success(), "{}: {}", file.display(), err);
+    Some(String::from_utf8(out.stdout).unwrap())
+}
+
+// Both print the same output and exit with the same code, errors of the
+// compiled program lack the file name
+fn assert_agrees(file: &Path, run: Output, compiled: Output) {
+    assert_eq!(compiled.stdout, run.stdout, "{}", file.display());
+    assert_eq!(
+        compiled.status.code(),
+        run.status.code(),
+        "{}",
+        file.display()
+    );
+    let err = String::from_utf8(compiled.stderr).unwrap();
+    let expected = String::from_utf8(run.stderr).unwrap();
+    if !expected.is_empty() {
+        assert_eq!(format!("{}: {}", file.display(), err), expected);
+    }
+}
+
+// The C programs are built with the system C compiler
+#[test]
+fn c_agrees_with_run() {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("c");
+    fs::create_dir_all(&dir).unwrap();
+    let mut compiled = 0;
+    for file in programs() {
+        let Some(c) = compile("c", &file) else {
+            continue;
+        };
+        let name = file.file_stem().unwrap().to_str().unwrap();
+        let src = dir.join(format!("{}.c", name));
+        let exe = dir.join(name);
+        fs::write(&src, c).unwrap();
+        let cc = Command::new("cc")
+            .args(["-std=c99", "-pedantic-errors", "-o"])
+            .arg(&exe)
+            .arg(&src)
+            .output()
+            .unwrap();
+        let err = String::from_utf8(cc.stderr).unwrap();
+        assert!(cc.status.success(), "{}: {}", src.display(), err);
+        let out = Command::new(&exe).args(INPUT).output().unwrap();
+        assert_agrees(&file, run(&file), out);
+        compiled += 1;
+    }
+    assert!(compiled >= 6, "{}", compiled);
+}
+
+// Programs with type errors are not translated, the first error is the
+// one named in the first line of the file
+#[test]
+fn type_errors() {
+    let dir = common::path("run/types");
+    for file in common::imp_files(&dir) {
+        let src = fs::read_to_string(&file).unwrap();
+        let expected = src
+            .lines()
+            .next()
+            .unwrap()
+            .strip_prefix("// error: ")
+            .unwrap();
+        let out = common::imp(&["compile", "--target", "c"])
+            .arg(&file)
+            .output()
+            .unwrap();
+        let err = String::from_utf8(out.stderr).unwrap();
+        assert_eq!(out.status.code(), Some(1), "{}", file.display());
+        assert_eq!(
+            err.lines().next().unwrap(),
+            format!("{}:{}", file.display(), expected)
+        );
+    }
+}
+
+// Compares the module of every tests/compile/*.imp with the golden file
+// next to it. UPDATE_GOLDEN=1 rewrites the golden files.
+#[test]
+fn wat_golden_files() {
+    for file in common::imp_files(&common::path("compile")) {
+        let actual = compile("wat", &file).unwrap();
+        common::golden(&file.with_extension("wat"), &actual);
+    }
+}
+
+fn validate(file: &Path) -> Output {
+    common::imp(&["validate"]).arg(file).output().unwrap()
+}
+
+// The modules of all programs the WAT backend translates are valid
+#[test]
+fn wat_modules_validate() {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("wat");
+    fs::create_dir_all(&dir).unwrap();
+    let mut compiled = 0;
+    for file in programs() {
+        let Some(wat) = compile("wat", &file) else {
+            continue;
+        };
+        let module = dir.join(file.with_extension("wat").file_name().unwrap());
+        fs::write(&module, wat).unwrap();
+        let out = validate(&module);
+        let err = String::from_utf8(out.stderr).unwrap();
+        assert!(out.status.success(), "{}", err);
+        compiled += 1;
+    }
+    assert!(compiled >= 6, "{}", compiled);
+}
+
+// Every module in tests/compile/invalid has the error of its first line
+#[test]
+fn invalid_wat_modules() {
+    for file in fs::read_dir(common::path("compile/invalid"))
+        .unwrap()
+        .map(|e| e.unwrap().path())
+    {
+        let text = fs::read_to_string(&file).unwrap();
+        let expected = text
+            .lines()
+            .next()
+            .unwrap()
+            .strip_prefix(";; error: ")
+            .unwrap();
+        let out = validate(&file);
+        let err = String::from_utf8(out.stderr).unwrap();
+        assert_eq!(out.status.code(), Some(1), "{}", file.display());
+        assert_eq!(err, format!("{}: {}\n", file.display(), expected));
+    }
+}
+
+// Assembles and links the programs the x86-64 backend translates and
+// compares them with imp run
+#[test]
+#[cfg(all(target_arch = "x86_64", target_os = "linux"))]
+fn x86_agrees_with_run() {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("x86");
+    fs::create_dir_all(&dir).unwrap();
+    let mut compiled = 0;
+    for file in programs() {
+        let Some(asm) = compile("x86-64", &file) else {
+            continue;
+        };
+        let name = file.file_stem().unwrap().to_str().unwrap();
+        let src = dir.join(format!("{}.s", name));
+        let obj = dir.join(format!("{}.o", name));
+        let exe = dir.join(name);
+        fs::write(&src, asm).unwrap();
+        for tool in [
+            Command::new("as").arg("-o").arg(&obj).arg(&src).output(),
+            Command::new("ld").arg("-o").arg(&exe).arg(&obj).output(),
+        ] {
+            let tool = tool.unwrap();
+            let err = String::from_utf8(tool.stderr).unwrap();
+            assert!(tool.status.success(), "{}: {}", src.display(), err);
+        }
+        let out = Command::new(&exe).args(INPUT).output().unwrap();
+        assert_agrees(&file, run(&file), out);
+        compiled += 1;
+    }
+    assert!(compiled >= 6, "{}", compiled);
+}
@@ -0,0 +1,8 @@
+;; error: func $main: unknown function $f_fact
+(module
+  (func $main
+    i32.const 1
+    call $f_fact
+    drop
+  )
+)
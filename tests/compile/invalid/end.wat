@@ -0,0 +1,9 @@
+;; error: func $main: block without end
+(module
+  (func $main
+    block $exit1
+      loop $head1
+        br $exit1
+    end
+  )
+)
@@ -0,0 +1,8 @@
+;; error: func $main: global $depth is immutable
+(module
+  (global $depth i32 (i32.const 0))
+  (func $main
+    i32.const 1
+    global.set $depth
+  )
+)
@@ -0,0 +1,11 @@
+;; error: func $main: unknown label $exit2
+(module
+  (func $main
+    block $exit1
+      loop $head1
+        i32.const 1
+        br_if $exit2
+      end
+    end
+  )
+)
@@ -0,0 +1,9 @@
+;; error: func $main: 1 values left on the stack at end
+(module
+  (func $main
+    i32.const 1
+    if
+      i32.const 2
+    end
+  )
+)
@@ -0,0 +1,8 @@
+;; error: func $main: unknown local $v_y
+(module
+  (func $main (export "main")
+    (local $v_x i32)
+    i32.const 1
+    local.set $v_y
+  )
+)
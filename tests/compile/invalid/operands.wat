@@ -0,0 +1,7 @@
+;; error: func $f: i32.add needs 2 operands
+(module
+  (func $f (param $v_x i32) (result i32)
+    local.get $v_x
+    i32.add
+  )
+)
@@ -0,0 +1,8 @@
+;; error: func $f: the function ends with 0 values on the stack instead of 1
+(module
+  (import "imp" "print_i32" (func $print_i32 (param i32)))
+  (func $f (param $v_x i32) (result i32)
+    local.get $v_x
+    call $print_i32
+  )
+)
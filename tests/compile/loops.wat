@@ -0,0 +1,199 @@
+(module
+  (import "imp" "print_i32" (func $print_i32 (param i32)))
+  (import "imp" "print_bool" (func $print_bool (param i32)))
+  (import "imp" "read_i32" (func $read_i32 (result i32)))
+  (import "imp" "read_bool" (func $read_bool (result i32)))
+  (import "imp" "assert_failed" (func $assert_failed (param i32)))
+  (import "imp" "no_return" (func $no_return (param i32)))
+  (import "imp" "stack_overflow" (func $stack_overflow))
+  (import "imp" "overflow" (func $overflow (param i32 i32 i32)))
+  (global $depth (mut i32) (i32.const 0))
+  (func $add (param $a i32) (param $b i32) (result i32)
+    (local $r i32)
+    local.get $a
+    local.get $b
+    i32.add
+    local.set $r
+    ;; both operands differ from the result in the sign
+    local.get $a
+    local.get $r
+    i32.xor
+    local.get $b
+    local.get $r
+    i32.xor
+    i32.and
+    i32.const 0
+    i32.lt_s
+    if
+      local.get $a
+      i32.const 43
+      local.get $b
+      call $overflow
+      unreachable
+    end
+    local.get $r
+  )
+  (func $mul (param $a i32) (param $b i32) (result i32)
+    (local $r i32)
+    local.get $a
+    local.get $b
+    i32.mul
+    local.set $r
+    ;; r / a is b unless the product overflows, -1 * -2147483648 traps
+    local.get $a
+    if
+      local.get $a
+      i32.const -1
+      i32.eq
+      if
+        local.get $b
+        i32.const -2147483648
+        i32.eq
+        if
+          local.get $a
+          i32.const 42
+          local.get $b
+          call $overflow
+          unreachable
+        end
+      else
+        local.get $r
+        local.get $a
+        i32.div_s
+        local.get $b
+        i32.ne
+        if
+          local.get $a
+          i32.const 42
+          local.get $b
+          call $overflow
+          unreachable
+        end
+      end
+    end
+    local.get $r
+  )
+  (func $f_below (param $v_i i32) (param $v_n i32) (result i32)
+    global.get $depth
+    i32.const 1000
+    i32.eq
+    if
+      call $stack_overflow
+      unreachable
+    end
+    global.get $depth
+    i32.const 1
+    i32.add
+    global.set $depth
+    local.get $v_i
+    call $print_i32
+    local.get $v_i
+    local.get $v_n
+    i32.lt_s
+    global.get $depth
+    i32.const 1
+    i32.sub
+    global.set $depth
+    return
+    i32.const 0
+    call $no_return
+    unreachable
+  )
+  (func $main (export "main")
+    (local $v_s i32)
+    (local $v_i i32)
+    (local $v_k i32)
+    (local $v_b i32)
+    i32.const 0
+    local.set $v_s
+    i32.const 0
+    local.set $v_i
+    block $exit1
+      loop $head1
+        local.get $v_i
+        i32.const 10
+        i32.lt_s
+        i32.eqz
+        br_if $exit1
+        block $body1
+          local.get $v_i
+          i32.const 3
+          i32.eq
+          local.get $v_i
+          i32.const 5
+          i32.eq
+          i32.or
+          if
+            br $body1
+          end
+          local.get $v_i
+          i32.const 8
+          i32.eq
+          if
+            br $exit1
+          end
+          local.get $v_s
+          local.get $v_i
+          call $add
+          local.set $v_s
+        end
+        local.get $v_i
+        i32.const 1
+        call $add
+        local.set $v_i
+        br $head1
+      end
+    end
+    ;; assertion 0: (s==20)
+    local.get $v_s
+    i32.const 20
+    i32.eq
+    i32.eqz
+    if
+      i32.const 0
+      call $assert_failed
+      unreachable
+    end
+    i32.const 0
+    local.set $v_k
+    block $exit2
+      loop $head2
+        local.get $v_k
+        i32.const 3
+        call $f_below
+        i32.eqz
+        br_if $exit2
+        local.get $v_k
+        i32.const 1
+        call $add
+        local.set $v_k
+        br $head2
+      end
+    end
+    ;; assertion 1: ((k==3)&&(!((k<3))))
+    local.get $v_k
+    i32.const 3
+    i32.eq
+    local.get $v_k
+    i32.const 3
+    i32.lt_s
+    i32.eqz
+    i32.and
+    i32.eqz
+    if
+      i32.const 1
+      call $assert_failed
+      unreachable
+    end
+    i32.const 7
+    i32.const 1
+    call $f_below
+    i32.const 8
+    i32.const 1
+    call $f_below
+    i32.and
+    local.set $v_b
+    local.get $v_b
+    call $print_bool
+  )
+)
@@ -0,0 +1,122 @@
+(module
+  (import "imp" "print_i32" (func $print_i32 (param i32)))
+  (import "imp" "print_bool" (func $print_bool (param i32)))
+  (import "imp" "read_i32" (func $read_i32 (result i32)))
+  (import "imp" "read_bool" (func $read_bool (result i32)))
+  (import "imp" "assert_failed" (func $assert_failed (param i32)))
+  (import "imp" "no_return" (func $no_return (param i32)))
+  (import "imp" "stack_overflow" (func $stack_overflow))
+  (import "imp" "overflow" (func $overflow (param i32 i32 i32)))
+  (global $depth (mut i32) (i32.const 0))
+  (func $add (param $a i32) (param $b i32) (result i32)
+    (local $r i32)
+    local.get $a
+    local.get $b
+    i32.add
+    local.set $r
+    ;; both operands differ from the result in the sign
+    local.get $a
+    local.get $r
+    i32.xor
+    local.get $b
+    local.get $r
+    i32.xor
+    i32.and
+    i32.const 0
+    i32.lt_s
+    if
+      local.get $a
+      i32.const 43
+      local.get $b
+      call $overflow
+      unreachable
+    end
+    local.get $r
+  )
+  (func $mul (param $a i32) (param $b i32) (result i32)
+    (local $r i32)
+    local.get $a
+    local.get $b
+    i32.mul
+    local.set $r
+    ;; r / a is b unless the product overflows, -1 * -2147483648 traps
+    local.get $a
+    if
+      local.get $a
+      i32.const -1
+      i32.eq
+      if
+        local.get $b
+        i32.const -2147483648
+        i32.eq
+        if
+          local.get $a
+          i32.const 42
+          local.get $b
+          call $overflow
+          unreachable
+        end
+      else
+        local.get $r
+        local.get $a
+        i32.div_s
+        local.get $b
+        i32.ne
+        if
+          local.get $a
+          i32.const 42
+          local.get $b
+          call $overflow
+          unreachable
+        end
+      end
+    end
+    local.get $r
+  )
+  (func $f_show (param $v_x i32) (result i32)
+    global.get $depth
+    i32.const 1000
+    i32.eq
+    if
+      call $stack_overflow
+      unreachable
+    end
+    global.get $depth
+    i32.const 1
+    i32.add
+    global.set $depth
+    local.get $v_x
+    call $print_i32
+    local.get $v_x
+    global.get $depth
+    i32.const 1
+    i32.sub
+    global.set $depth
+    return
+    i32.const 0
+    call $no_return
+    unreachable
+  )
+  (func $main (export "main")
+    (local $v_x i32)
+    (local $v_y i32)
+    i32.const 46341
+    local.set $v_x
+    local.get $v_x
+    i32.const 46340
+    call $mul
+    call $print_i32
+    i32.const 2
+    call $f_show
+    local.get $v_x
+    local.get $v_x
+    call $mul
+    call $add
+    i32.const 3
+    call $f_show
+    call $add
+    local.set $v_y
+    local.get $v_y
+    call $print_i32
+  )
+)
@@ -0,0 +1,68 @@
+mod common;
+
+use std::io::Write;
+use std::process::Stdio;
+
+fn debug(file: &str, commands: &str) -> String {
+    debug_with_input(file, &[], commands)
+}
+
+// Debugs the program tests/run/file, which reads input, the values after
+// "--"
+fn debug_with_input(file: &str, input: &[&str], commands: &str) -> String {
+    let file = common::path("run").join(file);
+    let mut child = common::imp(&["debug"])
+        .arg(&file)
+        .arg("--")
+        .args(input)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(commands.as_bytes())
+        .unwrap();
+    let out = child.wait_with_output().unwrap();
+    assert!(out.status.success());
+    String::from_utf8(out.stdout).unwrap()
+}
+
+#[test]
+fn breakpoint_and_print() {
+    let out = debug("sum.imp", "b 5\nc\np i\nc\np\nq\n");
+    assert!(out.contains("breakpoint at 5:3 [#3]"), "{}", out);
+    assert!(out.contains("breakpoint 5:3 [#3]: i = (i+1)"), "{}", out);
+    assert!(out.contains("i = 0\n"), "{}", out);
+    assert!(out.contains("i = 1\ns = 1\n"), "{}", out);
+    assert!(!out.contains("program finished"), "{}", out);
+}
+
+#[test]
+fn watchpoint() {
+    let out = debug("sum.imp", "s\ns\nw s\nc\nc\n");
+    assert!(out.contains("watching s = 0"), "{}", out);
+    assert!(out.contains("watch s: 0 -> 1\n"), "{}", out);
+    assert!(out.contains("watch s: 1 -> 3\n"), "{}", out);
+    assert!(out.contains("program finished"), "{}", out);
+}
+
+#[test]
+fn next_steps_over_loops() {
+    let out = debug("sum.imp", "n\nn\nn\nn\n");
+    assert!(out.contains("4:1 [#2]: while"), "{}", out);
+    assert!(out.contains("8:1 [#5]: assert (s==55)"), "{}", out);
+    assert!(!out.contains("[#3]"), "{}", out);
+}
+
+// The program reads the values after "--", stdin has the commands
+#[test]
+fn program_input() {
+    let input = ["sum", "2", "3", "4", "true"];
+    let out = debug_with_input("input/total.imp", &input, "b 10\nc\np n\nc\n");
+    assert!(out.contains("n = 2\n"), "{}", out);
+    assert!(out.contains("Output sum\nOuput 7\n"), "{}", out);
+    assert!(out.contains("program finished"), "{}", out);
+}
@@ -0,0 +1,62 @@
+mod common;
+
+use std::path::Path;
+
+fn derive(file: &Path) -> String {
+    let out = common::imp(&["derive"]).arg(file).output().unwrap();
+    let err = String::from_utf8(out.stderr).unwrap();
+    assert!(out.status.success(), "{}: {}", file.display(), err);
+    String::from_utf8(out.stdout).unwrap()
+}
+
+// Compares the LaTeX document of every tests/derive/*.imp with the golden
+// file next to it. UPDATE_GOLDEN=1 rewrites the golden files.
+#[test]
+fn derive_golden_files() {
+    for file in common::imp_files(&common::path("derive")) {
+        common::golden(&file.with_extension("tex"), &derive(&file));
+    }
+}
+
+// The loop runs twice and takes both branches of the if, so the evaluation
+// tree uses both rules of while and of if; the typing and the evaluation
+// tree are each one prooftree
+#[test]
+fn loop_and_if_rules() {
+    let file = common::path("derive").join("loop.imp");
+    let tex = derive(&file);
+    for rule in [
+        "T-While",
+        "T-If",
+        "E-While-True",
+        "E-While-False",
+        "E-If-True",
+        "E-If-False",
+    ] {
+        let label = format!("\\RightLabel{{\\scriptsize {}}}", rule);
+        assert!(tex.contains(&label), "{}", rule);
+    }
+    assert_eq!(tex.matches("\\begin{prooftree}").count(), 2);
+    assert_eq!(tex.matches("\\end{prooftree}").count(), 2);
+    assert!(tex.ends_with("\\end{document}\n"));
+}
+
+// Every subexpression is evaluated once, so is the call and its print
+#[test]
+fn calls_are_evaluated_once() {
+    let file = common::path("derive").join("call.imp");
+    let tex = derive(&file);
+    assert_eq!(tex.matches("Ouput 1\n").count(), 1, "{}", tex);
+}
+
+// A runtime error, also in the condition of an if or while, ends the
+// derivation with the error of imp run
+#[test]
+fn runtime_errors() {
+    for file in common::imp_files(&common::path("run/overflow")) {
+        let run = common::imp(&["run"]).arg(&file).output().unwrap();
+        let derive = common::imp(&["derive"]).arg(&file).output().unwrap();
+        assert_eq!(derive.status.code(), Some(1), "{}", file.display());
+        assert_eq!(derive.stderr, run.stderr, "{}", file.display());
+    }
+}
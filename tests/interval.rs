@@ -0,0 +1,85 @@
+mod common;
+
+use std::path::Path;
+
+// Runs in the directory of file, so that the output has its plain name
+fn interval(file: &Path) -> String {
+    let out = common::imp(&["interval"])
+        .current_dir(file.parent().unwrap())
+        .arg(file.file_name().unwrap())
+        .output()
+        .unwrap();
+    assert!(out.status.success(), "{}", file.display());
+    String::from_utf8(out.stdout).unwrap()
+}
+
+fn example(name: &str) -> String {
+    interval(&common::path("interval").join(name))
+}
+
+// Compares the states of every tests/interval/*.imp with the golden file
+// next to it. UPDATE_GOLDEN=1 rewrites the golden files.
+#[test]
+fn interval_golden_files() {
+    for file in common::imp_files(&common::path("interval")) {
+        common::golden(&file.with_extension("out"), &interval(&file));
+    }
+}
+
+// After an if the interval spans the values of both branches
+#[test]
+fn join_of_branches() {
+    let out = example("join.imp");
+    assert!(out.contains("end: {x: [1, 5]}"), "{}", out);
+}
+
+// The bound of a counter is widened to +inf and narrowed back to the loop
+// condition, so the loop head, the body and the exit are all exact
+#[test]
+fn loop_counter_bounds() {
+    let out = example("counter.imp");
+    assert!(out.contains("4:1: {i: [0, 10]}"), "{}", out);
+    assert!(out.contains("5:3: {i: [0, 9]}"), "{}", out);
+    assert!(out.contains("end: {i: [10, 10]}"), "{}", out);
+    assert!(!out.contains("overflow"), "{}", out);
+}
+
+// Without a bound in the condition the widened bound stays infinite
+#[test]
+fn widening_without_bound() {
+    let out = example("widen.imp");
+    assert!(
+        out.contains("5:1: {i: [0, +inf], n: [-inf, +inf]}"),
+        "{}",
+        out
+    );
+    assert!(out.contains("widen.imp:6:3: possible i32 overflow in (i+1)"));
+}
+
+// Narrowing takes the bound from i < n, which keeps i + 1 within i32, and
+// the comparison with i leaves n unbounded
+#[test]
+fn narrowing_by_condition() {
+    let out = example("unbounded.imp");
+    assert!(
+        out.contains("5:3: {i: [0, 2147483646], n: [1, +inf]}"),
+        "{}",
+        out
+    );
+    assert!(out.contains("end: {i: [0, 2147483647], n: [-inf, +inf]}"));
+    assert!(!out.contains("overflow"), "{}", out);
+}
+
+// An exact bound at the limit of i32 is a number, not infinity
+#[test]
+fn exact_bound_at_limit() {
+    let out = example("limit.imp");
+    assert!(
+        out.contains("3:1: {x: [2147483647, 2147483647]}"),
+        "{}",
+        out
+    );
+    assert!(out.contains("7:3: unreachable"), "{}", out);
+    assert!(!out.contains("inf"), "{}", out);
+    assert!(out.contains("limit.imp:9:1: possible i32 overflow in (x+1)"));
+}
@@ -0,0 +1,66 @@
+mod common;
+
+use std::path::Path;
+use std::process::Output;
+
+// imp args file with the input 3 4 5
+fn with_input(args: &[&str], file: &Path) -> Output {
+    common::imp(args)
+        .arg(file)
+        .args(["--", "3", "4", "5"])
+        .output()
+        .unwrap()
+}
+
+// The IR in SSA form prints the same output and fails with the same errors
+// as Stmt::exec, except for the programs with closures which it rejects
+#[test]
+fn ir_agrees_with_run() {
+    let mut lowered = 0;
+    for dir in ["run", "run/bounds", "run/fail", "run/input", "run/stack"] {
+        for file in common::imp_files(&common::path(dir)) {
+            let ir = with_input(&["ir", "--run"], &file);
+            let err = String::from_utf8(ir.stderr).unwrap();
+            if err.contains("not supported by the IR") {
+                continue;
+            }
+            let run = with_input(&["run"], &file);
+            assert_eq!(ir.status.code(), run.status.code(), "{}", file.display());
+            assert_eq!(ir.stdout, run.stdout, "{}", file.display());
+            assert_eq!(err, String::from_utf8(run.stderr).unwrap());
+            lowered += 1;
+        }
+    }
+    assert!(lowered >= 10, "{}", lowered);
+}
+
+// Compares the SSA form of every tests/ir/*.imp with the golden file next
+// to it. UPDATE_GOLDEN=1 rewrites the golden files.
+#[test]
+fn ssa_golden_files() {
+    for file in common::imp_files(&common::path("ir")) {
+        let out = common::imp(&["ir"]).arg(&file).output().unwrap();
+        assert!(out.status.success(), "{}", file.display());
+        let actual = String::from_utf8(out.stdout).unwrap();
+        common::golden(&file.with_extension("ir"), &actual);
+    }
+}
+
+// Every variable of the SSA form is assigned once
+#[test]
+fn single_assignment() {
+    for file in common::imp_files(&common::path("run")) {
+        let out = common::imp(&["ir"]).arg(&file).output().unwrap();
+        let ir = String::from_utf8(out.stdout).unwrap();
+        let mut assigned = Vec::new();
+        for line in ir.lines() {
+            if line.ends_with(") {") {
+                assigned.clear();
+            }
+            if let Some((x, _)) = line.trim().split_once(" = ") {
+                assert!(!assigned.contains(&x), "{}: {}", file.display(), x);
+                assigned.push(x);
+            }
+        }
+    }
+}
@@ -0,0 +1,76 @@
+mod common;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Output;
+
+fn lint(file: &Path, allow: &[&str]) -> Output {
+    let mut cmd = common::imp(&["lint"]);
+    for code in allow {
+        cmd.args(["--allow", code]);
+    }
+    cmd.arg(file).output().unwrap()
+}
+
+fn example(name: &str) -> PathBuf {
+    common::path("lint").join(name)
+}
+
+// A program whose first line is "// warning: L:C: msg" gets exactly this
+// warning, all others none
+#[test]
+fn warnings() {
+    for file in common::imp_files(&common::path("lint")) {
+        let src = fs::read_to_string(&file).unwrap();
+        let out = lint(&file, &[]);
+        let actual = String::from_utf8(out.stdout).unwrap();
+        match src.lines().next().unwrap().strip_prefix("// warning: ") {
+            Some(expected) => {
+                assert_eq!(out.status.code(), Some(1), "{}", file.display());
+                assert_eq!(actual, format!("{}:{}\n", file.display(), expected));
+            }
+            None => {
+                assert_eq!(out.status.code(), Some(0), "{}", file.display());
+                assert_eq!(actual, "", "{}", file.display());
+            }
+        }
+    }
+}
+
+// Every code has a program which gets its warning and one in which a
+// comment suppresses it; without the comment it gets the warning again
+#[test]
+fn suppressed_by_comment() {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("lint");
+    fs::create_dir_all(&dir).unwrap();
+    for (code, name) in [
+        ("W001", "unused"),
+        ("W002", "dead"),
+        ("W003", "branch"),
+        ("W004", "loop"),
+    ] {
+        let src = fs::read_to_string(example(&format!("{}.imp", name))).unwrap();
+        assert!(src.contains(&format!("warning[{}]", code)), "{}", name);
+        let src = fs::read_to_string(example(&format!("{}_allowed.imp", name))).unwrap();
+        assert!(src.contains("lint: allow("), "{}", name);
+        let file = dir.join(format!("{}.imp", name));
+        fs::write(&file, src.replace("lint: allow(", "lint: deny(")).unwrap();
+        let out = lint(&file, &[]);
+        let out = String::from_utf8(out.stdout).unwrap();
+        assert!(out.contains(&format!("warning[{}]", code)), "{}", out);
+    }
+}
+
+// --allow takes a code or a name and leaves the other codes alone
+#[test]
+fn allow_flag() {
+    let out = lint(&example("unused.imp"), &["W001"]);
+    assert_eq!(out.status.code(), Some(0));
+    assert!(out.stdout.is_empty());
+    let out = lint(&example("dead.imp"), &["dead-store"]);
+    assert_eq!(out.status.code(), Some(0));
+    let out = lint(&example("branch.imp"), &["W001", "infinite-loop"]);
+    assert_eq!(out.status.code(), Some(1));
+    let out = String::from_utf8(out.stdout).unwrap();
+    assert!(out.contains("warning[W003]"), "{}", out);
+}
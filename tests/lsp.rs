@@ -0,0 +1,346 @@
+mod common;
+
+use std::io::Write;
+use std::process::Stdio;
+
+const URI: &str = "file:///test.imp";
+
+// Runs imp lsp with the given messages, wrapped in initialize and
+// shutdown/exit, and returns the messages sent by the server
+fn session(src: &str, requests: &[String]) -> Vec<String> {
+    let open = format!(
+        r#"{{"jsonrpc":"2.0","method":"textDocument/didOpen","params":{{"textDocument":{{"uri":"{}","languageId":"imp","version":1,"text":"{}"}}}}}}"#,
+        URI,
+        src.replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+    );
+    let mut msgs = vec![
+        r#"{"jsonrpc":"2.0","id":0,"method":"initialize","params":{}}"#.to_string(),
+        r#"{"jsonrpc":"2.0","method":"initialized","params":{}}"#.to_string(),
+        open,
+    ];
+    msgs.extend(requests.iter().cloned());
+    msgs.push(r#"{"jsonrpc":"2.0","id":99,"method":"shutdown"}"#.to_string());
+    msgs.push(r#"{"jsonrpc":"2.0","method":"exit"}"#.to_string());
+    let mut child = common::imp(&["lsp"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    for m in &msgs {
+        write!(stdin, "Content-Length: {}\r\n\r\n{}", m.len(), m).unwrap();
+    }
+    drop(stdin);
+    let out = child.wait_with_output().unwrap();
+    assert!(out.status.success());
+    let mut rest = String::from_utf8(out.stdout).unwrap();
+    let mut replies = Vec::new();
+    while let Some(i) = rest.find("\r\n\r\n") {
+        let len: usize = rest[..i]
+            .strip_prefix("Content-Length: ")
+            .unwrap()
+            .parse()
+            .unwrap();
+        replies.push(rest[i + 4..i + 4 + len].to_string());
+        rest = rest[i + 4 + len..].to_string();
+    }
+    assert!(rest.is_empty());
+    replies
+}
+
+fn at(id: usize, method: &str, line: usize, character: usize) -> String {
+    format!(
+        r#"{{"jsonrpc":"2.0","id":{},"method":"textDocument/{}","params":{{"textDocument":{{"uri":"{}"}},"position":{{"line":{},"character":{}}}}}}}"#,
+        id, method, URI, line, character
+    )
+}
+
+fn reply(replies: &[String], id: usize) -> &str {
+    let key = format!(r#""id":{},"#, id);
+    replies.iter().find(|r| r.contains(&key)).unwrap()
+}
+
+#[test]
+fn diagnostics() {
+    let replies = session("x := 1;\ny := x + true;\nz = 3", &[]);
+    let diags = replies
+        .iter()
+        .find(|r| r.contains("publishDiagnostics"))
+        .unwrap();
+    assert!(
+        diags.contains(r#""message":"ill-typed expression (x+true)""#),
+        "{}",
+        diags
+    );
+    assert!(diags.contains(r#""start":{"line":1,"character":5},"end":{"line":1,"character":13}"#));
+    assert!(
+        diags.contains(r#""message":"undeclared variable z""#),
+        "{}",
+        diags
+    );
+
+    let replies = session("x := ;", &[]);
+    assert!(
+        replies[1].contains("expected an expression, found ';'"),
+        "{}",
+        replies[1]
+    );
+}
+
+#[test]
+fn procedures() {
+    let src = "proc f(n: int): int {\n  return n < 1\n}\nx := f(1, 2);\nreturn 3";
+    let replies = session(src, &[at(1, "hover", 3, 6)]);
+    let diags = replies
+        .iter()
+        .find(|r| r.contains("publishDiagnostics"))
+        .unwrap();
+    for msg in [
+        "return type must be int, found bool",
+        "ill-typed expression f(1, 2)",
+        "return outside of a procedure",
+    ] {
+        assert!(diags.contains(msg), "{}: {}", msg, diags);
+    }
+    // a call with the wrong arity has no type
+    assert!(
+        reply(&replies, 1).contains(r#""value":"f(1, 2): illtyped""#),
+        "{}",
+        reply(&replies, 1)
+    );
+}
+
+#[test]
+fn headers() {
+    let src = "proc f(): int {\n  skip\n}\nproc g(x: int, x: bool): int {\n  return 1\n}\nproc f(): int {\n  return 2\n}\nskip";
+    let replies = session(src, &[]);
+    let diags = replies
+        .iter()
+        .find(|r| r.contains("publishDiagnostics"))
+        .unwrap();
+    for msg in [
+        "procedure f may end without return",
+        "duplicate parameter x of g",
+        "duplicate procedure f",
+    ] {
+        assert!(diags.contains(msg), "{}: {}", msg, diags);
+    }
+    assert!(
+        diags.contains(r#""start":{"line":0,"character":5},"end":{"line":0,"character":6}"#),
+        "{}",
+        diags
+    );
+}
+
+#[test]
+fn lambdas() {
+    let src = "k := 1;\nf := fn(x: int) => x + k;\ng := fn(b: bool) => b + 1";
+    let replies = session(
+        src,
+        &[
+            at(1, "hover", 1, 19),
+            at(2, "hover", 1, 5),
+            at(3, "definition", 1, 19),
+        ],
+    );
+    assert!(
+        reply(&replies, 1).contains(r#""value":"x: int""#),
+        "{}",
+        reply(&replies, 1)
+    );
+    assert!(
+        reply(&replies, 2).contains(r#""value":"(fn(x: int) => (x+k)): int -> int""#),
+        "{}",
+        reply(&replies, 2)
+    );
+    // a parameter has no Decl
+    assert!(reply(&replies, 3).contains(r#""result":null"#));
+    let diags = replies
+        .iter()
+        .find(|r| r.contains("publishDiagnostics"))
+        .unwrap();
+    assert!(diags.contains("ill-typed expression (b+1)"), "{}", diags);
+}
+
+#[test]
+fn arrays() {
+    let src = "a := [1, 2];\na[0] = true;\nb := 1;\nb[0] = 2";
+    let replies = session(src, &[at(1, "hover", 0, 5), at(2, "definition", 1, 0)]);
+    assert!(
+        reply(&replies, 1).contains(r#""value":"[1, 2]: array<int>""#),
+        "{}",
+        reply(&replies, 1)
+    );
+    assert!(reply(&replies, 2).contains(r#""start":{"line":0,"character":0}"#));
+    let diags = replies
+        .iter()
+        .find(|r| r.contains("publishDiagnostics"))
+        .unwrap();
+    assert!(
+        diags.contains("cannot assign bool to element of a of type array<int>"),
+        "{}",
+        diags
+    );
+    assert!(diags.contains("b of type int is not an array"), "{}", diags);
+}
+
+#[test]
+fn records() {
+    let src = "record P { x: int }\np := P { x: 1 };\np.x = true;\np.y = 1;\nn := 1;\nn.x = 2;\nq := P { x: 1, y: 2 }";
+    let replies = session(src, &[at(1, "hover", 1, 5), at(2, "definition", 2, 0)]);
+    assert!(
+        reply(&replies, 1).contains(r#""value":"P { x: 1 }: P""#),
+        "{}",
+        reply(&replies, 1)
+    );
+    assert!(reply(&replies, 2).contains(r#""start":{"line":1,"character":0}"#));
+    let diags = replies
+        .iter()
+        .find(|r| r.contains("publishDiagnostics"))
+        .unwrap();
+    for msg in [
+        "cannot assign bool to p.x of type int",
+        "record P has no field y",
+        "n of type int is not a record",
+        "ill-typed expression P { x: 1, y: 2 }",
+    ] {
+        assert!(diags.contains(msg), "{}: {}", msg, diags);
+    }
+}
+
+#[test]
+fn loops() {
+    let src =
+        "s := 0;\nfor i := 0; i < 3; i = i + 1 {\n  s = s + i;\n  continue\n};\nbreak;\ns = true";
+    let replies = session(src, &[at(1, "hover", 1, 23), at(2, "definition", 2, 10)]);
+    assert!(
+        reply(&replies, 1).contains(r#""value":"i: int""#),
+        "{}",
+        reply(&replies, 1)
+    );
+    assert!(reply(&replies, 2).contains(r#""start":{"line":1,"character":4}"#));
+    let diags = replies
+        .iter()
+        .find(|r| r.contains("publishDiagnostics"))
+        .unwrap();
+    assert!(diags.contains("break outside of a loop"), "{}", diags);
+    assert!(!diags.contains("continue"), "{}", diags);
+    // the next statement after the loop is still checked
+    assert!(
+        diags.contains("cannot assign bool to s of type int"),
+        "{}",
+        diags
+    );
+}
+
+#[test]
+fn annotations() {
+    let src = "n: int := true;\nvar b: bool;\nb = n;\nm: bool := b";
+    let replies = session(src, &[at(1, "definition", 3, 11)]);
+    assert!(
+        reply(&replies, 1)
+            .contains(r#""start":{"line":1,"character":4},"end":{"line":1,"character":5}"#),
+        "{}",
+        reply(&replies, 1)
+    );
+    let diags = replies
+        .iter()
+        .find(|r| r.contains("publishDiagnostics"))
+        .unwrap();
+    assert!(
+        diags.contains("n is declared as int but initialized with bool"),
+        "{}",
+        diags
+    );
+    // n has the annotated type after the error
+    assert!(
+        diags.contains("cannot assign int to b of type bool"),
+        "{}",
+        diags
+    );
+    assert!(!diags.contains("m is declared"), "{}", diags);
+}
+
+#[test]
+fn inference() {
+    let src = "id := fn (x) => x;\nn := id(1) + id(2);\nb := id(true) + 1;\nbreak";
+    let replies = session(src, &[]);
+    let diags = replies
+        .iter()
+        .find(|r| r.contains("publishDiagnostics"))
+        .unwrap();
+    assert!(
+        diags.contains(r#""start":{"line":2,"character":5},"end":{"line":2,"character":17}"#),
+        "{}",
+        diags
+    );
+    assert!(
+        diags.contains("operands of different types bool and int"),
+        "{}",
+        diags
+    );
+    assert!(diags.contains("break outside of a loop"), "{}", diags);
+    assert_eq!(diags.matches("\"message\"").count(), 2, "{}", diags);
+}
+
+#[test]
+fn strings() {
+    let src = "s := \"a\" + \"b\";\nx := s + 1";
+    let replies = session(src, &[at(1, "hover", 0, 9)]);
+    assert!(
+        reply(&replies, 1).contains(r#""value":"(\"a\"+\"b\"): string""#),
+        "{}",
+        reply(&replies, 1)
+    );
+    let diags = replies
+        .iter()
+        .find(|r| r.contains("publishDiagnostics"))
+        .unwrap();
+    assert!(diags.contains("ill-typed expression (s+1)"), "{}", diags);
+
+    let replies = session("s := \"abc", &[]);
+    assert!(replies[1].contains("unterminated string"), "{}", replies[1]);
+}
+
+#[test]
+fn hover_and_definition() {
+    let src = "x := 1;\nb := x < 2;\nif b { x = x + 1 } else { skip }";
+    let replies = session(
+        src,
+        &[
+            at(1, "hover", 1, 5),
+            at(2, "hover", 1, 7),
+            at(3, "definition", 2, 11),
+        ],
+    );
+    assert!(reply(&replies, 1).contains(r#""value":"x: int""#));
+    assert!(reply(&replies, 2).contains(r#""value":"(x<2): bool""#));
+    let def = reply(&replies, 3);
+    assert!(
+        def.contains(r#""start":{"line":0,"character":0},"end":{"line":0,"character":1}"#),
+        "{}",
+        def
+    );
+}
+
+#[test]
+fn formatting() {
+    let req = format!(
+        r#"{{"jsonrpc":"2.0","id":1,"method":"textDocument/formatting","params":{{"textDocument":{{"uri":"{}"}},"options":{{"tabSize":2,"insertSpaces":true}}}}}}"#,
+        URI
+    );
+    let replies = session(
+        "x := 1; while x < 3 { x = x + 1 }",
+        std::slice::from_ref(&req),
+    );
+    assert!(
+        reply(&replies, 1).contains(r#""newText":"x:= 1;\nwhile (x<3) {\n  x = (x+1)\n}\n""#),
+        "{}",
+        reply(&replies, 1)
+    );
+    // the pretty printer would drop the comment
+    let replies = session("// keep me\nx := 1", &[req]);
+    assert!(reply(&replies, 1).contains(r#""result":null"#));
+}
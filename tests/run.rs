@@ -0,0 +1,252 @@
+mod common;
+
+use std::fs;
+use std::io::Write;
+use std::process::Stdio;
+
+// The programs in tests/run check their results with assert
+#[test]
+fn self_checking_programs() {
+    let dir = common::path("run");
+    for file in common::imp_files(&dir) {
+        let out = common::imp(&["run"]).arg(&file).output().unwrap();
+        let err = String::from_utf8(out.stderr).unwrap();
+        assert!(out.status.success(), "{}: {}", file.display(), err);
+    }
+}
+
+// The small-step tracer prints the same output as eval and reaches the end
+#[test]
+fn trace_agrees_with_run() {
+    let dir = common::path("run");
+    for file in common::imp_files(&dir) {
+        let run = common::imp(&["run"]).arg(&file).output().unwrap();
+        let trace = common::imp(&["trace"]).arg(&file).output().unwrap();
+        assert!(trace.status.success(), "{}", file.display());
+        let run = String::from_utf8(run.stdout).unwrap();
+        let trace = String::from_utf8(trace.stdout).unwrap();
+        let output: Vec<&str> = trace
+            .lines()
+            .filter(|l| !l.chars().next().unwrap().is_ascii_digit())
+            .collect();
+        let run: Vec<&str> = run.lines().collect();
+        assert_eq!(output, run, "{}", file.display());
+        assert!(trace.lines().last().unwrap().contains(": skip | "));
+    }
+}
+
+// On a runtime error, also in the condition of an if, the tracer stops
+// with the same error and output as eval
+#[test]
+fn trace_fails_like_run() {
+    let root = common::path("run");
+    for dir in ["bounds", "fail", "overflow"] {
+        for file in common::imp_files(&root.join(dir)) {
+            let run = common::imp(&["run"]).arg(&file).output().unwrap();
+            let trace = common::imp(&["trace"]).arg(&file).output().unwrap();
+            assert_eq!(trace.status.code(), Some(1), "{}", file.display());
+            assert_eq!(trace.stderr, run.stderr, "{}", file.display());
+            let trace = String::from_utf8(trace.stdout).unwrap();
+            let output: Vec<&str> = trace
+                .lines()
+                .filter(|l| !l.chars().next().unwrap().is_ascii_digit())
+                .collect();
+            let run = String::from_utf8(run.stdout).unwrap();
+            assert_eq!(
+                output,
+                run.lines().collect::<Vec<_>>(),
+                "{}",
+                file.display()
+            );
+        }
+    }
+}
+
+// Every program in tests/run/fail violates an assertion
+#[test]
+fn failing_assertions() {
+    let dir = common::path("run/fail");
+    for file in common::imp_files(&dir) {
+        let out = common::imp(&["run"]).arg(&file).output().unwrap();
+        let err = String::from_utf8(out.stderr).unwrap();
+        assert_eq!(out.status.code(), Some(1), "{}", file.display());
+        assert!(err.contains("assertion failed: "), "{}", err);
+    }
+}
+
+// A program which does not terminate stops with the partial state
+#[test]
+fn out_of_fuel() {
+    let file = common::path("run/fuel/forever.imp");
+    let out = common::imp(&["run", "--fuel", "1000"])
+        .arg(&file)
+        .output()
+        .unwrap();
+    let err = String::from_utf8(out.stderr).unwrap();
+    assert_eq!(out.status.code(), Some(1));
+    assert!(err.contains("out of fuel after 1000 steps"), "{}", err);
+    assert!(err.contains("state: x = 499"), "{}", err);
+}
+
+// Unbounded recursion stops at the call depth limit
+#[test]
+fn stack_overflow() {
+    let file = common::path("run/stack/down.imp");
+    let out = common::imp(&["run"]).arg(&file).output().unwrap();
+    let err = String::from_utf8(out.stderr).unwrap();
+    assert_eq!(out.status.code(), Some(1));
+    assert!(
+        err.contains("stack overflow after 1000 nested calls"),
+        "{}",
+        err
+    );
+}
+
+// Indexing past the end of an array is a runtime error
+#[test]
+fn index_out_of_bounds() {
+    let file = common::path("run/bounds/oob.imp");
+    let out = common::imp(&["run"]).arg(&file).output().unwrap();
+    let err = String::from_utf8(out.stderr).unwrap();
+    assert_eq!(out.status.code(), Some(1));
+    assert!(
+        err.contains("index 3 out of bounds for array of length 3"),
+        "{}",
+        err
+    );
+}
+
+// Coverage counts statements and branches of tests/run/sum.imp
+#[test]
+fn coverage_report() {
+    let file = common::path("run/sum.imp");
+    let lcov = std::env::temp_dir().join(format!("imp-coverage-{}.info", std::process::id()));
+    let out = common::imp(&["coverage"])
+        .arg("--lcov")
+        .arg(&lcov)
+        .arg(&file)
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    let report = String::from_utf8(out.stdout).unwrap();
+    assert!(
+        report.contains("      1 | while (i<10) {  // body: 10"),
+        "{}",
+        report
+    );
+    assert!(report.contains("     10 |   i = (i+1);"), "{}", report);
+    let info = fs::read_to_string(&lcov).unwrap();
+    fs::remove_file(&lcov).unwrap();
+    for l in [
+        "DA:4,1",
+        "DA:5,10",
+        "BRDA:4,2,0,10",
+        "BRDA:4,2,1,1",
+        "LH:7",
+        "end_of_record",
+    ] {
+        assert!(info.lines().any(|x| x == l), "{}: {}", l, info);
+    }
+}
+
+// Input is read from the arguments after "--" or else from stdin
+#[test]
+fn program_input() {
+    let file = common::path("run/input/total.imp");
+    let out = common::imp(&["run"])
+        .arg(&file)
+        .args(["--", "sum", "3", "1", "2", "3", "true"])
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    let lines: Vec<String> = String::from_utf8(out.stdout)
+        .unwrap()
+        .lines()
+        .map(|l| l.to_string())
+        .collect();
+    assert_eq!(lines, ["Output sum", "Ouput 6"]);
+    let mut child = common::imp(&["trace"])
+        .arg(&file)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let text = "a b\n2\n 10\n5\nfalse\n";
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(text.as_bytes())
+        .unwrap();
+    let out = child.wait_with_output().unwrap();
+    assert!(out.status.success());
+    let trace = String::from_utf8(out.stdout).unwrap();
+    assert!(trace.contains("Ouput 15"), "{}", trace);
+    assert!(!trace.contains("Output a b"), "{}", trace);
+    let out = common::imp(&["run"])
+        .arg(&file)
+        .args(["--", "sum", "two"])
+        .output()
+        .unwrap();
+    let err = String::from_utf8(out.stderr).unwrap();
+    assert_eq!(out.status.code(), Some(1));
+    assert!(err.contains("cannot read \"two\" as int"), "{}", err);
+    let out = common::imp(&["coverage"])
+        .arg(&file)
+        .args(["--", "sum", "2", "3", "4", "false"])
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    let report = String::from_utf8(out.stdout).unwrap();
+    assert!(report.starts_with("Ouput 7\n"), "{}", report);
+    assert!(report.contains("then: 0, else: 1"), "{}", report);
+}
+
+// A program with type errors is not run by any of the subcommands which
+// execute programs, the first error is the one named in the first line of
+// the file
+#[test]
+fn type_errors() {
+    let dir = common::path("run/types");
+    for file in common::imp_files(&dir) {
+        let src = fs::read_to_string(&file).unwrap();
+        let expected = src
+            .lines()
+            .next()
+            .unwrap()
+            .strip_prefix("// error: ")
+            .unwrap();
+        for cmd in [
+            &["run"][..],
+            &["trace"],
+            &["derive"],
+            &["debug"],
+            &["coverage"],
+            &["ir", "--run"],
+        ] {
+            let out = common::imp(cmd)
+                .arg(&file)
+                .stdin(Stdio::null())
+                .output()
+                .unwrap();
+            let err = String::from_utf8(out.stderr).unwrap();
+            assert_eq!(out.status.code(), Some(1), "{:?} {}", cmd, file.display());
+            assert!(out.stdout.is_empty(), "{:?} {}", cmd, file.display());
+            assert_eq!(
+                err.lines().next().unwrap(),
+                format!("{}:{}", file.display(), expected)
+            );
+        }
+    }
+}
+
+// A result of + or * which is no i32 is a runtime error
+#[test]
+fn integer_overflow() {
+    let file = common::path("run/overflow/square.imp");
+    let out = common::imp(&["run"]).arg(&file).output().unwrap();
+    let err = String::from_utf8(out.stderr).unwrap();
+    assert_eq!(out.status.code(), Some(1));
+    assert_eq!(String::from_utf8(out.stdout).unwrap(), "Ouput 2147441940\n");
+    assert!(err.contains("integer overflow in 46341 * 46341"), "{}", err);
+}
@@ -0,0 +1,17 @@
+mod common;
+
+// Compares the output of `imp smt --unroll 2` for every tests/smt/*.imp with
+// the golden file next to it. UPDATE_GOLDEN=1 rewrites the golden files.
+#[test]
+fn smt_golden_files() {
+    let dir = common::path("smt");
+    for file in common::imp_files(&dir) {
+        let out = common::imp(&["smt", "--unroll", "2"])
+            .arg(&file)
+            .output()
+            .unwrap();
+        assert!(out.status.success(), "{}", file.display());
+        let actual = String::from_utf8(out.stdout).unwrap();
+        common::golden(&file.with_extension("smt2"), &actual);
+    }
+}
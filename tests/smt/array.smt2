@@ -0,0 +1,13 @@
+(set-option :produce-models true)
+(set-logic ALL)
+(declare-const x_0 Int)
+(declare-const y_0 Int)
+(define-fun a_1 () (Seq Int) (seq.++ (seq.unit x_0) (seq.unit 2) (seq.unit 3)))
+(define-fun a_2 () (Seq Int) (seq.update a_1 1 (seq.unit (+ (seq.nth a_1 0) (seq.len a_1)))))
+(define-fun b_1 () (Seq Int) (seq.update a_2 2 (seq.unit y_0)))
+; assert (b[1]==(x+3))
+(push 1)
+(assert (and true (not (= (seq.nth b_1 1) (+ x_0 3)))))
+(check-sat)
+(get-model)
+(pop 1)
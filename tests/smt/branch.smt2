@@ -0,0 +1,22 @@
+(set-option :produce-models true)
+(set-logic QF_LIA)
+(declare-const x_0 Int)
+(define-fun y_1 () Int (+ x_0 1))
+(define-fun c_1 () Bool (< y_1 10))
+(define-fun g_2 () Bool c_1)
+(define-fun g_3 () Bool (not c_1))
+(define-fun y_2 () Int (* y_1 2))
+(define-fun z_1 () Bool true)
+(define-fun y_3 () Int (ite c_1 y_2 y_1))
+; print y
+(push 1)
+(assert g_3)
+(check-sat)
+(get-model)
+(pop 1)
+; print y
+(push 1)
+(assert true)
+(check-sat)
+(get-model)
+(pop 1)
@@ -0,0 +1,50 @@
+(set-option :produce-models true)
+(set-logic QF_LIA)
+(declare-const n_0 Int)
+(define-fun s_1 () Int 0)
+(define-fun i_1 () Int 0)
+(define-fun c_1 () Bool (< i_1 n_0))
+(define-fun g_2 () Bool c_1)
+(define-fun c_3 () Bool (= i_1 1))
+(define-fun g_4 () Bool (and g_2 c_3))
+(define-fun g_5 () Bool (and g_2 (not c_3)))
+(define-fun g_6 () Bool (or false g_5))
+(define-fun c_7 () Bool (< s_1 i_1))
+(define-fun g_8 () Bool (and g_6 c_7))
+(define-fun g_9 () Bool (and g_6 (not c_7)))
+(define-fun g_10 () Bool (or false g_9))
+(define-fun s_2 () Int (+ s_1 i_1))
+(define-fun s_3 () Int (ite g_4 s_1 s_2))
+(define-fun g_11 () Bool (or g_10 g_4))
+(define-fun i_2 () Int (+ i_1 1))
+(define-fun c_12 () Bool (< i_2 n_0))
+(define-fun g_13 () Bool (and g_11 c_12))
+(define-fun c_14 () Bool (= i_2 1))
+(define-fun g_15 () Bool (and g_13 c_14))
+(define-fun g_16 () Bool (and g_13 (not c_14)))
+(define-fun g_17 () Bool (or false g_16))
+(define-fun c_18 () Bool (< s_3 i_2))
+(define-fun g_19 () Bool (and g_17 c_18))
+(define-fun g_20 () Bool (and g_17 (not c_18)))
+(define-fun g_21 () Bool (or false g_20))
+(define-fun s_4 () Int (+ s_3 i_2))
+(define-fun s_5 () Int (ite g_15 s_3 s_4))
+(define-fun g_22 () Bool (or g_21 g_15))
+(define-fun i_3 () Int (+ i_2 1))
+(define-fun c_23 () Bool (< i_3 n_0))
+(define-fun i_4 () Int (ite c_12 i_3 i_2))
+(define-fun s_6 () Int (ite c_12 s_5 s_3))
+(define-fun i_5 () Int (ite g_19 i_2 i_4))
+(define-fun s_7 () Int (ite g_19 s_3 s_6))
+(define-fun i_6 () Int (ite c_1 i_5 i_1))
+(define-fun s_8 () Int (ite c_1 s_7 s_1))
+(define-fun i_7 () Int (ite g_8 i_1 i_6))
+(define-fun s_9 () Int (ite g_8 s_1 s_8))
+; unwinding assumption
+(assert (not (and g_22 c_23)))
+; assert (s<3)
+(push 1)
+(assert (and true (not (< s_9 3))))
+(check-sat)
+(get-model)
+(pop 1)
@@ -0,0 +1,13 @@
+(set-option :produce-models true)
+(set-logic ALL)
+(declare-const y_0 Int)
+(define-fun k_1 () Int 2)
+(define-fun scale_1 () (Array Int Int) (lambda ((x_arg Int)) (* x_arg k_1)))
+(define-fun k_2 () Int 3)
+(define-fun pos_1 () (Array Int Bool) (lambda ((x_arg Int)) (< 0 x_arg)))
+; assert pos(scale(y))
+(push 1)
+(assert (and true (not (select pos_1 (select scale_1 y_0)))))
+(check-sat)
+(get-model)
+(pop 1)
@@ -0,0 +1,16 @@
+(set-option :produce-models true)
+(set-logic QF_LIA)
+(define-fun x_1 () Int (+ 1 (* 2 3)))
+(define-fun b_1 () Bool (and (not (< x_1 4)) (or true (= x_1 7))))
+; print ((x+1))
+(push 1)
+(assert true)
+(check-sat)
+(get-model)
+(pop 1)
+; print (b==false)
+(push 1)
+(assert true)
+(check-sat)
+(get-model)
+(pop 1)
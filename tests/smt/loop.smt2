@@ -0,0 +1,26 @@
+(set-option :produce-models true)
+(set-logic QF_LIA)
+(declare-const n_0 Int)
+(define-fun i_1 () Int 0)
+(define-fun s_1 () Int 0)
+(define-fun c_1 () Bool (< i_1 n_0))
+(define-fun g_2 () Bool c_1)
+(define-fun s_2 () Int (+ s_1 i_1))
+(define-fun i_2 () Int (+ i_1 1))
+(define-fun c_3 () Bool (< i_2 n_0))
+(define-fun g_4 () Bool (and g_2 c_3))
+(define-fun s_3 () Int (+ s_2 i_2))
+(define-fun i_3 () Int (+ i_2 1))
+(define-fun c_5 () Bool (< i_3 n_0))
+(define-fun i_4 () Int (ite c_3 i_3 i_2))
+(define-fun s_4 () Int (ite c_3 s_3 s_2))
+(define-fun i_5 () Int (ite c_1 i_4 i_1))
+(define-fun s_5 () Int (ite c_1 s_4 s_1))
+; unwinding assumption
+(assert (not (and g_4 c_5)))
+; print s
+(push 1)
+(assert true)
+(check-sat)
+(get-model)
+(pop 1)
@@ -0,0 +1,20 @@
+(set-option :produce-models true)
+(set-logic QF_NIA)
+(declare-const x_0 Int)
+(declare-const y_0 Int)
+(declare-const b_0 Bool)
+(define-fun c_1 () Bool (and (= (* x_0 y_0) 12) b_0))
+(define-fun g_2 () Bool c_1)
+(define-fun g_3 () Bool (not c_1))
+; print x
+(push 1)
+(assert g_2)
+(check-sat)
+(get-model)
+(pop 1)
+; print y
+(push 1)
+(assert g_3)
+(check-sat)
+(get-model)
+(pop 1)
@@ -0,0 +1,18 @@
+(set-option :produce-models true)
+(set-logic ALL)
+(declare-datatypes ((Point 0) (Box 0)) (((mk.Point (Point.x Int) (Point.y Int))) ((mk.Box (Box.min Point) (Box.max Point)))))
+(declare-const a_0 Int)
+(define-fun p_1 () Point (mk.Point a_0 2))
+(define-fun p_2 () Point (mk.Point (+ (Point.x p_1) 1) (Point.y p_1)))
+(define-fun b_1 () Box (mk.Box (mk.Point (Point.x p_2) 0) p_2))
+(define-fun c_1 () Bool (< (Point.x (Box.min b_1)) (Point.y (Box.max b_1))))
+(define-fun g_2 () Bool c_1)
+(define-fun g_3 () Bool (not c_1))
+(define-fun b_2 () Box (mk.Box (Box.min b_1) (Box.min b_1)))
+(define-fun b_3 () Box (ite c_1 b_2 b_1))
+; assert ((b.max.y==2)||(b.max.y==0))
+(push 1)
+(assert (and true (not (or (= (Point.y (Box.max b_3)) 2) (= (Point.y (Box.max b_3)) 0)))))
+(check-sat)
+(get-model)
+(pop 1)
@@ -0,0 +1,16 @@
+(set-option :produce-models true)
+(set-logic ALL)
+(declare-const name_0 String)
+(define-fun greeting_1 () String (str.++ "Hello, " name_0))
+; assert (len(greeting)==(len(name)+7))
+(push 1)
+(assert (and true (not (= (str.len greeting_1) (+ (str.len name_0) 7)))))
+(check-sat)
+(get-model)
+(pop 1)
+; assert (("a"<name)||(name=="\"a\""))
+(push 1)
+(assert (and true (not (or (str.< "a" name_0) (= name_0 """a""")))))
+(check-sat)
+(get-model)
+(pop 1)
@@ -0,0 +1,75 @@
+mod common;
+
+use std::path::Path;
+use std::process::Output;
+
+// Runs in the directory of file, so that the output has its plain name
+fn symbolic(file: &Path) -> Output {
+    common::imp(&["symbolic", "--unroll", "2"])
+        .current_dir(file.parent().unwrap())
+        .arg(file.file_name().unwrap())
+        .output()
+        .unwrap()
+}
+
+// Compares the paths of every tests/symbolic/*.imp with the golden file
+// next to it. UPDATE_GOLDEN=1 rewrites the golden files.
+#[test]
+fn symbolic_golden_files() {
+    let dir = common::path("symbolic");
+    for file in common::imp_files(&dir) {
+        let out = symbolic(&file);
+        assert!(out.status.success(), "{}", file.display());
+        let actual = String::from_utf8(out.stdout).unwrap();
+        common::golden(&file.with_extension("out"), &actual);
+    }
+}
+
+// The solver decides the conditions of all paths, which are linear
+#[test]
+fn paths_are_decided() {
+    let dir = common::path("symbolic");
+    for file in common::imp_files(&dir) {
+        let out = String::from_utf8(symbolic(&file).stdout).unwrap();
+        assert!(
+            !out.contains("inputs: unknown"),
+            "{}: {}",
+            file.display(),
+            out
+        );
+    }
+}
+
+// An unsatisfiable branch is dropped, its print is never reached
+#[test]
+fn unsat_branches() {
+    let file = common::path("symbolic/unsat.imp");
+    let out = String::from_utf8(symbolic(&file).stdout).unwrap();
+    assert_eq!(out.matches("path ").count(), 1, "{}", out);
+    assert!(out.contains("unsat.imp:3:3: print not reached"), "{}", out);
+    assert!(out.contains("unsat.imp:8:3: print not reached"), "{}", out);
+}
+
+// Two branches after each other and a loop unrolled twice give four paths
+// for every number of iterations, those which go on are cut
+#[test]
+fn path_enumeration() {
+    let file = common::path("symbolic/paths.imp");
+    let out = String::from_utf8(symbolic(&file).stdout).unwrap();
+    assert_eq!(out.matches("path ").count(), 12, "{}", out);
+    assert!(
+        out.contains("4 paths cut after 2 loop unrollings"),
+        "{}",
+        out
+    );
+}
+
+// Inputs without an upper bound are found, and so is the violation of an
+// upper bound
+#[test]
+fn unbounded_inputs() {
+    let file = common::path("symbolic/unbounded.imp");
+    let out = String::from_utf8(symbolic(&file).stdout).unwrap();
+    assert!(out.contains("print reached with x = 101"), "{}", out);
+    assert!(out.contains("assertion fails with x = 1000"), "{}", out);
+}
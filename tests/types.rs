@@ -0,0 +1,25 @@
+mod common;
+
+// Compares the output of `imp types` for every tests/types/*.imp with the
+// golden file next to it. UPDATE_GOLDEN=1 rewrites the golden files.
+#[test]
+fn types_golden_files() {
+    let dir = common::path("types");
+    for file in common::imp_files(&dir) {
+        let out = common::imp(&["types"]).arg(&file).output().unwrap();
+        let actual = String::from_utf8(out.stdout).unwrap();
+        // paths in error messages are relative to the test directory
+        let actual = actual.replace(&format!("{}/", dir.display()), "");
+        common::golden(&file.with_extension("types"), &actual);
+    }
+}
+
+// Programs whose lambdas are all annotated type as with Stmt::check
+#[test]
+fn first_order_programs() {
+    for file in common::imp_files(&common::path("run")) {
+        let out = common::imp(&["types"]).arg(&file).output().unwrap();
+        let stdout = String::from_utf8(out.stdout).unwrap();
+        assert!(out.status.success(), "{}: {}", file.display(), stdout);
+    }
+}
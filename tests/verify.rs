@@ -0,0 +1,81 @@
+mod common;
+
+use std::path::{Path, PathBuf};
+use std::process::Output;
+
+// Runs in the directory of file, so that the output has its plain name
+fn verify(file: &Path) -> Output {
+    common::imp(&["verify"])
+        .current_dir(file.parent().unwrap())
+        .arg(file.file_name().unwrap())
+        .output()
+        .unwrap()
+}
+
+fn example(name: &str) -> PathBuf {
+    common::path("verify").join(name)
+}
+
+// Compares the verification conditions of every tests/verify/*.imp and
+// their status with the golden file next to it. UPDATE_GOLDEN=1 rewrites
+// the golden files.
+#[test]
+fn verify_golden_files() {
+    for file in common::imp_files(&common::path("verify")) {
+        let out = verify(&file);
+        let actual = String::from_utf8(out.stdout).unwrap();
+        common::golden(&file.with_extension("out"), &actual);
+    }
+}
+
+// Every condition of a correct program with a loop invariant is proved
+#[test]
+fn valid_program() {
+    let out = verify(&example("valid.imp"));
+    let text = String::from_utf8(out.stdout).unwrap();
+    assert!(out.status.success(), "{}", text);
+    assert_eq!(text.matches(": proved\n").count(), 4, "{}", text);
+}
+
+// A violated assertion gives a counterexample and exit code 1
+#[test]
+fn invalid_program() {
+    let out = verify(&example("invalid.imp"));
+    let text = String::from_utf8(out.stdout).unwrap();
+    assert_eq!(out.status.code(), Some(1));
+    assert!(
+        text.contains("precondition of the program holds: fails with x = 6"),
+        "{}",
+        text
+    );
+}
+
+// An invariant which holds on entry but not after the body is reported for
+// the loop
+#[test]
+fn invariant_not_preserved() {
+    let out = verify(&example("invariant.imp"));
+    let text = String::from_utf8(out.stdout).unwrap();
+    assert_eq!(out.status.code(), Some(1));
+    assert!(
+        text.contains(
+            "invariant.imp:3:1: invariant is preserved by the loop body: fails with i = 4"
+        ),
+        "{}",
+        text
+    );
+    assert!(
+        text.contains("invariant.imp: precondition of the program holds: trivial"),
+        "{}",
+        text
+    );
+}
+
+// Closed sums which overflow are left to the solver
+#[test]
+fn overflow_is_not_folded() {
+    let out = verify(&example("overflow.imp"));
+    let text = String::from_utf8(out.stdout).unwrap();
+    assert!(out.status.success(), "{}", text);
+    assert!(text.contains("(2147483647<(2147483647+1))"), "{}", text);
+}